@@ -0,0 +1,27 @@
+//! Embed the git commit in the binary so bench reports can name the code
+//! that produced them.
+
+use std::process::Command;
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=GIT_HASH");
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs");
+    // An explicit GIT_HASH wins (e.g. builds from a source tarball in CI).
+    let commit = std::env::var("GIT_HASH")
+        .ok()
+        .filter(|hash| !hash.is_empty())
+        .or_else(|| {
+            let output = Command::new("git")
+                .args(["rev-parse", "--short=12", "HEAD"])
+                .output()
+                .ok()?;
+            if !output.status.success() {
+                return None;
+            }
+            let hash = String::from_utf8(output.stdout).ok()?;
+            Some(hash.trim().to_string()).filter(|hash| !hash.is_empty())
+        })
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=BLAZE_GIT_COMMIT={commit}");
+}
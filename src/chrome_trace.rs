@@ -0,0 +1,81 @@
+//! Zone occupancy spans exported in the Chrome Trace Event format, for
+//! viewing a run as a timeline in `chrome://tracing` or Perfetto.
+//!
+//! Each span becomes a `B`/`E` duration-event pair on the robot's own track
+//! (`pid` and `tid` are both the robot id), named after the zone.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::types::{RobotId, TaskId, ZoneId};
+
+/// One robot's hold on a zone for a task, in microseconds from run start.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ZoneSpan {
+    pub robot: RobotId,
+    pub zone: ZoneId,
+    pub task: TaskId,
+    pub begin_us: u64,
+    pub end_us: u64,
+}
+
+/// Render spans as a `{"traceEvents": [...]}` JSON document.
+///
+/// Every field is numeric or a fixed zone label, so no escaping is needed.
+pub fn render(spans: &[ZoneSpan]) -> String {
+    let mut events = Vec::with_capacity(spans.len() * 2);
+    for span in spans {
+        for (phase, ts) in [("B", span.begin_us), ("E", span.end_us)] {
+            events.push(format!(
+                r#"{{"name":"zone {}","cat":"zone","ph":"{phase}","ts":{ts},"pid":{},"tid":{},"args":{{"task":{}}}}}"#,
+                span.zone, span.robot, span.robot, span.task
+            ));
+        }
+    }
+    format!(
+        "{{\"displayTimeUnit\":\"ms\",\"traceEvents\":[\n{}\n]}}\n",
+        events.join(",\n")
+    )
+}
+
+/// Write the rendered trace to `path`.
+pub fn write(path: &Path, spans: &[ZoneSpan]) -> io::Result<()> {
+    fs::write(path, render(spans))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_pairs_begin_and_end_per_span() {
+        let spans = [
+            ZoneSpan {
+                robot: 0,
+                zone: 1,
+                task: 4,
+                begin_us: 10,
+                end_us: 25,
+            },
+            ZoneSpan {
+                robot: 1,
+                zone: 2,
+                task: 5,
+                begin_us: 12,
+                end_us: 30,
+            },
+        ];
+        let doc: serde_json::Value = serde_json::from_str(&render(&spans)).expect("valid JSON");
+        let events = doc["traceEvents"].as_array().expect("traceEvents array");
+        assert_eq!(events.len(), 4);
+        assert_eq!(events[0]["ph"], "B");
+        assert_eq!(events[1]["ph"], "E");
+        assert_eq!(events[1]["ts"], 25);
+        assert_eq!(events[2]["name"], "zone 2");
+        assert_eq!(events[3]["pid"], 1);
+        assert_eq!(events[3]["args"]["task"], 5);
+        let empty: serde_json::Value = serde_json::from_str(&render(&[])).expect("valid JSON");
+        assert!(empty["traceEvents"].as_array().unwrap().is_empty());
+    }
+}
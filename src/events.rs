@@ -0,0 +1,130 @@
+//! Structured per-task benchmark events and a JSON Lines sink for them.
+//!
+//! Each event is written as one JSON object per line with a `type` tag so a
+//! dashboard can `tail -f` the file while the run is in progress.
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::sync::Mutex;
+
+use crate::types::{RobotId, TaskId, ZoneId};
+
+/// One step in a task's lifecycle during a benchmark run.
+///
+/// `t_us` is measured from the start of the run.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(tag = "type", rename_all = "snake_case")
+)]
+pub enum BenchEvent {
+    Popped {
+        t_us: u64,
+        robot: RobotId,
+        task: TaskId,
+    },
+    Acquired {
+        t_us: u64,
+        robot: RobotId,
+        task: TaskId,
+        zone: ZoneId,
+        wait_us: u64,
+    },
+    Requeued {
+        t_us: u64,
+        robot: RobotId,
+        task: TaskId,
+        zone: ZoneId,
+    },
+    Released {
+        t_us: u64,
+        robot: RobotId,
+        task: TaskId,
+        zone: ZoneId,
+        held_us: u64,
+    },
+}
+
+impl BenchEvent {
+    /// Serialize as a single-line JSON object.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("bench event serialization failed")
+    }
+
+    /// Serialize as a single-line JSON object.
+    ///
+    /// Every field is numeric, so no string escaping is needed.
+    #[cfg(not(feature = "serde"))]
+    pub fn to_json(&self) -> String {
+        match *self {
+            BenchEvent::Popped { t_us, robot, task } => {
+                format!(r#"{{"type":"popped","t_us":{t_us},"robot":{robot},"task":{task}}}"#)
+            }
+            BenchEvent::Acquired {
+                t_us,
+                robot,
+                task,
+                zone,
+                wait_us,
+            } => format!(
+                r#"{{"type":"acquired","t_us":{t_us},"robot":{robot},"task":{task},"zone":{zone},"wait_us":{wait_us}}}"#
+            ),
+            BenchEvent::Requeued {
+                t_us,
+                robot,
+                task,
+                zone,
+            } => format!(
+                r#"{{"type":"requeued","t_us":{t_us},"robot":{robot},"task":{task},"zone":{zone}}}"#
+            ),
+            BenchEvent::Released {
+                t_us,
+                robot,
+                task,
+                zone,
+                held_us,
+            } => format!(
+                r#"{{"type":"released","t_us":{t_us},"robot":{robot},"task":{task},"zone":{zone},"held_us":{held_us}}}"#
+            ),
+        }
+    }
+}
+
+/// Shared JSON Lines writer that robots emit events into.
+pub struct EventSink {
+    out: Mutex<Box<dyn Write + Send>>,
+}
+
+impl EventSink {
+    /// Open a sink on a file path, or on stdout for `-`.
+    pub fn open(target: &str) -> io::Result<Self> {
+        let out: Box<dyn Write + Send> = if target == "-" {
+            Box::new(io::stdout())
+        } else {
+            Box::new(BufWriter::new(File::create(target)?))
+        };
+        Ok(Self {
+            out: Mutex::new(out),
+        })
+    }
+
+    /// Write one event as a line; I/O errors are reported once per event
+    /// rather than aborting the run.
+    pub fn emit(&self, event: &BenchEvent) {
+        let mut out = self.out.lock().expect("event sink mutex poisoned");
+        // Flush per line so tailing readers see events as they happen.
+        if let Err(err) = writeln!(out, "{}", event.to_json()).and_then(|_| out.flush()) {
+            eprintln!("# warning,events_jsonl,{err}");
+        }
+    }
+}
+
+// The writer itself has no useful debug form.
+impl fmt::Debug for EventSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventSink").finish_non_exhaustive()
+    }
+}
@@ -1,27 +1,91 @@
 //! Heartbeat tracking and offline detection for robots.
 
-use std::collections::{HashMap, HashSet};
-use std::sync::Mutex;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Condvar, Mutex};
 use std::time::{Duration, Instant};
 
+use crate::log_warn;
 use crate::types::RobotId;
 
+// Recent heartbeat intervals kept for timeout suggestions.
+const INTERVAL_WINDOW: usize = 1024;
+
 struct HealthState {
     last_seen: HashMap<RobotId, Instant>,
     offline: HashSet<RobotId>,
+    // Bumped each time a robot is newly marked offline, so waiters can tell
+    // a fresh mark from robots that were already offline.
+    offline_marks: u64,
+    // Heartbeats rejected because they predate the stored `last_seen`.
+    clock_regressions: u64,
+    // Gaps between consecutive heartbeats, newest last, across all robots.
+    intervals: VecDeque<Duration>,
+}
+
+/// Time source for heartbeat ages, so timeout logic can be tested without
+/// real time passing.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Instant;
+}
+
+/// The real monotonic clock; the default for every monitor.
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A clock that stands still until `advance` is called.
+pub struct MockClock {
+    now: Mutex<Instant>,
+}
+
+impl MockClock {
+    pub fn new() -> Self {
+        Self {
+            now: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Move the clock forward by `by`.
+    pub fn advance(&self, by: Duration) {
+        *self.now.lock().expect("mock clock mutex poisoned") += by;
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        *self.now.lock().expect("mock clock mutex poisoned")
+    }
+}
+
+/// How a robot looks to `HealthMonitor::robot_state`.
+#[allow(dead_code)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RobotHealthState {
+    Healthy,
+    /// Heartbeat overdue past the warn timeout: slow, but still dispatchable.
+    Degraded,
+    /// Past the dead timeout, marked offline, or never tracked.
+    Offline,
 }
 
 /// Tracks robot heartbeats and reports offline robots after a timeout.
 pub struct HealthMonitor {
     state: Mutex<HealthState>,
+    // Signaled whenever a robot is first marked offline.
+    went_offline: Condvar,
+    // (warn, dead) heartbeat ages for `robot_state`; unset means only
+    // explicit offline marks count.
+    thresholds: Option<(Duration, Duration)>,
+    clock: Arc<dyn Clock>,
 }
 
 impl HealthMonitor {
-    fn overdue_robots(
-        state: &HealthState,
-        now: Instant,
-        timeout: Duration,
-    ) -> Vec<RobotId> {
+    fn overdue_robots(state: &HealthState, now: Instant, timeout: Duration) -> Vec<RobotId> {
         state
             .last_seen
             .iter()
@@ -35,58 +99,295 @@ impl HealthMonitor {
             .collect()
     }
 
+    // Mark every overdue robot offline, waking offline waiters if any robot
+    // is newly offline.
+    fn mark_overdue(&self, state: &mut HealthState, now: Instant, timeout: Duration) {
+        let mut newly_offline = false;
+        for robot in Self::overdue_robots(state, now, timeout) {
+            if state.offline.insert(robot) {
+                state.offline_marks += 1;
+                newly_offline = true;
+            }
+        }
+        if newly_offline {
+            self.went_offline.notify_all();
+        }
+    }
+
+    // Sleep on the condvar, marking overdue robots at each wake, until
+    // `done` holds (true) or `deadline` passes (false). Each sleep lasts
+    // until the stalest still-online robot's heartbeat expires; heartbeats
+    // only push that moment later.
+    fn wait_offline_until(
+        &self,
+        timeout: Duration,
+        deadline: Instant,
+        done: impl Fn(&HealthState) -> bool,
+    ) -> bool {
+        let mut guard = self.state.lock().expect("health monitor mutex poisoned");
+        loop {
+            let now = self.clock.now();
+            self.mark_overdue(&mut guard, now, timeout);
+            if done(&guard) {
+                return true;
+            }
+            if now >= deadline {
+                return false;
+            }
+            let wake_at = guard
+                .last_seen
+                .iter()
+                .filter(|(robot, _)| !guard.offline.contains(robot))
+                .map(|(_, &seen)| seen)
+                .min()
+                .map_or(deadline, |stalest| (stalest + timeout).min(deadline));
+            // Step just past the expiry so the robot is strictly overdue.
+            let wait = wake_at.saturating_duration_since(now) + Duration::from_millis(1);
+            guard = self
+                .went_offline
+                .wait_timeout(guard, wait)
+                .expect("health monitor mutex poisoned")
+                .0;
+        }
+    }
+
     /// Create an empty health monitor.
     pub fn new() -> Self {
         Self {
             state: Mutex::new(HealthState {
                 last_seen: HashMap::new(),
                 offline: HashSet::new(),
+                offline_marks: 0,
+                clock_regressions: 0,
+                intervals: VecDeque::new(),
             }),
+            went_offline: Condvar::new(),
+            thresholds: None,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Create a monitor whose `robot_state` reports a robot `Degraded` once
+    /// its heartbeat is `warn_timeout` old and `Offline` at `dead_timeout`.
+    #[allow(dead_code)]
+    pub fn with_thresholds(warn_timeout: Duration, dead_timeout: Duration) -> Self {
+        debug_assert!(
+            warn_timeout <= dead_timeout,
+            "warn timeout must not exceed dead timeout"
+        );
+        Self {
+            thresholds: Some((warn_timeout, dead_timeout)),
+            ..Self::new()
+        }
+    }
+
+    /// Read heartbeat ages from `clock` instead of the system clock.
+    ///
+    /// The offline waits still sleep in real time between checks.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Classify `robot` by its heartbeat age against the configured
+    /// thresholds. An explicit offline mark always wins, and a robot that
+    /// was never tracked is `Offline`.
+    #[allow(dead_code)]
+    pub fn robot_state(&self, robot: RobotId) -> RobotHealthState {
+        let guard = self.state.lock().expect("health monitor mutex poisoned");
+        if guard.offline.contains(&robot) {
+            return RobotHealthState::Offline;
+        }
+        let Some(&last) = guard.last_seen.get(&robot) else {
+            return RobotHealthState::Offline;
+        };
+        let Some((warn_timeout, dead_timeout)) = self.thresholds else {
+            return RobotHealthState::Healthy;
+        };
+        let age = self.clock.now().duration_since(last);
+        if age > dead_timeout {
+            RobotHealthState::Offline
+        } else if age > warn_timeout {
+            RobotHealthState::Degraded
+        } else {
+            RobotHealthState::Healthy
         }
     }
 
     /// Ensure a robot is tracked; no-op if already registered.
+    #[allow(dead_code)]
     pub fn register_robot(&self, robot: RobotId) {
         let mut guard = self.state.lock().expect("health monitor mutex poisoned");
-        guard.last_seen.entry(robot).or_insert_with(Instant::now);
+        let now = self.clock.now();
+        guard.last_seen.entry(robot).or_insert(now);
+    }
+
+    /// Register many robots under one lock acquisition; already tracked
+    /// robots are left untouched.
+    pub fn register_robots(&self, robots: impl IntoIterator<Item = RobotId>) {
+        let mut guard = self.state.lock().expect("health monitor mutex poisoned");
+        let now = self.clock.now();
+        for robot in robots {
+            guard.last_seen.entry(robot).or_insert(now);
+        }
+    }
+
+    /// Stop tracking a robot that finished cleanly, so its silence is not
+    /// read as a failure; an existing offline mark is kept.
+    pub fn sign_off(&self, robot: RobotId) {
+        let mut guard = self.state.lock().expect("health monitor mutex poisoned");
+        guard.last_seen.remove(&robot);
     }
 
     /// Record a heartbeat; clears any prior offline mark for the robot.
-    pub fn heartbeat(&self, robot: RobotId) {
+    ///
+    /// Returns true if the robot was offline, i.e. this heartbeat recovered it.
+    pub fn heartbeat(&self, robot: RobotId) -> bool {
+        self.heartbeat_at(robot, self.clock.now())
+    }
+
+    // `Instant` never goes backwards, but a future wall-clock source could;
+    // a heartbeat older than `last_seen` is rejected with a warning.
+    fn heartbeat_at(&self, robot: RobotId, at: Instant) -> bool {
         let mut guard = self.state.lock().expect("health monitor mutex poisoned");
-        guard.last_seen.insert(robot, Instant::now());
-        guard.offline.remove(&robot);
+        if let Some(&last) = guard.last_seen.get(&robot)
+            && at < last
+        {
+            guard.clock_regressions += 1;
+            log_warn!(
+                "[HEALTH] heartbeat for robot {robot} is {}us older than last_seen; ignored",
+                last.duration_since(at).as_micros()
+            );
+            return false;
+        }
+        if let Some(last) = guard.last_seen.insert(robot, at) {
+            if guard.intervals.len() == INTERVAL_WINDOW {
+                guard.intervals.pop_front();
+            }
+            guard.intervals.push_back(at.duration_since(last));
+        }
+        guard.offline.remove(&robot)
+    }
+
+    /// Twice the p99 of recent heartbeat intervals: a timeout that the
+    /// observed cadence should not trip. `None` before any interval is seen.
+    pub fn suggested_timeout(&self) -> Option<Duration> {
+        let guard = self.state.lock().expect("health monitor mutex poisoned");
+        let mut intervals: Vec<Duration> = guard.intervals.iter().copied().collect();
+        drop(guard);
+        if intervals.is_empty() {
+            return None;
+        }
+        intervals.sort_unstable();
+        let p99_index = (intervals.len() * 99).div_ceil(100) - 1;
+        Some(intervals[p99_index] * 2)
+    }
+
+    /// Heartbeats rejected so far for predating a robot's `last_seen`.
+    #[allow(dead_code)]
+    pub fn clock_regressions(&self) -> u64 {
+        let guard = self.state.lock().expect("health monitor mutex poisoned");
+        guard.clock_regressions
     }
 
     /// Detect robots whose last heartbeat exceeds the timeout.
     pub fn detect_offline(&self, timeout: Duration) -> HashSet<RobotId> {
         let mut guard = self.state.lock().expect("health monitor mutex poisoned");
-        let now = Instant::now();
-        // Collect overdue robots first to avoid mutating while iterating.
-        let overdue = Self::overdue_robots(&guard, now, timeout);
-        for robot in overdue {
-            guard.offline.insert(robot);
-        }
+        self.mark_overdue(&mut guard, self.clock.now(), timeout);
         guard.offline.clone()
     }
 
     /// Detect offline robots and report whether any are offline.
+    #[allow(dead_code)]
     pub fn detect_offline_any(&self, timeout: Duration) -> bool {
         let mut guard = self.state.lock().expect("health monitor mutex poisoned");
-        let now = Instant::now();
-        let overdue = Self::overdue_robots(&guard, now, timeout);
-        for robot in overdue {
-            guard.offline.insert(robot);
-        }
+        self.mark_overdue(&mut guard, self.clock.now(), timeout);
         !guard.offline.is_empty()
     }
 
+    /// Block until some robot is offline or `deadline` passes; returns
+    /// whether any robot is offline.
+    ///
+    /// Instead of polling, this sleeps on a condvar until the stalest robot's
+    /// heartbeat expires (heartbeats only push that moment later) or another
+    /// caller marks a robot offline.
+    pub fn wait_for_any_offline(&self, timeout: Duration, deadline: Instant) -> bool {
+        self.wait_offline_until(timeout, deadline, |state| !state.offline.is_empty())
+    }
+
+    /// Block until a robot is newly marked offline after this call begins,
+    /// or `deadline` passes; returns whether one was.
+    ///
+    /// Robots already offline neither satisfy nor shorten the wait, so a
+    /// long-running watcher sleeps between expiries instead of spinning.
+    pub fn wait_for_new_offline(&self, timeout: Duration, deadline: Instant) -> bool {
+        let marks = {
+            let mut guard = self.state.lock().expect("health monitor mutex poisoned");
+            // Settle robots already overdue so only later expiries count.
+            self.mark_overdue(&mut guard, self.clock.now(), timeout);
+            guard.offline_marks
+        };
+        self.wait_offline_until(timeout, deadline, |state| state.offline_marks != marks)
+    }
+
     /// Snapshot of the robots currently marked offline.
     pub fn offline_robots(&self) -> HashSet<RobotId> {
         let guard = self.state.lock().expect("health monitor mutex poisoned");
         guard.offline.clone()
     }
 
+    /// Offline robots and the count of tracked robots still online, taken
+    /// under one lock so the two never disagree.
+    pub fn online_offline(&self) -> (usize, HashSet<RobotId>) {
+        let guard = self.state.lock().expect("health monitor mutex poisoned");
+        let online = guard
+            .last_seen
+            .keys()
+            .filter(|robot| !guard.offline.contains(robot))
+            .count();
+        (online, guard.offline.clone())
+    }
+
+    /// Union of this monitor's offline set with those of `others`.
+    ///
+    /// Each monitor is locked in turn, never all at once, so per-site
+    /// monitors compose without a global lock.
+    #[allow(dead_code)]
+    pub fn merge_offline(&self, others: &[&HealthMonitor]) -> HashSet<RobotId> {
+        let mut merged = self.offline_robots();
+        for other in others {
+            merged.extend(other.offline_robots());
+        }
+        merged
+    }
+
+    /// Robots tracked by any of the monitors that none of them marks offline.
+    #[allow(dead_code)]
+    pub fn combined_online_count(&self, others: &[&HealthMonitor]) -> usize {
+        let offline = self.merge_offline(others);
+        let mut tracked: HashSet<RobotId> = HashSet::new();
+        for monitor in std::iter::once(self).chain(others.iter().copied()) {
+            let guard = monitor.state.lock().expect("health monitor mutex poisoned");
+            tracked.extend(guard.last_seen.keys().copied());
+        }
+        tracked.difference(&offline).count()
+    }
+
+    /// Milliseconds since each robot's last heartbeat, measured at call time.
+    ///
+    /// Unlike raw `Instant`s these ages are serializable and comparable
+    /// outside this process.
+    #[allow(dead_code)]
+    pub fn last_seen_ms_ago(&self) -> HashMap<RobotId, u64> {
+        let guard = self.state.lock().expect("health monitor mutex poisoned");
+        let now = self.clock.now();
+        guard
+            .last_seen
+            .iter()
+            .map(|(&robot, &last)| (robot, now.duration_since(last).as_millis() as u64))
+            .collect()
+    }
+
     /// Test-only hook to set deterministic timestamps without sleeping.
     #[cfg(test)]
     fn set_last_seen_for_test(&self, robot: RobotId, instant: Instant) {
@@ -95,6 +396,23 @@ impl HealthMonitor {
     }
 }
 
+impl Default for HealthMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Summarize instead of dumping per-robot timestamps.
+impl fmt::Debug for HealthMonitor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let guard = self.state.lock().expect("health monitor mutex poisoned");
+        f.debug_struct("HealthMonitor")
+            .field("tracked", &guard.last_seen.len())
+            .field("offline", &guard.offline.len())
+            .finish()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -110,6 +428,24 @@ mod tests {
         assert!(offline.contains(&robot));
     }
 
+    #[test]
+    fn slow_robot_is_degraded_before_it_is_offline() {
+        let monitor =
+            HealthMonitor::with_thresholds(Duration::from_millis(100), Duration::from_secs(10));
+        let now = Instant::now();
+        monitor.set_last_seen_for_test(1, now - Duration::from_millis(500));
+        monitor.set_last_seen_for_test(2, now - Duration::from_secs(60));
+        monitor.heartbeat(3);
+        assert_eq!(monitor.robot_state(1), RobotHealthState::Degraded);
+        assert_eq!(monitor.robot_state(2), RobotHealthState::Offline);
+        assert_eq!(monitor.robot_state(3), RobotHealthState::Healthy);
+        assert_eq!(monitor.robot_state(99), RobotHealthState::Offline);
+
+        // A heartbeat brings the slow robot back to healthy.
+        monitor.heartbeat(1);
+        assert_eq!(monitor.robot_state(1), RobotHealthState::Healthy);
+    }
+
     #[test]
     fn marks_never_heartbeat_after_timeout() {
         let monitor = HealthMonitor::new();
@@ -128,11 +464,140 @@ mod tests {
         monitor.set_last_seen_for_test(robot, past);
         monitor.detect_offline(Duration::from_millis(5));
         assert!(monitor.offline_robots().contains(&robot));
-        // Heartbeat should clear the offline status.
-        monitor.heartbeat(robot);
+        // Heartbeat should clear the offline status and report the recovery.
+        assert!(monitor.heartbeat(robot));
+        assert!(!monitor.heartbeat(robot));
         assert!(!monitor.offline_robots().contains(&robot));
     }
 
+    #[test]
+    fn last_seen_ms_ago_reports_age() {
+        let monitor = HealthMonitor::new();
+        let past = Instant::now() - Duration::from_millis(200);
+        monitor.set_last_seen_for_test(3, past);
+        monitor.heartbeat(4);
+        let ages = monitor.last_seen_ms_ago();
+        let age = ages[&3];
+        assert!((200..1_000).contains(&age), "unexpected age {age}ms");
+        assert!(ages[&4] < 200);
+    }
+
+    #[test]
+    fn merge_offline_unions_per_site_monitors() {
+        let site_a = HealthMonitor::new();
+        let site_b = HealthMonitor::new();
+        let past = Instant::now() - Duration::from_secs(5);
+        site_a.set_last_seen_for_test(1, past);
+        site_a.heartbeat(2);
+        site_b.set_last_seen_for_test(3, past);
+        site_b.heartbeat(4);
+        site_a.detect_offline(Duration::from_secs(1));
+        site_b.detect_offline(Duration::from_secs(1));
+
+        let merged = site_a.merge_offline(&[&site_b]);
+        assert_eq!(merged, [1, 3].into_iter().collect());
+        assert_eq!(site_a.combined_online_count(&[&site_b]), 2);
+    }
+
+    #[test]
+    fn out_of_order_heartbeat_is_rejected_with_warning() {
+        let captured = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&captured);
+        crate::logging::set_warn_log_writer(move |line| {
+            sink.lock().unwrap().push(line.to_string());
+        });
+        let monitor = HealthMonitor::new();
+        let now = Instant::now();
+        monitor.set_last_seen_for_test(5, now);
+        assert!(!monitor.heartbeat_at(5, now - Duration::from_millis(10)));
+        crate::logging::clear_warn_log_writer();
+        assert_eq!(monitor.clock_regressions(), 1);
+        // Other tests may warn meanwhile; keep only this robot's line.
+        let warnings: Vec<String> = captured
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|line| line.contains("heartbeat for robot 5 "))
+            .cloned()
+            .collect();
+        assert_eq!(warnings.len(), 1, "{warnings:?}");
+        assert!(warnings[0].starts_with("[WARN]"), "{}", warnings[0]);
+        assert!(warnings[0].contains("older than last_seen; ignored"));
+        // The stored timestamp must not move backwards.
+        assert!(monitor.last_seen_ms_ago()[&5] < 10);
+        monitor.heartbeat(5);
+        assert_eq!(monitor.clock_regressions(), 1);
+    }
+
+    #[test]
+    fn suggested_timeout_exceeds_observed_intervals() {
+        let monitor = HealthMonitor::new();
+        assert_eq!(monitor.suggested_timeout(), None);
+        let mut at = Instant::now();
+        monitor.set_last_seen_for_test(3, at);
+        let gaps_ms = [10, 12, 9, 11, 40, 10, 13];
+        for gap in gaps_ms {
+            at += Duration::from_millis(gap);
+            monitor.heartbeat_at(3, at);
+        }
+        let suggested = monitor.suggested_timeout().expect("intervals observed");
+        // Seven samples put p99 at the largest gap.
+        assert_eq!(suggested, Duration::from_millis(80));
+        assert!(suggested > Duration::from_millis(*gaps_ms.iter().max().unwrap()));
+    }
+
+    #[test]
+    fn register_robots_tracks_every_id_at_once() {
+        let monitor = HealthMonitor::new();
+        monitor.register_robot(3);
+        monitor.heartbeat(3);
+        monitor.register_robots(0..100);
+        let ages = monitor.last_seen_ms_ago();
+        assert_eq!(ages.len(), 100);
+        assert!(monitor.detect_offline(Duration::from_secs(5)).is_empty());
+        monitor.register_robots([100, 101]);
+        assert_eq!(monitor.combined_online_count(&[]), 102);
+    }
+
+    #[test]
+    fn wait_for_any_offline_returns_when_heartbeat_expires() {
+        let monitor = HealthMonitor::new();
+        monitor.register_robots([1, 2]);
+        let start = Instant::now();
+        let offline =
+            monitor.wait_for_any_offline(Duration::from_millis(20), start + Duration::from_secs(5));
+        assert!(offline);
+        // Woken at expiry, not after a poll interval or the far deadline.
+        assert!(start.elapsed() < Duration::from_millis(500));
+
+        let fresh = HealthMonitor::new();
+        fresh.register_robot(7);
+        let deadline = Instant::now() + Duration::from_millis(10);
+        assert!(!fresh.wait_for_any_offline(Duration::from_secs(5), deadline));
+        assert!(Instant::now() >= deadline);
+    }
+
+    #[test]
+    fn wait_for_new_offline_ignores_robots_already_offline() {
+        let timeout = Duration::from_millis(20);
+        let monitor = HealthMonitor::new();
+        monitor.register_robot(1);
+        std::thread::sleep(timeout * 2);
+        assert_eq!(monitor.detect_offline(timeout), HashSet::from([1]));
+
+        // Robot 1 stays offline; only robot 2's expiry ends the wait.
+        let start = Instant::now();
+        monitor.register_robot(2);
+        assert!(monitor.wait_for_new_offline(timeout, start + Duration::from_secs(5)));
+        assert!(start.elapsed() >= timeout);
+        assert_eq!(monitor.offline_robots(), HashSet::from([1, 2]));
+
+        // Nobody left online to expire: the wait runs to its deadline.
+        let deadline = Instant::now() + Duration::from_millis(30);
+        assert!(!monitor.wait_for_new_offline(timeout, deadline));
+        assert!(Instant::now() >= deadline);
+    }
+
     #[test]
     fn deterministic_offline_without_sleep() {
         let monitor = HealthMonitor::new();
@@ -142,4 +607,41 @@ mod tests {
         let offline = monitor.detect_offline(Duration::from_secs(1));
         assert!(offline.contains(&robot));
     }
+
+    #[test]
+    fn signed_off_robot_is_never_marked_offline() {
+        let monitor = HealthMonitor::new();
+        let past = Instant::now() - Duration::from_secs(5);
+        monitor.set_last_seen_for_test(1, past);
+        monitor.set_last_seen_for_test(2, past);
+        monitor.sign_off(1);
+        assert_eq!(
+            monitor.detect_offline(Duration::from_secs(1)),
+            HashSet::from([2])
+        );
+        // Signing off does not clear a mark already made.
+        monitor.sign_off(2);
+        assert_eq!(monitor.offline_robots(), HashSet::from([2]));
+    }
+
+    #[test]
+    fn mock_clock_drives_offline_detection() {
+        let clock = Arc::new(MockClock::new());
+        let monitor =
+            HealthMonitor::with_thresholds(Duration::from_secs(1), Duration::from_secs(3))
+                .with_clock(Arc::clone(&clock) as Arc<dyn Clock>);
+        let timeout = Duration::from_secs(3);
+        monitor.register_robots([1, 2]);
+
+        clock.advance(Duration::from_secs(2));
+        monitor.heartbeat(2);
+        assert_eq!(monitor.robot_state(1), RobotHealthState::Degraded);
+        assert!(monitor.detect_offline(timeout).is_empty());
+
+        clock.advance(Duration::from_secs(2));
+        assert_eq!(monitor.detect_offline(timeout), HashSet::from([1]));
+        // Robot 2's heartbeat is two seconds old: late, but not dead.
+        assert_eq!(monitor.robot_state(2), RobotHealthState::Degraded);
+        assert_eq!(monitor.last_seen_ms_ago()[&1], 4000);
+    }
 }
@@ -1,15 +1,46 @@
-//! Lightweight debug logging helpers (no-ops in release).
+//! Lightweight logging helpers: debug-only dev logs and always-on warnings.
 
 use std::fmt::Arguments;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+type LogWriter = Arc<dyn Fn(&str) + Send + Sync>;
+
+// Orders dev log lines totally, even within one millisecond.
+static DEV_LOG_SEQ: AtomicU64 = AtomicU64::new(0);
+// Replaces stdout for dev log lines when set.
+static DEV_LOG_WRITER: Mutex<Option<LogWriter>> = Mutex::new(None);
+// Replaces stderr for warning lines when set.
+static WARN_LOG_WRITER: Mutex<Option<LogWriter>> = Mutex::new(None);
+
+/// Send dev log lines to `writer` instead of stdout; replaces any earlier
+/// writer.
+#[allow(dead_code)]
+pub fn set_dev_log_writer(writer: impl Fn(&str) + Send + Sync + 'static) {
+    let mut slot = DEV_LOG_WRITER.lock().expect("log writer mutex poisoned");
+    *slot = Some(Arc::new(writer));
+}
+
+/// Send dev log lines back to stdout.
+#[allow(dead_code)]
+pub fn clear_dev_log_writer() {
+    let mut slot = DEV_LOG_WRITER.lock().expect("log writer mutex poisoned");
+    *slot = None;
+}
+
 /// Print a debug log line when compiled with debug assertions.
 pub fn dev_log(args: Arguments) {
-    if !cfg!(debug_assertions) {
-        return;
+    if cfg!(debug_assertions) {
+        write_dev_line(args);
     }
+}
 
+// Number, stamp and emit one dev log line; runs in every profile so the
+// sequencing and writer hook stay testable in release builds.
+fn write_dev_line(args: Arguments) {
+    let seq = DEV_LOG_SEQ.fetch_add(1, Ordering::SeqCst);
     // Millisecond timestamp since Unix epoch for quick ordering.
     let ts = SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -17,7 +48,16 @@ pub fn dev_log(args: Arguments) {
         .unwrap_or(0);
     let current = thread::current();
     let thread_name = current.name().unwrap_or("unnamed");
-    println!("[{ts}ms][{thread_name}] {args}");
+    let line = format!("[{seq}][{ts}ms][{thread_name}] {args}");
+    // Clone out so the writer runs without the mutex held.
+    let writer = DEV_LOG_WRITER
+        .lock()
+        .expect("log writer mutex poisoned")
+        .clone();
+    match writer {
+        Some(writer) => writer(&line),
+        None => println!("{line}"),
+    }
 }
 
 /// Convenience macro for debug-only logging.
@@ -29,3 +69,71 @@ macro_rules! log_dev {
         }
     };
 }
+
+/// Send warning lines to `writer` instead of stderr; replaces any earlier
+/// writer.
+#[allow(dead_code)]
+pub fn set_warn_log_writer(writer: impl Fn(&str) + Send + Sync + 'static) {
+    let mut slot = WARN_LOG_WRITER.lock().expect("log writer mutex poisoned");
+    *slot = Some(Arc::new(writer));
+}
+
+/// Send warning lines back to stderr.
+#[allow(dead_code)]
+pub fn clear_warn_log_writer() {
+    let mut slot = WARN_LOG_WRITER.lock().expect("log writer mutex poisoned");
+    *slot = None;
+}
+
+/// Print a warning line to stderr in every build profile.
+pub fn warn_log(args: Arguments) {
+    let current = thread::current();
+    let thread_name = current.name().unwrap_or("unnamed");
+    let line = format!("[WARN][{thread_name}] {args}");
+    let writer = WARN_LOG_WRITER
+        .lock()
+        .expect("log writer mutex poisoned")
+        .clone();
+    match writer {
+        Some(writer) => writer(&line),
+        None => eprintln!("{line}"),
+    }
+}
+
+/// Convenience macro for warnings that must survive release builds.
+#[macro_export]
+macro_rules! log_warn {
+    ($($arg:tt)*) => {
+        $crate::logging::warn_log(format_args!($($arg)*))
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dev_log_lines_carry_increasing_sequence_numbers() {
+        let captured = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&captured);
+        set_dev_log_writer(move |line| sink.lock().unwrap().push(line.to_string()));
+        // `log_dev!` is a no-op without debug assertions, so go below it.
+        write_dev_line(format_args!("seq-test first"));
+        write_dev_line(format_args!("seq-test second"));
+        clear_dev_log_writer();
+
+        // Other tests may log meanwhile; keep only this test's lines.
+        let seqs: Vec<u64> = captured
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|line| line.contains("seq-test"))
+            .map(|line| {
+                let end = line.find(']').expect("sequence prefix");
+                line[1..end].parse().expect("sequence number")
+            })
+            .collect();
+        assert_eq!(seqs.len(), 2);
+        assert!(seqs[0] < seqs[1], "{seqs:?}");
+    }
+}
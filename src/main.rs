@@ -1,14 +1,27 @@
 //! Project Blaze CLI entry point and argument parsing.
 
+mod chrome_trace;
+mod events;
 mod health_monitor;
 mod logging;
+mod results;
+mod rng;
+mod robot_threads;
 mod sim;
+mod sync;
 mod task_queue;
+mod task_source;
+mod tasks_file;
 mod types;
 mod zones;
 
 use std::io::Write;
 use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+// Each robot is an OS thread, so counts past this are almost always typos.
+const DEFAULT_MAX_ROBOTS: usize = 4096;
 
 // Parse a comma-separated list of values, or "-" to mean "skip".
 fn parse_list<T: FromStr>(arg: &str) -> Option<Vec<T>> {
@@ -41,6 +54,18 @@ fn write_usage<W: Write>(out: &mut W, program: &str) {
     let _ = writeln!(out, "Project Blaze CLI");
     let _ = writeln!(out, "Usage:");
     let _ = writeln!(out, "  {program} (run demo)");
+    let _ = writeln!(
+        out,
+        "  {program} --timeline <path> (run demo, write a DOT zone schedule)"
+    );
+    let _ = writeln!(
+        out,
+        "  {program} --demo-timeout-ms <ms> (run demo, stop robots still running after <ms>)"
+    );
+    let _ = writeln!(
+        out,
+        "  {program} --no-offline-demo (run demo with every robot heartbeating)"
+    );
     let _ = writeln!(
         out,
         "  {program} bench [robots] [tasks_per_robot] [zones] [work_ms] [validate] [offline-demo]"
@@ -49,6 +74,27 @@ fn write_usage<W: Write>(out: &mut W, program: &str) {
         out,
         "  {program} stress [robot_sets] [task_sets] [zone_sets] [work_ms] [validate] [offline-demo]"
     );
+    let _ = writeln!(out, "  {program} health [robots] [offline_ids]");
+    let _ = writeln!(
+        out,
+        "  {program} fairness [robots] [tasks_per_robot] [zones] [work_ms] (Jain's index over per-robot tasks)"
+    );
+    let _ = writeln!(
+        out,
+        "  {program} qbench [producers] [consumers] [tasks_per_producer] (queue-only throughput)"
+    );
+    let _ = writeln!(
+        out,
+        "  {program} detection-latency [trials] [timeout_ms] [poll_ms] (offline detection delay)"
+    );
+    let _ = writeln!(
+        out,
+        "  {program} validate-tasks <path> [zones] (check a tasks file without running)"
+    );
+    let _ = writeln!(
+        out,
+        "  {program} autotune [max_robots] [tasks_per_robot] [zones] [work_ms]"
+    );
     let _ = writeln!(out, "  {program} --help");
     let _ = writeln!(out);
     let _ = writeln!(
@@ -65,8 +111,149 @@ fn write_usage<W: Write>(out: &mut W, program: &str) {
         out,
         "  stress robots=1,2,4,8,12 tasks_per_robot=10,25,50 zones=1,2,4 work_ms=5"
     );
+    let _ = writeln!(out, "  health robots=4 offline_ids=1");
+    let _ = writeln!(
+        out,
+        "  fairness robots=4 tasks_per_robot=25 zones=2 work_ms=5"
+    );
+    let _ = writeln!(
+        out,
+        "  qbench producers=2 consumers=2 tasks_per_producer=10000"
+    );
+    let _ = writeln!(
+        out,
+        "  detection-latency trials=200 timeout_ms=100 poll_ms=5"
+    );
+    let _ = writeln!(
+        out,
+        "  autotune max_robots=16 tasks_per_robot=25 zones=2 work_ms=5"
+    );
     let _ = writeln!(out, "Flags:");
     let _ = writeln!(out, "  validate       enable extra safety checks");
+    let _ = writeln!(
+        out,
+        "  --trace        log each task's lifecycle to stderr (bench/stress)"
+    );
+    let _ = writeln!(
+        out,
+        "  --offline-recover-after <tasks>  offline robot resumes heartbeats after <tasks> more tasks"
+    );
+    let _ = writeln!(
+        out,
+        "  --zone-acquire-timeout-ms <ms>   requeue a task if its zone stays busy this long"
+    );
+    let _ = writeln!(
+        out,
+        "  --max-duration <ms>              stop a stress sweep after <ms> of wall time"
+    );
+    let _ = writeln!(
+        out,
+        "  --configs <file>                 stress: run robots,tasks,zones[,work_ms] lines, no cross product"
+    );
+    let _ = writeln!(
+        out,
+        "  --fixed-tasks                    stress: reuse one generated task set across configurations"
+    );
+    let _ = writeln!(
+        out,
+        "  --baseline                       stress: add a speedup column relative to the first configuration"
+    );
+    let _ = writeln!(
+        out,
+        "  --sort-by <column>[:desc]        stress: print rows ordered by a result column"
+    );
+    let _ = writeln!(
+        out,
+        "  --events-jsonl <path|->          stream per-task events as JSON Lines (- for stdout)"
+    );
+    let _ = writeln!(
+        out,
+        "  --rng-log <path>                 record every seeded random draw as seq,context,value"
+    );
+    let _ = writeln!(
+        out,
+        "  --format <csv|table|binary>      print results as CSV (default), a table, or binary records"
+    );
+    let _ = writeln!(
+        out,
+        "  --queue-discipline <fifo|lifo>   take the oldest (default) or newest queued task first"
+    );
+    let _ = writeln!(
+        out,
+        "  --contention-only                bench: robots hammer one zone, no queue or work"
+    );
+    let _ = writeln!(
+        out,
+        "  --min-throughput <t>             bench: exit 1 if throughput falls below t tasks/sec"
+    );
+    let _ = writeln!(
+        out,
+        "  --max-robots <n>                 refuse robot counts above n (default 4096)"
+    );
+    let _ = writeln!(
+        out,
+        "  --retries <n>                    bench: retry a run up to n times if it cannot start"
+    );
+    let _ = writeln!(
+        out,
+        "  --strict-leftover                bench: exit 1 if any task is left in the queue"
+    );
+    let _ = writeln!(
+        out,
+        "  --tasks-file <path>              bench: draw tasks from an id,zone,description file"
+    );
+    let _ = writeln!(
+        out,
+        "  --total-tasks <n>                bench: queue n tasks in total; robots drain the shared queue"
+    );
+    let _ = writeln!(
+        out,
+        "  --spin-zones                     busy-wait for zones instead of blocking on a condvar"
+    );
+    let _ = writeln!(
+        out,
+        "  --affinity                       robot i prefers tasks for zone i % zones + 1"
+    );
+    let _ = writeln!(
+        out,
+        "  --zone-weights <w1,w2,...>       sample task zones by weight (one weight per zone)"
+    );
+    let _ = writeln!(
+        out,
+        "  --seed <n>                       seed for randomized choices (default 24301)"
+    );
+    let _ = writeln!(
+        out,
+        "  --max-rate <tasks_per_s>         cap each robot's task consumption (token bucket)"
+    );
+    let _ = writeln!(
+        out,
+        "  --add-robot-after-ms <ms>        bring one extra robot online mid-run"
+    );
+    let _ = writeln!(
+        out,
+        "  --work-ms-per-zone <list>        work time by target zone, cycling if shorter than zones"
+    );
+    let _ = writeln!(
+        out,
+        "  --deterministic                  run robots round-robin on one thread for exact replays"
+    );
+    let _ = writeln!(
+        out,
+        "  --chaos <seed>                   inject seeded slow releases and late heartbeats, then validate"
+    );
+    let _ = writeln!(
+        out,
+        "  --fail-rate <p>                  report each processed task as failed with probability p"
+    );
+    let _ = writeln!(
+        out,
+        "  --travel-ms <ms>                 travel time between tasks (per zone hop with --zone-weights)"
+    );
+    let _ = writeln!(
+        out,
+        "  --trace-out <path>               write zone holds as Chrome Trace JSON (chrome://tracing)"
+    );
     let _ = writeln!(
         out,
         "  offline-demo   simulate a robot going offline (alias: offline)"
@@ -97,90 +284,433 @@ struct BenchArgs {
     tasks_per_robot: Option<usize>,
     zones: Option<u64>,
     work_ms: Option<u64>,
-    validate: bool,
-    simulate_offline: bool,
+    // Flag-driven settings; positional values are folded in by `into_config`.
+    config: sim::BenchConfig,
+    // Exit nonzero when the run's throughput lands below this floor.
+    min_throughput: Option<f64>,
+}
+
+impl BenchArgs {
+    fn into_config(self) -> sim::BenchConfig {
+        let mut config = self.config;
+        if let Some(robots) = self.robots {
+            config.robots = robots;
+        }
+        if let Some(tasks_per_robot) = self.tasks_per_robot {
+            config.tasks_per_robot = tasks_per_robot;
+        }
+        if let Some(zones) = self.zones {
+            config.zones_total = zones;
+        } else if let Some(weights) = config.zone_weights.as_ref() {
+            // One weight per zone, so the weights imply the zone count.
+            config.zones_total = weights.len() as u64;
+        }
+        if let Some(work_ms) = self.work_ms {
+            config.work_ms = work_ms;
+        }
+        config
+    }
 }
 
 struct StressArgs {
     robot_sets: Option<Vec<usize>>,
     task_sets: Option<Vec<usize>>,
     zone_sets: Option<Vec<u64>>,
-    work_ms: Option<u64>,
-    validate: bool,
-    simulate_offline: bool,
+    // Shared settings for every configuration in the sweep.
+    config: sim::BenchConfig,
+    options: sim::StressOptions,
+}
+
+struct DemoArgs {
+    timeline: Option<String>,
+    options: sim::DemoOptions,
+}
+
+// Demo flags may appear in any order; `first` was already taken from `args`.
+fn parse_demo_args(program: &str, first: &str, args: impl Iterator<Item = String>) -> DemoArgs {
+    let mut demo = DemoArgs {
+        timeline: None,
+        options: sim::DemoOptions::default(),
+    };
+    let mut args = std::iter::once(first.to_string()).chain(args);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--timeline" => demo.timeline = Some(flag_value(program, &arg, &mut args)),
+            "--demo-timeout-ms" => {
+                let ms: u64 = flag_value(program, &arg, &mut args);
+                demo.options.timeout = Some(Duration::from_millis(ms));
+            }
+            "--no-offline-demo" => demo.options.simulate_offline = false,
+            _ => exit_with_usage(program, &format!("demo: unexpected argument: {arg}")),
+        }
+    }
+    demo
+}
+
+struct HealthArgs {
+    robots: Option<usize>,
+    offline_ids: Option<Vec<u64>>,
 }
 
-fn parse_common_flag(arg: &str, validate: &mut bool, simulate_offline: &mut bool) -> bool {
+fn parse_health_args(program: &str, args: impl Iterator<Item = String>) -> HealthArgs {
+    let mut robots: Option<usize> = None;
+    let mut offline_ids: Option<Vec<u64>> = None;
+    let mut offline_ids_skipped = false;
+
+    for arg in args {
+        if robots.is_none() {
+            robots = arg.parse::<usize>().ok();
+            if robots.is_none() {
+                exit_with_usage(program, &format!("health: invalid robots value: {arg}"));
+            } else if robots == Some(0) {
+                exit_with_usage(program, "health: robots must be > 0");
+            }
+        } else if offline_ids.is_none() && !offline_ids_skipped {
+            if arg == "-" {
+                offline_ids_skipped = true;
+            } else if let Some(values) = parse_u64_list(&arg) {
+                offline_ids = Some(values);
+            } else {
+                exit_with_usage(
+                    program,
+                    &format!("health: invalid offline_ids value: {arg}"),
+                );
+            }
+        } else {
+            exit_with_usage(program, &format!("health: unexpected argument: {arg}"));
+        }
+    }
+
+    HealthArgs {
+        robots,
+        offline_ids,
+    }
+}
+
+// Up to `N` positive positional values named by `names`; omitted ones stay
+// `None` so the command applies its defaults.
+fn parse_positive_args<const N: usize>(
+    program: &str,
+    command: &str,
+    names: [&str; N],
+    args: impl Iterator<Item = String>,
+) -> [Option<usize>; N] {
+    let mut values = [None; N];
+    for (index, arg) in args.enumerate() {
+        let Some(slot) = values.get_mut(index) else {
+            exit_with_usage(program, &format!("{command}: unexpected argument: {arg}"));
+        };
+        match arg.parse::<usize>() {
+            Ok(value) if value > 0 => *slot = Some(value),
+            _ => exit_with_usage(
+                program,
+                &format!("{command}: invalid {} value: {arg}", names[index]),
+            ),
+        }
+    }
+    values
+}
+
+// Parse the value following a `--flag`, exiting with usage if missing or invalid.
+fn flag_value<T: FromStr>(program: &str, flag: &str, args: &mut impl Iterator<Item = String>) -> T {
+    let Some(raw) = args.next() else {
+        exit_with_usage(program, &format!("{flag}: missing value"));
+    };
+    match raw.parse::<T>() {
+        Ok(value) => value,
+        Err(_) => exit_with_usage(program, &format!("{flag}: invalid value: {raw}")),
+    }
+}
+
+// Read a `BLAZE_*` fallback; unset or empty means absent, garbage is a usage error.
+fn env_value<T: FromStr>(program: &str, name: &str) -> Option<T> {
+    let raw = std::env::var(name).ok().filter(|raw| !raw.is_empty())?;
+    match raw.parse::<T>() {
+        Ok(value) => Some(value),
+        Err(_) => exit_with_usage(program, &format!("{name}: invalid value: {raw}")),
+    }
+}
+
+// Seed the config from the environment before flags are parsed, so any CLI
+// flag or positional still wins.
+fn apply_env_defaults(program: &str, config: &mut sim::BenchConfig) {
+    if let Some(validate) = env_value::<String>(program, "BLAZE_VALIDATE") {
+        config.validate = match validate.as_str() {
+            "1" | "true" | "yes" => true,
+            "0" | "false" | "no" => false,
+            _ => exit_with_usage(
+                program,
+                &format!("BLAZE_VALIDATE: invalid value: {validate}"),
+            ),
+        };
+    }
+    if let Some(seed) = env_value(program, "BLAZE_SEED") {
+        config.seed = seed;
+    }
+    if let Some(work_ms) = env_value(program, "BLAZE_WORK_MS") {
+        config.work_ms = work_ms;
+    }
+}
+
+// Apply a flag shared by bench-style commands; returns false if `arg` is not one.
+fn parse_common_flag(
+    program: &str,
+    arg: &str,
+    args: &mut impl Iterator<Item = String>,
+    config: &mut sim::BenchConfig,
+) -> bool {
     match arg {
         "validate" => {
-            *validate = true;
+            config.validate = true;
             true
         }
         "offline" | "offline-demo" | "--offline-demo" => {
-            *simulate_offline = true;
+            config.simulate_offline = true;
+            true
+        }
+        "--trace" => {
+            config.trace = true;
+            true
+        }
+        "--spin-zones" => {
+            config.spin_zones = true;
+            true
+        }
+        "--affinity" => {
+            config.affinity = true;
+            true
+        }
+        "--zone-weights" => {
+            let raw: String = flag_value(program, arg, args);
+            match parse_u64_list(&raw) {
+                Some(weights) if weights.iter().any(|&weight| weight > 0) => {
+                    config.zone_weights = Some(weights);
+                }
+                _ => exit_with_usage(program, &format!("{arg}: invalid weights: {raw}")),
+            }
+            true
+        }
+        "--seed" => {
+            config.seed = flag_value(program, arg, args);
+            true
+        }
+        "--trace-out" => {
+            let path: String = flag_value(program, arg, args);
+            config.trace_out = Some(path.into());
+            true
+        }
+        "--max-rate" => {
+            let rate: f64 = flag_value(program, arg, args);
+            if !(rate.is_finite() && rate > 0.0) {
+                exit_with_usage(program, &format!("{arg}: rate must be > 0"));
+            }
+            config.max_rate = Some(rate);
+            true
+        }
+        "--work-ms-per-zone" => {
+            let raw: String = flag_value(program, arg, args);
+            match parse_u64_list(&raw) {
+                Some(values) if !values.is_empty() => config.work_ms_per_zone = Some(values),
+                _ => exit_with_usage(program, &format!("{arg}: invalid list: {raw}")),
+            }
+            true
+        }
+        "--deterministic" => {
+            config.deterministic = true;
+            true
+        }
+        "--fail-rate" => {
+            let rate: f64 = flag_value(program, arg, args);
+            if !(0.0..=1.0).contains(&rate) {
+                exit_with_usage(program, &format!("{arg}: must be between 0 and 1"));
+            }
+            config.fail_rate = Some(rate);
+            true
+        }
+        "--chaos" => {
+            config.chaos = Some(flag_value(program, arg, args));
+            true
+        }
+        "--travel-ms" => {
+            config.travel_ms = flag_value(program, arg, args);
+            true
+        }
+        "--add-robot-after-ms" => {
+            config.add_robot_after_ms = Some(flag_value(program, arg, args));
+            true
+        }
+        "--zone-acquire-timeout-ms" => {
+            config.zone_acquire_timeout_ms = Some(flag_value(program, arg, args));
+            true
+        }
+        "--format" => {
+            config.format = flag_value(program, arg, args);
+            true
+        }
+        "--queue-discipline" => {
+            config.queue_discipline = flag_value(program, arg, args);
+            true
+        }
+        "--rng-log" => {
+            let path: String = flag_value(program, arg, args);
+            match rng::RngLog::create(std::path::Path::new(&path)) {
+                Ok(log) => config.rng_log = Some(Arc::new(log)),
+                Err(err) => exit_with_usage(program, &format!("{arg}: cannot open {path}: {err}")),
+            }
+            true
+        }
+        "--events-jsonl" => {
+            let target: String = flag_value(program, arg, args);
+            match events::EventSink::open(&target) {
+                Ok(sink) => config.events = Some(Arc::new(sink)),
+                Err(err) => {
+                    exit_with_usage(program, &format!("{arg}: cannot open {target}: {err}"))
+                }
+            }
+            true
+        }
+        "--offline-recover-after" => {
+            // Recovery only makes sense once a robot has gone silent.
+            config.simulate_offline = true;
+            config.offline_recover_after = Some(flag_value(program, arg, args));
             true
         }
         _ => false,
     }
 }
 
-fn parse_bench_args(program: &str, args: impl Iterator<Item = String>) -> BenchArgs {
+// Refuse to spawn more robot threads than `max_robots` rather than let the
+// OS fail partway through the spawn.
+fn check_robot_cap(program: &str, command: &str, robots: usize, max_robots: usize) {
+    if robots > max_robots {
+        exit_with_usage(
+            program,
+            &format!(
+                "{command}: robots={robots} exceeds --max-robots {max_robots} \
+                 (one OS thread per robot); pass --max-robots {robots} to run it anyway"
+            ),
+        );
+    }
+}
+
+fn parse_bench_args(program: &str, mut args: impl Iterator<Item = String>) -> BenchArgs {
     let mut robots: Option<usize> = None;
     let mut tasks_per_robot: Option<usize> = None;
     let mut zones: Option<u64> = None;
     let mut work_ms: Option<u64> = None;
-    let mut validate = false;
-    let mut simulate_offline = false;
+    let mut config = sim::BenchConfig::default();
+    apply_env_defaults(program, &mut config);
+    let mut min_throughput: Option<f64> = None;
+    let mut max_robots = DEFAULT_MAX_ROBOTS;
 
-    for arg in args {
-        if parse_common_flag(&arg, &mut validate, &mut simulate_offline) {
+    while let Some(arg) = args.next() {
+        if parse_common_flag(program, &arg, &mut args, &mut config) {
+            continue;
+        }
+        if arg == "--contention-only" {
+            config.contention_only = true;
+            continue;
+        }
+        if arg == "--max-robots" {
+            max_robots = flag_value(program, &arg, &mut args);
+            continue;
+        }
+        if arg == "--retries" {
+            config.retries = flag_value(program, &arg, &mut args);
+            continue;
+        }
+        if arg == "--strict-leftover" {
+            config.strict_leftover = true;
+            continue;
+        }
+        if arg == "--tasks-file" {
+            let path: String = flag_value(program, &arg, &mut args);
+            match tasks_file::load_tasks(std::path::Path::new(&path)) {
+                Ok(tasks) => {
+                    config.task_source = Some(Arc::new(task_source::ListSource::new(tasks)));
+                }
+                Err(err) => exit_with_usage(program, &format!("{arg}: {err}")),
+            }
+            continue;
+        }
+        if arg == "--total-tasks" {
+            let total: usize = flag_value(program, &arg, &mut args);
+            if total == 0 {
+                exit_with_usage(program, "bench: --total-tasks must be > 0");
+            }
+            config.total_tasks = Some(total);
+            continue;
+        }
+        if arg == "--min-throughput" {
+            let floor: f64 = flag_value(program, &arg, &mut args);
+            if !floor.is_finite() || floor < 0.0 {
+                exit_with_usage(program, "bench: --min-throughput must be >= 0");
+            }
+            min_throughput = Some(floor);
             continue;
         }
         if robots.is_none() {
             robots = arg.parse::<usize>().ok();
             if robots.is_none() {
-                exit_with_usage(&program, &format!("bench: invalid robots value: {arg}"));
+                exit_with_usage(program, &format!("bench: invalid robots value: {arg}"));
             } else if robots == Some(0) {
-                exit_with_usage(&program, "bench: robots must be > 0");
+                exit_with_usage(program, "bench: robots must be > 0");
             }
         } else if tasks_per_robot.is_none() {
             tasks_per_robot = arg.parse::<usize>().ok();
             if tasks_per_robot.is_none() {
                 exit_with_usage(
-                    &program,
+                    program,
                     &format!("bench: invalid tasks_per_robot value: {arg}"),
                 );
             } else if tasks_per_robot == Some(0) {
-                exit_with_usage(&program, "bench: tasks_per_robot must be > 0");
+                exit_with_usage(program, "bench: tasks_per_robot must be > 0");
             }
         } else if zones.is_none() {
             zones = arg.parse::<u64>().ok();
             if zones.is_none() {
-                exit_with_usage(&program, &format!("bench: invalid zones value: {arg}"));
+                exit_with_usage(program, &format!("bench: invalid zones value: {arg}"));
             } else if zones == Some(0) {
-                exit_with_usage(&program, "bench: zones must be > 0");
+                exit_with_usage(program, "bench: zones must be > 0");
             }
         } else if work_ms.is_none() {
             work_ms = arg.parse::<u64>().ok();
             if work_ms.is_none() {
-                exit_with_usage(&program, &format!("bench: invalid work_ms value: {arg}"));
+                exit_with_usage(program, &format!("bench: invalid work_ms value: {arg}"));
             }
         } else {
-            exit_with_usage(&program, &format!("bench: unexpected argument: {arg}"));
+            exit_with_usage(program, &format!("bench: unexpected argument: {arg}"));
         }
     }
 
+    // Positionals left out fall back to the environment.
+    robots = robots.or_else(|| env_value(program, "BLAZE_ROBOTS"));
+    tasks_per_robot = tasks_per_robot.or_else(|| env_value(program, "BLAZE_TASKS"));
+    zones = zones.or_else(|| env_value(program, "BLAZE_ZONES"));
+    if robots == Some(0) {
+        exit_with_usage(program, "BLAZE_ROBOTS: robots must be > 0");
+    }
+    if tasks_per_robot == Some(0) {
+        exit_with_usage(program, "BLAZE_TASKS: tasks_per_robot must be > 0");
+    }
+    if zones == Some(0) {
+        exit_with_usage(program, "BLAZE_ZONES: zones must be > 0");
+    }
+    if let Some(robots) = robots {
+        check_robot_cap(program, "bench", robots, max_robots);
+    }
+
     BenchArgs {
         robots,
         tasks_per_robot,
         zones,
         work_ms,
-        validate,
-        simulate_offline,
+        config,
+        min_throughput,
     }
 }
 
-fn parse_stress_args(program: &str, args: impl Iterator<Item = String>) -> StressArgs {
+fn parse_stress_args(program: &str, mut args: impl Iterator<Item = String>) -> StressArgs {
     let mut robot_sets: Option<Vec<usize>> = None;
     let mut task_sets: Option<Vec<usize>> = None;
     let mut zone_sets: Option<Vec<u64>> = None;
@@ -188,11 +718,49 @@ fn parse_stress_args(program: &str, args: impl Iterator<Item = String>) -> Stres
     let mut robot_sets_skipped = false;
     let mut task_sets_skipped = false;
     let mut zone_sets_skipped = false;
-    let mut validate = false;
-    let mut simulate_offline = false;
+    let mut config = sim::BenchConfig::default();
+    apply_env_defaults(program, &mut config);
+    let mut options = sim::StressOptions::default();
+    let mut max_robots = DEFAULT_MAX_ROBOTS;
 
-    for arg in args {
-        if parse_common_flag(&arg, &mut validate, &mut simulate_offline) {
+    while let Some(arg) = args.next() {
+        if parse_common_flag(program, &arg, &mut args, &mut config) {
+            continue;
+        }
+        if arg == "--max-robots" {
+            max_robots = flag_value(program, &arg, &mut args);
+            continue;
+        }
+        if arg == "--max-duration" {
+            let ms: u64 = flag_value(program, &arg, &mut args);
+            options.max_duration = Some(Duration::from_millis(ms));
+            continue;
+        }
+        if arg == "--fixed-tasks" {
+            options.fixed_tasks = true;
+            continue;
+        }
+        if arg == "--baseline" {
+            options.baseline = true;
+            continue;
+        }
+        if arg == "--sort-by" {
+            let spec: String = flag_value(program, &arg, &mut args);
+            match sim::parse_sort_key(&spec) {
+                Ok(key) => options.sort_by = Some(key),
+                Err(err) => exit_with_usage(program, &format!("stress: --sort-by {err}")),
+            }
+            continue;
+        }
+        if arg == "--configs" {
+            let path: String = flag_value(program, &arg, &mut args);
+            let parsed = std::fs::read_to_string(&path)
+                .map_err(|err| format!("cannot read {path}: {err}"))
+                .and_then(|text| sim::parse_stress_configs(&text));
+            match parsed {
+                Ok(configs) => options.configs = Some(configs),
+                Err(err) => exit_with_usage(program, &format!("stress: --configs {err}")),
+            }
             continue;
         }
 
@@ -202,14 +770,14 @@ fn parse_stress_args(program: &str, args: impl Iterator<Item = String>) -> Stres
                 robot_sets_skipped = true;
                 consumed = true;
             } else if let Some(values) = parse_usize_list(&arg) {
-                if values.iter().any(|&robots| robots == 0) {
-                    exit_with_usage(&program, "stress: robot_sets must be > 0");
+                if values.contains(&0) {
+                    exit_with_usage(program, "stress: robot_sets must be > 0");
                 }
                 robot_sets = Some(values);
                 consumed = true;
             }
             if !consumed {
-                exit_with_usage(&program, &format!("stress: invalid robot_sets value: {arg}"));
+                exit_with_usage(program, &format!("stress: invalid robot_sets value: {arg}"));
             }
             continue;
         }
@@ -218,14 +786,14 @@ fn parse_stress_args(program: &str, args: impl Iterator<Item = String>) -> Stres
                 task_sets_skipped = true;
                 consumed = true;
             } else if let Some(values) = parse_usize_list(&arg) {
-                if values.iter().any(|&tasks| tasks == 0) {
-                    exit_with_usage(&program, "stress: task_sets must be > 0");
+                if values.contains(&0) {
+                    exit_with_usage(program, "stress: task_sets must be > 0");
                 }
                 task_sets = Some(values);
                 consumed = true;
             }
             if !consumed {
-                exit_with_usage(&program, &format!("stress: invalid task_sets value: {arg}"));
+                exit_with_usage(program, &format!("stress: invalid task_sets value: {arg}"));
             }
             continue;
         }
@@ -238,7 +806,7 @@ fn parse_stress_args(program: &str, args: impl Iterator<Item = String>) -> Stres
                 consumed = true;
             }
             if !consumed {
-                exit_with_usage(&program, &format!("stress: invalid zone_sets value: {arg}"));
+                exit_with_usage(program, &format!("stress: invalid zone_sets value: {arg}"));
             }
             continue;
         }
@@ -246,12 +814,12 @@ fn parse_stress_args(program: &str, args: impl Iterator<Item = String>) -> Stres
             if let Ok(value) = arg.parse::<u64>() {
                 work_ms = Some(value);
             } else {
-                exit_with_usage(&program, &format!("stress: invalid work_ms value: {arg}"));
+                exit_with_usage(program, &format!("stress: invalid work_ms value: {arg}"));
             }
             continue;
         }
 
-        exit_with_usage(&program, &format!("stress: unexpected argument: {arg}"));
+        exit_with_usage(program, &format!("stress: unexpected argument: {arg}"));
     }
 
     if let Some(zones) = zone_sets.as_mut() {
@@ -262,17 +830,25 @@ fn parse_stress_args(program: &str, args: impl Iterator<Item = String>) -> Stres
             eprintln!("stress warning: ignored {dropped} zone set(s) <= 0");
         }
         if zones.is_empty() {
-            exit_with_usage(&program, "stress: zones must be > 0");
+            exit_with_usage(program, "stress: zones must be > 0");
         }
     }
 
+    if let Some(work_ms) = work_ms {
+        config.work_ms = work_ms;
+    }
+    // A configs file replaces the robot sets, so cap its tuples too.
+    let listed = options.configs.iter().flatten().map(|point| point.robots);
+    if let Some(largest) = robot_sets.iter().flatten().copied().chain(listed).max() {
+        check_robot_cap(program, "stress", largest, max_robots);
+    }
+
     StressArgs {
         robot_sets,
         task_sets,
         zone_sets,
-        work_ms,
-        validate,
-        simulate_offline,
+        config,
+        options,
     }
 }
 
@@ -286,14 +862,22 @@ fn main() {
         Some("bench") => {
             // Simple positional CLI parsing for a single benchmark run.
             let bench = parse_bench_args(&program, args);
-            sim::run_benchmark(
-                bench.robots,
-                bench.tasks_per_robot,
-                bench.zones,
-                bench.work_ms,
-                bench.validate,
-                bench.simulate_offline,
-            );
+            let floor = bench.min_throughput;
+            let config = bench.into_config();
+            let strict_leftover = config.strict_leftover;
+            let result = sim::run_benchmark(config);
+            if let Some(floor) = floor {
+                match result.as_ref() {
+                    Some(result) => {
+                        eprintln!("# gate,throughput,{:.2},{floor:.2}", result.throughput)
+                    }
+                    None => eprintln!("# gate,throughput,NA,{floor:.2}"),
+                }
+            }
+            let status = sim::bench_exit_status(result.as_ref(), strict_leftover, floor);
+            if status != 0 {
+                std::process::exit(status);
+            }
         }
         Some("stress") => {
             // Parse list-based inputs and flags for a stress sweep.
@@ -302,15 +886,72 @@ fn main() {
                 stress.robot_sets,
                 stress.task_sets,
                 stress.zone_sets,
-                stress.work_ms,
-                stress.validate,
-                stress.simulate_offline,
+                stress.config,
+                stress.options,
+            );
+        }
+        Some("autotune") => {
+            // Same positional layout as bench, with robots as the sweep ceiling.
+            let tune = parse_bench_args(&program, args);
+            let max_robots = tune.robots;
+            sim::run_autotune(max_robots, tune.into_config());
+        }
+        Some("fairness") => {
+            // Same positional layout as bench; scores the spread of work.
+            let fairness = parse_bench_args(&program, args);
+            sim::run_fairness(fairness.into_config());
+        }
+        Some("health") => {
+            // Score offline detection against a planned set of silent robots.
+            let health = parse_health_args(&program, args);
+            sim::run_health(health.robots, health.offline_ids);
+        }
+        Some("qbench") => {
+            // Queue-only throughput: producers, consumers, tasks per producer.
+            let names = ["producers", "consumers", "tasks_per_producer"];
+            let [producers, consumers, tasks] =
+                parse_positive_args(&program, "qbench", names, args);
+            sim::run_qbench(producers, consumers, tasks);
+        }
+        Some("detection-latency") => {
+            // Mock-clock trials: how late past the timeout polling flags a robot.
+            let names = ["trials", "timeout_ms", "poll_ms"];
+            let [trials, timeout_ms, poll_ms] =
+                parse_positive_args(&program, "detection-latency", names, args);
+            sim::run_detection_latency(
+                trials,
+                timeout_ms.map(|ms| ms as u64),
+                poll_ms.map(|ms| ms as u64),
             );
         }
+        Some("validate-tasks") => {
+            // Parse-only check of a tasks file; zones bounds explicit zone ids.
+            let Some(path) = args.next() else {
+                exit_with_usage(&program, "validate-tasks: missing path");
+            };
+            let zones = args.next().map(|arg| match arg.parse::<u64>() {
+                Ok(zones) if zones > 0 => zones,
+                _ => exit_with_usage(&program, &format!("validate-tasks: invalid zones: {arg}")),
+            });
+            if let Some(extra) = args.next() {
+                exit_with_usage(
+                    &program,
+                    &format!("validate-tasks: unexpected argument: {extra}"),
+                );
+            }
+            if !tasks_file::run_validate(std::path::Path::new(&path), zones) {
+                std::process::exit(1);
+            }
+        }
         Some("--help") | Some("-h") | Some("help") => print_usage_stdout(&program),
+        Some(flag @ ("--timeline" | "--demo-timeout-ms" | "--no-offline-demo")) => {
+            let demo = parse_demo_args(&program, flag, args);
+            let timeline = demo.timeline.as_deref().map(std::path::Path::new);
+            sim::run_demo(timeline, &demo.options);
+        }
         Some(other) => {
             exit_with_usage(&program, &format!("unknown command: {other}"));
         }
-        None => sim::run_demo(),
+        None => sim::run_demo(None, &sim::DemoOptions::default()),
     }
 }
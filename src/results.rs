@@ -0,0 +1,212 @@
+//! Compact binary encoding of benchmark result rows, for high-volume sweeps
+//! where CSV text is too bulky.
+//!
+//! A stream starts with the 4-byte magic `BLZR` and a version byte, then
+//! holds one record per row: a little-endian `u32` payload length followed by
+//! the payload. The length prefix lets a reader skip fields appended by newer
+//! versions.
+
+use std::io::{self, Read, Write};
+
+const MAGIC: &[u8; 4] = b"BLZR";
+const VERSION: u8 = 1;
+
+/// One result row; the same columns as the CSV output.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResultRecord {
+    pub robots: u64,
+    pub tasks_per_robot: u64,
+    pub zones: u64,
+    pub total_tasks: u64,
+    pub elapsed_ms: f64,
+    pub throughput: f64,
+    pub avg_zone_wait_us: f64,
+    pub cpu_user_s: Option<f64>,
+    pub cpu_sys_s: Option<f64>,
+    pub max_occupancy: u64,
+    pub zone_violation: bool,
+    pub duplicate_tasks: bool,
+    pub offline_robots: u64,
+}
+
+/// Start a stream; call once before any `write_record`.
+pub fn write_header(out: &mut impl Write) -> io::Result<()> {
+    out.write_all(MAGIC)?;
+    out.write_all(&[VERSION])
+}
+
+/// Append one length-prefixed record.
+pub fn write_record(out: &mut impl Write, record: &ResultRecord) -> io::Result<()> {
+    let mut payload = Vec::with_capacity(96);
+    for value in [
+        record.robots,
+        record.tasks_per_robot,
+        record.zones,
+        record.total_tasks,
+    ] {
+        payload.extend_from_slice(&value.to_le_bytes());
+    }
+    for value in [
+        record.elapsed_ms,
+        record.throughput,
+        record.avg_zone_wait_us,
+    ] {
+        payload.extend_from_slice(&value.to_le_bytes());
+    }
+    // Absent CPU times are a 0 flag byte with no value.
+    for value in [record.cpu_user_s, record.cpu_sys_s] {
+        match value {
+            Some(seconds) => {
+                payload.push(1);
+                payload.extend_from_slice(&seconds.to_le_bytes());
+            }
+            None => payload.push(0),
+        }
+    }
+    payload.extend_from_slice(&record.max_occupancy.to_le_bytes());
+    payload.push(u8::from(record.zone_violation));
+    payload.push(u8::from(record.duplicate_tasks));
+    payload.extend_from_slice(&record.offline_robots.to_le_bytes());
+
+    out.write_all(&(payload.len() as u32).to_le_bytes())?;
+    out.write_all(&payload)
+}
+
+/// Read a whole stream written by `write_header` and `write_record`.
+#[allow(dead_code)]
+pub fn read_records(input: &mut impl Read) -> io::Result<Vec<ResultRecord>> {
+    let mut header = [0u8; 5];
+    input.read_exact(&mut header)?;
+    if &header[..4] != MAGIC || header[4] != VERSION {
+        return Err(invalid("not a results stream"));
+    }
+    let mut records = Vec::new();
+    loop {
+        let mut len = [0u8; 4];
+        match input.read_exact(&mut len) {
+            Ok(()) => {}
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => break,
+            Err(err) => return Err(err),
+        }
+        let mut payload = vec![0u8; u32::from_le_bytes(len) as usize];
+        input.read_exact(&mut payload)?;
+        records.push(decode(&payload)?);
+    }
+    Ok(records)
+}
+
+#[allow(dead_code)]
+fn decode(payload: &[u8]) -> io::Result<ResultRecord> {
+    let mut cursor = Cursor { bytes: payload };
+    Ok(ResultRecord {
+        robots: cursor.u64()?,
+        tasks_per_robot: cursor.u64()?,
+        zones: cursor.u64()?,
+        total_tasks: cursor.u64()?,
+        elapsed_ms: cursor.f64()?,
+        throughput: cursor.f64()?,
+        avg_zone_wait_us: cursor.f64()?,
+        cpu_user_s: cursor.optional_f64()?,
+        cpu_sys_s: cursor.optional_f64()?,
+        max_occupancy: cursor.u64()?,
+        zone_violation: cursor.bool()?,
+        duplicate_tasks: cursor.bool()?,
+        offline_robots: cursor.u64()?,
+    })
+}
+
+#[allow(dead_code)]
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+// Reads fields front to back; trailing bytes from newer writers are ignored.
+#[allow(dead_code)]
+struct Cursor<'a> {
+    bytes: &'a [u8],
+}
+
+#[allow(dead_code)]
+impl Cursor<'_> {
+    fn take<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+        let (head, rest) = self
+            .bytes
+            .split_first_chunk::<N>()
+            .ok_or_else(|| invalid("truncated record"))?;
+        self.bytes = rest;
+        Ok(*head)
+    }
+
+    fn u64(&mut self) -> io::Result<u64> {
+        self.take().map(u64::from_le_bytes)
+    }
+
+    fn f64(&mut self) -> io::Result<f64> {
+        self.take().map(f64::from_le_bytes)
+    }
+
+    fn bool(&mut self) -> io::Result<bool> {
+        self.take::<1>().map(|[byte]| byte != 0)
+    }
+
+    fn optional_f64(&mut self) -> io::Result<Option<f64>> {
+        if self.bool()? {
+            self.f64().map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_round_trip() {
+        let records = vec![
+            ResultRecord {
+                robots: 4,
+                tasks_per_robot: 25,
+                zones: 2,
+                total_tasks: 100,
+                elapsed_ms: 131.25,
+                throughput: 761.9,
+                avg_zone_wait_us: 42.5,
+                cpu_user_s: Some(0.0125),
+                cpu_sys_s: None,
+                max_occupancy: 2,
+                zone_violation: false,
+                duplicate_tasks: true,
+                offline_robots: 1,
+            },
+            ResultRecord {
+                robots: 1,
+                tasks_per_robot: 1,
+                zones: 1,
+                total_tasks: 1,
+                elapsed_ms: 0.5,
+                throughput: 2000.0,
+                avg_zone_wait_us: 0.0,
+                cpu_user_s: None,
+                cpu_sys_s: Some(0.001),
+                max_occupancy: 1,
+                zone_violation: true,
+                duplicate_tasks: false,
+                offline_robots: 0,
+            },
+        ];
+        let mut bytes = Vec::new();
+        write_header(&mut bytes).unwrap();
+        for record in &records {
+            write_record(&mut bytes, record).unwrap();
+        }
+        assert_eq!(read_records(&mut bytes.as_slice()).unwrap(), records);
+
+        // A cut-off record is reported, not silently dropped.
+        bytes.pop();
+        let err = read_records(&mut bytes.as_slice()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        assert!(read_records(&mut &b"CSV!\x01"[..]).is_err());
+    }
+}
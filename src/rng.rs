@@ -0,0 +1,119 @@
+//! Small seeded PRNG so randomized simulation choices replay exactly.
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, LineWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Seed used when a run does not pass `--seed`.
+pub const DEFAULT_SEED: u64 = 0x5eed;
+
+/// Records every draw from the generators attached to it, as
+/// `seq,context,value` CSV lines, so a run's random decisions can be
+/// replayed by eye.
+pub struct RngLog {
+    out: Mutex<Box<dyn Write + Send>>,
+    seq: AtomicU64,
+}
+
+impl RngLog {
+    /// Log to `path`, truncating it.
+    pub fn create(path: &Path) -> io::Result<Self> {
+        Self::to_writer(LineWriter::new(File::create(path)?))
+    }
+
+    /// Log to any writer; the header line is written immediately.
+    pub fn to_writer(out: impl Write + Send + 'static) -> io::Result<Self> {
+        let mut out: Box<dyn Write + Send> = Box::new(out);
+        writeln!(out, "seq,context,value")?;
+        Ok(Self {
+            out: Mutex::new(out),
+            seq: AtomicU64::new(0),
+        })
+    }
+
+    fn record(&self, context: &str, value: u64) {
+        let mut out = self.out.lock().expect("rng log mutex poisoned");
+        // Numbered under the lock so file order matches sequence order.
+        let seq = self.seq.fetch_add(1, Ordering::Relaxed);
+        // A debugging aid must not fail the run it is watching.
+        let _ = writeln!(out, "{seq},{context},{value}");
+    }
+}
+
+impl fmt::Debug for RngLog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RngLog")
+            .field("draws", &self.seq.load(Ordering::Relaxed))
+            .finish()
+    }
+}
+
+/// SplitMix64: tiny, fast, and good enough for workload sampling.
+#[derive(Clone, Debug)]
+pub struct SplitMix64 {
+    state: u64,
+    log: Option<(Arc<RngLog>, String)>,
+}
+
+impl SplitMix64 {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            state: seed,
+            log: None,
+        }
+    }
+
+    /// Record every draw to `log` (when given) under `context`, which
+    /// should name the decision, e.g. `zone_pick` or `fail:robot=2`.
+    pub fn logged(mut self, log: Option<&Arc<RngLog>>, context: impl Into<String>) -> Self {
+        self.log = log.map(|log| (Arc::clone(log), context.into()));
+        self
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        let value = z ^ (z >> 31);
+        if let Some((log, context)) = self.log.as_ref() {
+            log.record(context, value);
+        }
+        value
+    }
+
+    /// Uniform value in `0..bound`; `bound` must be non-zero.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        debug_assert!(bound > 0, "bound must be > 0");
+        // Modulo bias is negligible for the small bounds used here.
+        self.next_u64() % bound
+    }
+
+    /// True with probability `p` (clamped to `0.0..=1.0`).
+    pub fn chance(&mut self, p: f64) -> bool {
+        // The top 53 bits give a uniform f64 in [0, 1).
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < p
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_replays_same_sequence() {
+        let mut a = SplitMix64::new(42);
+        let mut b = SplitMix64::new(42);
+        let mut c = SplitMix64::new(43);
+        let first: Vec<u64> = (0..8).map(|_| a.next_u64()).collect();
+        let second: Vec<u64> = (0..8).map(|_| b.next_u64()).collect();
+        let other: Vec<u64> = (0..8).map(|_| c.next_u64()).collect();
+        assert_eq!(first, second);
+        assert_ne!(first, other);
+        assert!((0..100).all(|_| a.below(7) < 7));
+        assert!((0..100).all(|_| !a.chance(0.0) && a.chance(1.0)));
+    }
+}
@@ -0,0 +1,202 @@
+//! Robot thread spawning that degrades instead of aborting when the OS
+//! refuses new threads (e.g. resource limits at high robot counts).
+//!
+//! Each failed spawn is retried with exponential backoff. Robots that still
+//! cannot get a thread run back to back on one shared thread, or on the
+//! joining thread if even that spawn fails.
+
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::log_warn;
+
+// Attempts per robot before it falls back to the shared thread.
+const SPAWN_ATTEMPTS: u32 = 4;
+// First retry delay; doubled after each failure.
+const SPAWN_BACKOFF_MS: u64 = 2;
+
+// A job stays reachable here while `spawn` may drop the closure holding it.
+type JobSlot<F> = Arc<Mutex<Option<F>>>;
+
+/// Running robot jobs; `join` returns their results in robot order.
+pub struct RobotThreads<T> {
+    spawned: Vec<(usize, JoinHandle<T>)>,
+    shared: Option<JoinHandle<Vec<(usize, T)>>>,
+    inline: Vec<(usize, Box<dyn FnOnce() -> T + Send>)>,
+}
+
+impl<T: Send + 'static> RobotThreads<T> {
+    /// Spawn one `robot-N` thread per job.
+    pub fn spawn<F>(jobs: Vec<F>) -> Self
+    where
+        F: FnOnce() -> T + Send + 'static,
+    {
+        Self::spawn_limited(jobs, None)
+    }
+
+    /// Like `spawn`, but every spawn past `limit` threads fails as if the OS
+    /// had refused it, so the fallback paths can be exercised.
+    pub fn spawn_limited<F>(jobs: Vec<F>, limit: Option<usize>) -> Self
+    where
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let mut started = 0usize;
+        let mut spawn_one = |name: String, job: Box<dyn FnOnce() -> T + Send>| {
+            if limit.is_some_and(|limit| started >= limit) {
+                return Err(io::Error::new(
+                    io::ErrorKind::WouldBlock,
+                    "thread limit reached",
+                ));
+            }
+            let handle = thread::Builder::new().name(name).spawn(job)?;
+            started += 1;
+            Ok(handle)
+        };
+
+        let mut spawned = Vec::with_capacity(jobs.len());
+        let mut leftover: Vec<(usize, F)> = Vec::new();
+        for (robot, job) in jobs.into_iter().enumerate() {
+            // Once a robot has exhausted its retries, later ones skip straight
+            // to the fallback rather than each paying the full backoff.
+            if !leftover.is_empty() {
+                leftover.push((robot, job));
+                continue;
+            }
+            match spawn_with_retry(format!("robot-{robot}"), job, &mut spawn_one) {
+                Ok(handle) => spawned.push((robot, handle)),
+                Err((job, err)) => {
+                    log_warn!("cannot spawn robot {robot} thread: {err}");
+                    leftover.push((robot, job));
+                }
+            }
+        }
+        if leftover.is_empty() {
+            return Self {
+                spawned,
+                shared: None,
+                inline: Vec::new(),
+            };
+        }
+
+        log_warn!(
+            "running {} robots back to back on a shared thread",
+            leftover.len()
+        );
+        let slot: JobSlot<Vec<(usize, F)>> = Arc::new(Mutex::new(Some(leftover)));
+        let shared_slot = Arc::clone(&slot);
+        let run_shared = Box::new(move || {
+            let jobs = take_job(&shared_slot);
+            jobs.into_iter()
+                .map(|(robot, job)| (robot, job()))
+                .collect()
+        });
+        let shared = thread::Builder::new()
+            .name("robots-shared".to_string())
+            .spawn(run_shared);
+        match shared {
+            Ok(handle) => Self {
+                spawned,
+                shared: Some(handle),
+                inline: Vec::new(),
+            },
+            Err(err) => {
+                log_warn!("cannot spawn shared robot thread: {err}; running robots on join");
+                let inline = take_job(&slot)
+                    .into_iter()
+                    .map(|(robot, job)| (robot, Box::new(job) as Box<dyn FnOnce() -> T + Send>))
+                    .collect();
+                Self {
+                    spawned,
+                    shared: None,
+                    inline,
+                }
+            }
+        }
+    }
+
+    /// Threads actually started; robots run on join are not counted.
+    pub fn thread_count(&self) -> usize {
+        self.spawned.len() + usize::from(self.shared.is_some())
+    }
+
+    /// Wait for every robot, running any that never got a thread.
+    pub fn join(self) -> Vec<T> {
+        let mut results: Vec<(usize, T)> = self
+            .spawned
+            .into_iter()
+            .map(|(robot, handle)| (robot, handle.join().expect("robot thread panicked")))
+            .collect();
+        if let Some(shared) = self.shared {
+            results.extend(shared.join().expect("shared robot thread panicked"));
+        }
+        results.extend(self.inline.into_iter().map(|(robot, job)| (robot, job())));
+        results.sort_by_key(|(robot, _)| *robot);
+        results.into_iter().map(|(_, result)| result).collect()
+    }
+}
+
+fn take_job<F>(slot: &JobSlot<F>) -> F {
+    slot.lock()
+        .expect("job slot mutex poisoned")
+        .take()
+        .expect("job taken once")
+}
+
+// Spawn `job`, backing off between failed attempts; hands the job back on
+// final failure so the caller can still run it.
+fn spawn_with_retry<T, F, S>(
+    name: String,
+    job: F,
+    spawn: &mut S,
+) -> Result<JoinHandle<T>, (F, io::Error)>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+    S: FnMut(String, Box<dyn FnOnce() -> T + Send>) -> io::Result<JoinHandle<T>>,
+{
+    let slot: JobSlot<F> = Arc::new(Mutex::new(Some(job)));
+    let mut backoff = Duration::from_millis(SPAWN_BACKOFF_MS);
+    let mut attempt = 1;
+    loop {
+        let thread_slot = Arc::clone(&slot);
+        match spawn(name.clone(), Box::new(move || take_job(&thread_slot)())) {
+            Ok(handle) => return Ok(handle),
+            Err(err) if attempt >= SPAWN_ATTEMPTS => return Err((take_job(&slot), err)),
+            Err(_) => {
+                thread::sleep(backoff);
+                backoff *= 2;
+                attempt += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jobs_past_the_limit_share_one_thread() {
+        let jobs: Vec<_> = (0..5).map(|robot| move || robot * 10).collect();
+        let threads = RobotThreads::spawn_limited(jobs, Some(2));
+        assert_eq!(threads.thread_count(), 3);
+        assert_eq!(threads.join(), vec![0, 10, 20, 30, 40]);
+    }
+
+    #[test]
+    fn zero_limit_runs_every_job_on_one_thread() {
+        let jobs: Vec<_> = (0..3)
+            .map(|_| || thread::current().name().map(str::to_string))
+            .collect();
+        let threads = RobotThreads::spawn_limited(jobs, Some(0));
+        assert_eq!(threads.thread_count(), 1);
+        let names = threads.join();
+        assert!(
+            names
+                .iter()
+                .all(|name| name.as_deref() == Some("robots-shared"))
+        );
+    }
+}
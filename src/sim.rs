@@ -1,15 +1,24 @@
 //! Simulation, benchmark, and stress-test runners for Project Blaze.
 
-use std::collections::HashSet;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::io;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Condvar, Mutex, OnceLock, mpsc};
 use std::thread;
 use std::time::{Duration, Instant};
 
-use crate::health_monitor::HealthMonitor;
+use crate::chrome_trace::{self, ZoneSpan};
+use crate::events::{BenchEvent, EventSink};
+use crate::health_monitor::{Clock, HealthMonitor, MockClock};
 use crate::log_dev;
-use crate::task_queue::TaskQueue;
-use crate::types::Task;
+use crate::log_warn;
+use crate::results::{self, ResultRecord};
+use crate::rng::{DEFAULT_SEED, RngLog, SplitMix64};
+use crate::robot_threads::RobotThreads;
+use crate::task_queue::{QueueDiscipline, TaskQueue};
+use crate::task_source::TaskSource;
+use crate::types::{RobotId, Task, TaskId, TaskOutcome, ZoneId};
 use crate::zones::ZoneAccess;
 
 // Demo/offline timing knobs (small for quick CLI feedback).
@@ -18,8 +27,37 @@ const DEMO_OFFLINE_MAX_WAIT_MS: u64 = 600;
 // Benchmark offline timing (looser to reduce false positives).
 const BENCH_OFFLINE_TIMEOUT_MS: u64 = 500;
 const BENCH_OFFLINE_MAX_WAIT_MS: u64 = 1000;
-// Polling interval used while waiting for offline detection.
-const OFFLINE_POLL_MS: u64 = 50;
+// Throughput series resolution; completions past the last bucket are counted
+// as overflow rather than binned.
+const THROUGHPUT_BUCKET_MS: u64 = 10;
+const THROUGHPUT_MAX_BUCKETS: usize = 1024;
+// Health scenario timing (fine-grained so latency measurements are meaningful).
+const HEALTH_TIMEOUT_MS: u64 = 100;
+const HEALTH_HEARTBEAT_MS: u64 = 10;
+const HEALTH_STOP_AFTER_MS: u64 = 100;
+const HEALTH_POLL_MS: u64 = 5;
+// Extra release attempts while the robot still owns the zone.
+const ZONE_RELEASE_RETRIES: usize = 3;
+// After this many back-to-back zone timeouts a robot blocks instead of requeueing.
+const ZONE_REQUEUE_LIMIT: usize = 8;
+// `--contention-only` robots all fight over this single zone.
+const CONTENTION_ZONE: ZoneId = 1;
+// Validate mode lists at most this many stranded task ids.
+const LEFTOVER_IDS_SHOWN: usize = 16;
+// Above this many zones, per-zone counters switch to a sparse map.
+const DENSE_ZONE_COUNTER_LIMIT: usize = 4096;
+// `--chaos`: each task has a 1-in-N chance of a slow release and, separately,
+// of a heartbeat held back past the offline timeout.
+const CHAOS_SLOW_RELEASE_ODDS: u64 = 8;
+const CHAOS_HEARTBEAT_DELAY_ODDS: u64 = 32;
+const CHAOS_SLOW_RELEASE_MS: u64 = 5;
+// How far past the offline timeout a delayed heartbeat lands; longer than the
+// bench monitor's 100ms poll so the lapse is always observed.
+const CHAOS_HEARTBEAT_OVERSHOOT_MS: u64 = 150;
+// Keeps `--fail-rate` draws independent of the chaos streams for one seed.
+const FAIL_RATE_SALT: u64 = 0xfa11_5a17;
+// Autotune stops scaling once doubling robots gains less than this fraction.
+const AUTOTUNE_MIN_GAIN: f64 = 0.10;
 
 /// Best-effort CPU user/system time snapshot (seconds) on Unix platforms.
 #[cfg(unix)]
@@ -71,27 +109,360 @@ fn spawn_health_monitor(
     stop_flag: Arc<AtomicBool>,
     timeout: Duration,
     poll: Duration,
-) -> thread::JoinHandle<()> {
-    thread::spawn(move || {
-        while !stop_flag.load(Ordering::SeqCst) {
-            let _ = monitor.detect_offline_any(timeout);
-            thread::sleep(poll);
+) -> io::Result<thread::JoinHandle<()>> {
+    thread::Builder::new()
+        .name("health-monitor".to_string())
+        .spawn(move || {
+            while !stop_flag.load(Ordering::SeqCst) {
+                // Sleeps on the monitor's condvar until a still-online robot's
+                // heartbeat expires; robots already offline do not wake it.
+                // `poll` only bounds how long a stop request can go unnoticed.
+                monitor.wait_for_new_offline(timeout, Instant::now() + poll);
+            }
+        })
+}
+
+/// FNV-1a of a task id. Completed tasks' hashes are summed, so the total
+/// does not depend on which robot finished what, or in which order.
+fn task_id_hash(id: TaskId) -> u64 {
+    id.to_le_bytes()
+        .iter()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+        })
+}
+
+/// Token bucket holding at most one token, refilled at `rate` per second,
+/// so a robot never bursts above its cap.
+struct RateLimiter {
+    interval: Duration,
+    // When the next token becomes available.
+    next: Instant,
+}
+
+impl RateLimiter {
+    fn new(rate: f64) -> Self {
+        Self {
+            interval: Duration::from_secs_f64(1.0 / rate),
+            next: Instant::now(),
+        }
+    }
+
+    /// Wait for a token and spend it.
+    fn take(&mut self) {
+        let now = Instant::now();
+        if self.next > now {
+            thread::sleep(self.next - now);
         }
+        // An idle robot does not bank tokens beyond the one it is spending.
+        self.next = self.next.max(now) + self.interval;
+    }
+}
+
+/// Sample one zone per task, zone `i + 1` with probability `weights[i] / sum`.
+fn weighted_zones(weights: &[u64], count: usize, mut rng: SplitMix64) -> Vec<ZoneId> {
+    let mut cumulative = Vec::with_capacity(weights.len());
+    let mut total = 0u64;
+    for &weight in weights {
+        total += weight;
+        cumulative.push(total);
+    }
+    debug_assert!(total > 0, "zone weights must not all be zero");
+    (0..count)
+        .map(|_| {
+            let pick = rng.below(total);
+            // First bucket whose upper edge lies past `pick`; zero weights are skipped.
+            cumulative.partition_point(|&edge| edge <= pick) as ZoneId + 1
+        })
+        .collect()
+}
+
+// Zone weights must cover exactly the configured zones.
+fn zone_weights_error(zone_weights: Option<&[u64]>, zones_total: u64) -> Option<String> {
+    let weights = zone_weights?;
+    (weights.len() as u64 != zones_total).then(|| {
+        format!(
+            "--zone-weights lists {} weights but zones={zones_total}",
+            weights.len()
+        )
     })
 }
 
+/// Start line for a run: robots park in `arrive` until `open` stamps the
+/// start time. Later arrivals pass straight through, so robots sharing a
+/// thread never hold up the start.
+struct StartGate {
+    // (robots arrived, start once opened)
+    state: Mutex<(usize, Option<Instant>)>,
+    changed: Condvar,
+}
+
+impl StartGate {
+    fn new() -> Self {
+        Self {
+            state: Mutex::new((0, None)),
+            changed: Condvar::new(),
+        }
+    }
+
+    fn arrive(&self) -> Instant {
+        let mut state = self.state.lock().expect("start gate mutex poisoned");
+        state.0 += 1;
+        self.changed.notify_all();
+        loop {
+            if let Some(start) = state.1 {
+                return start;
+            }
+            state = self.changed.wait(state).expect("start gate wait failed");
+        }
+    }
+
+    fn wait_for(&self, arrivals: usize) {
+        let mut state = self.state.lock().expect("start gate mutex poisoned");
+        while state.0 < arrivals {
+            state = self.changed.wait(state).expect("start gate wait failed");
+        }
+    }
+
+    fn open(&self, start: Instant) {
+        let mut state = self.state.lock().expect("start gate mutex poisoned");
+        state.1 = Some(start);
+        self.changed.notify_all();
+    }
+}
+
 /// Wait until at least one robot is offline or a max wait is reached.
 fn wait_for_offline(monitor: &HealthMonitor, timeout_ms: u64, max_wait_ms: u64) {
-    let max_wait = Duration::from_millis(max_wait_ms);
-    let poll = Duration::from_millis(OFFLINE_POLL_MS);
-    let timeout = Duration::from_millis(timeout_ms);
-    let start = Instant::now();
-    loop {
-        if monitor.detect_offline_any(timeout) || start.elapsed() >= max_wait {
-            return;
+    let deadline = Instant::now() + Duration::from_millis(max_wait_ms);
+    monitor.wait_for_any_offline(Duration::from_millis(timeout_ms), deadline);
+}
+
+/// Emit one structured trace line for a task lifecycle event (stderr).
+fn trace_event(start: Instant, robot: usize, task: TaskId, event: &str, detail: &str) {
+    eprintln!(
+        "# trace,t_us={},robot={robot},task={task},event={event}{detail}",
+        start.elapsed().as_micros()
+    );
+}
+
+/// Index of the robot that finished last and its lag behind the median
+/// finish time; `None` without any robots.
+fn straggler(finish_ms: &[f64]) -> Option<(usize, f64)> {
+    let (last, &latest) = finish_ms
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.total_cmp(b.1))?;
+    let mut sorted = finish_ms.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let median = sorted[sorted.len() / 2];
+    Some((last, latest - median))
+}
+
+/// One dashboard view across the queue, zones, and health monitor.
+///
+/// Each component is read under its own lock, so every group of fields is
+/// self-consistent; the groups are taken back to back, not atomically.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SimStatus {
+    pub queue_len: usize,
+    pub queue_pushed: u64,
+    pub queue_popped: u64,
+    /// Sorted ascending.
+    pub occupied_zones: Vec<ZoneId>,
+    /// Sorted ascending.
+    pub offline_robots: Vec<RobotId>,
+    pub online_robots: usize,
+}
+
+/// Assemble a `SimStatus` from the three shared components.
+#[allow(dead_code)]
+pub fn sim_status(queue: &TaskQueue, zones: &ZoneAccess, monitor: &HealthMonitor) -> SimStatus {
+    let counts = queue.counts();
+    let mut occupied_zones: Vec<ZoneId> = zones.occupied_zones().into_iter().collect();
+    occupied_zones.sort_unstable();
+    let (online_robots, offline) = monitor.online_offline();
+    let mut offline_robots: Vec<RobotId> = offline.into_iter().collect();
+    offline_robots.sort_unstable();
+    SimStatus {
+        queue_len: counts.len,
+        queue_pushed: counts.pushed,
+        queue_popped: counts.popped,
+        occupied_zones,
+        offline_robots,
+        online_robots,
+    }
+}
+
+/// What one bench robot reports back when its thread finishes.
+struct RobotOutcome {
+    finished: Instant,
+    checksum: u64,
+    completed: usize,
+    // None if the robot never got a task.
+    first_task: Option<TaskId>,
+    // Every zone wait as (began_us since the gate opened, waited_us).
+    waits: Vec<(u64, u64)>,
+    // Completed tasks reported as failed under `--fail-rate`.
+    failed: usize,
+}
+
+/// The benchmark's built-in workload: `count` tasks with sequential ids,
+/// zoned by `weights` when given.
+pub struct SyntheticTasks {
+    count: usize,
+    weighted: Option<Vec<ZoneId>>,
+    next_id: AtomicUsize,
+}
+
+impl SyntheticTasks {
+    /// `rng` picks each task's zone when `weights` are given.
+    pub fn new(count: usize, weights: Option<&[u64]>, rng: SplitMix64) -> Self {
+        Self {
+            count,
+            weighted: weights.map(|weights| weighted_zones(weights, count, rng)),
+            next_id: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl TaskSource for SyntheticTasks {
+    fn next(&self) -> Option<Task> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        if id >= self.count {
+            return None;
+        }
+        let task = Task::new(id as u64, format!("bench-{id}"));
+        Some(match self.weighted.as_ref() {
+            Some(zones) => task.with_zone(zones[id]),
+            None => task,
+        })
+    }
+}
+
+/// The `count` tasks a benchmark queues, zoned by `weights` when given;
+/// zone picks are recorded to `rng_log` as `zone_pick`.
+fn bench_tasks(
+    count: usize,
+    weights: Option<&[u64]>,
+    seed: u64,
+    rng_log: Option<&Arc<RngLog>>,
+) -> Vec<Task> {
+    let rng = SplitMix64::new(seed).logged(rng_log, "zone_pick");
+    let source = SyntheticTasks::new(count, weights, rng);
+    std::iter::from_fn(|| source.next()).collect()
+}
+
+/// Why a benchmark cannot run `task` on zones `1..=zones_total`, if its
+/// explicit zone is out of that range.
+fn zone_range_error(task: &Task, zones_total: u64) -> Option<String> {
+    let zone = task.target_zone(zones_total);
+    (!(1..=zones_total).contains(&zone)).then(|| {
+        format!(
+            "task {} targets zone {zone} outside 1..={zones_total}",
+            task.id
+        )
+    })
+}
+
+// Reject a task set a benchmark could not run, before any robot starts.
+fn check_bench_tasks(queue: &TaskQueue, tasks: &[Task], zones_total: u64) -> io::Result<()> {
+    let invalid = |err| io::Error::new(io::ErrorKind::InvalidInput, err);
+    if let Some(err) = tasks
+        .iter()
+        .find_map(|task| zone_range_error(task, zones_total))
+    {
+        return Err(invalid(err));
+    }
+    queue.check_dependencies(tasks).map_err(invalid)
+}
+
+/// Whether `leftover` is explained: nothing was stranded, or an offline
+/// robot left at least that many tasks of its share unfinished.
+fn leftover_is_expected(leftover: usize, offline_unfinished: Option<usize>) -> bool {
+    leftover == 0 || offline_unfinished.is_some_and(|unfinished| leftover <= unfinished)
+}
+
+/// Population standard deviation of per-robot finish times; 0 for fewer
+/// than two robots.
+fn stddev_ms(finish_ms: &[f64]) -> f64 {
+    if finish_ms.len() < 2 {
+        return 0.0;
+    }
+    let n = finish_ms.len() as f64;
+    let mean = finish_ms.iter().sum::<f64>() / n;
+    let variance = finish_ms.iter().map(|ms| (ms - mean).powi(2)).sum::<f64>() / n;
+    variance.sqrt()
+}
+
+/// Mean wait per task in microseconds from a nanosecond total; converting
+/// only here keeps many sub-microsecond waits from summing to zero.
+fn avg_wait_us(total_wait_ns: u64, tasks: usize) -> f64 {
+    if tasks == 0 {
+        return 0.0;
+    }
+    total_wait_ns as f64 / 1000.0 / tasks as f64
+}
+
+/// Mean zone wait for waits that began before `cutoff_us` (startup) and
+/// those that began later (steady state); 0 for an empty bucket.
+fn phase_avg_wait_us(waits: &[(u64, u64)], cutoff_us: u64) -> (f64, f64) {
+    let mut startup = (0u64, 0u64);
+    let mut steady = (0u64, 0u64);
+    for &(began_us, waited_us) in waits {
+        let bucket = if began_us < cutoff_us {
+            &mut startup
+        } else {
+            &mut steady
+        };
+        bucket.0 += waited_us;
+        bucket.1 += 1;
+    }
+    let avg = |(total, count): (u64, u64)| {
+        if count == 0 {
+            0.0
+        } else {
+            total as f64 / count as f64
+        }
+    };
+    (avg(startup), avg(steady))
+}
+
+/// Release via `release`, retrying a bounded number of times while `robot`
+/// is still recorded as the zone owner. Returns whether the zone was freed.
+fn release_with_retry(
+    zones: &ZoneAccess,
+    zone: ZoneId,
+    robot: RobotId,
+    mut release: impl FnMut() -> bool,
+) -> bool {
+    if release() {
+        return true;
+    }
+    for _ in 0..ZONE_RELEASE_RETRIES {
+        if zones.owner(zone) != Some(robot) {
+            // Someone else owns it (or nobody does); retrying cannot help.
+            return false;
+        }
+        if release() {
+            return true;
         }
-        thread::sleep(poll);
     }
+    eprintln!(
+        "[ZONE] release gave up after {} attempts: zone={zone} robot={robot}",
+        ZONE_RELEASE_RETRIES + 1
+    );
+    false
+}
+
+// Warn when a run has far more zones than tasks: most zones stay empty, yet
+// the sparse counters still cost a global lock on each zone enter and exit.
+fn sparse_counter_warning(zones_total: usize, total_tasks: usize) -> Option<String> {
+    (zones_total > DENSE_ZONE_COUNTER_LIMIT && zones_total > total_tasks).then(|| {
+        format!(
+            "{zones_total} zones for {total_tasks} tasks; sparse zone counters add a \
+             global mutex to every zone enter and exit"
+        )
+    })
 }
 
 /// Pre-size per-zone occupancy counters (index 1..=zones_total).
@@ -103,32 +474,244 @@ fn init_zone_counters(zones_total: usize) -> Vec<AtomicUsize> {
     counters
 }
 
+/// Per-zone occupancy counters: a lock-free vector for realistic zone counts,
+/// or a sparse map once the vector would be wastefully large. The sparse map
+/// sits behind one mutex, so every zone enter and exit then serializes on it.
+enum ZoneCounters {
+    Dense(Vec<AtomicUsize>),
+    // Only zones that have ever been entered take up space.
+    Sparse(Mutex<HashMap<ZoneId, usize>>),
+}
+
+impl ZoneCounters {
+    fn new(zones_total: usize, total_tasks: usize) -> Self {
+        if zones_total <= DENSE_ZONE_COUNTER_LIMIT {
+            Self::Dense(init_zone_counters(zones_total))
+        } else {
+            if let Some(warning) = sparse_counter_warning(zones_total, total_tasks) {
+                log_warn!("{warning}");
+            }
+            Self::Sparse(Mutex::new(HashMap::new()))
+        }
+    }
+
+    /// Increment a zone's counter and return the new value.
+    fn increment(&self, zone: ZoneId) -> usize {
+        match self {
+            Self::Dense(counters) => counters[zone as usize].fetch_add(1, Ordering::SeqCst) + 1,
+            Self::Sparse(map) => {
+                let mut guard = map.lock().expect("zone counter mutex poisoned");
+                let count = guard.entry(zone).or_insert(0);
+                *count += 1;
+                *count
+            }
+        }
+    }
+
+    /// Decrement a zone's counter and return the previous value.
+    fn decrement(&self, zone: ZoneId) -> usize {
+        match self {
+            Self::Dense(counters) => counters[zone as usize].fetch_sub(1, Ordering::SeqCst),
+            Self::Sparse(map) => {
+                let mut guard = map.lock().expect("zone counter mutex poisoned");
+                let count = guard.entry(zone).or_insert(0);
+                let prev = *count;
+                *count = prev.wrapping_sub(1);
+                prev
+            }
+        }
+    }
+}
+
+/// Records task completions into fixed-width elapsed-time buckets.
+struct ThroughputSeries {
+    buckets: Vec<AtomicUsize>,
+    // Completions after the last bucket; binning them would fake a spike.
+    overflow: AtomicUsize,
+}
+
+impl ThroughputSeries {
+    fn new() -> Self {
+        Self {
+            buckets: (0..THROUGHPUT_MAX_BUCKETS)
+                .map(|_| AtomicUsize::new(0))
+                .collect(),
+            overflow: AtomicUsize::new(0),
+        }
+    }
+
+    fn record(&self, elapsed: Duration) {
+        let index = (elapsed.as_millis() / THROUGHPUT_BUCKET_MS as u128) as usize;
+        match self.buckets.get(index) {
+            Some(bucket) => bucket.fetch_add(1, Ordering::SeqCst),
+            None => self.overflow.fetch_add(1, Ordering::SeqCst),
+        };
+    }
+
+    /// Completions that landed past the last bucket.
+    fn overflow(&self) -> usize {
+        self.overflow.load(Ordering::SeqCst)
+    }
+
+    /// Bucket counts up to and including the last non-empty bucket.
+    fn snapshot(&self) -> Vec<usize> {
+        let mut counts: Vec<usize> = self
+            .buckets
+            .iter()
+            .map(|count| count.load(Ordering::SeqCst))
+            .collect();
+        while counts.last() == Some(&0) {
+            counts.pop();
+        }
+        counts
+    }
+}
+
 /// Tracks aggregate and per-zone occupancy metrics and constraint violations.
 struct ZoneMetrics {
     occupancy: AtomicUsize,
     max_occupancy: AtomicUsize,
     zone_violation: AtomicBool,
-    per_zone_occupancy: Vec<AtomicUsize>,
+    per_zone_occupancy: ZoneCounters,
+    // Longest single wait each robot saw on each zone, for fairness reporting.
+    max_waits_us: Mutex<HashMap<(ZoneId, RobotId), u64>>,
+    // Total time each zone was held and the number of holds, for utilization
+    // and average-hold reporting.
+    hold_us: Mutex<BTreeMap<ZoneId, (u64, u64)>>,
+    // Set once, by whichever `enter` first flips the violation flag.
+    first_violation: OnceLock<(Instant, ZoneId, RobotId, usize)>,
+    // Called with (zone, observed occupancy) on the first violation only.
+    on_violation: Mutex<Option<ViolationCallback>>,
+}
+
+type ViolationCallback = Arc<dyn Fn(ZoneId, usize) + Send + Sync>;
+
+/// Details of the first zone-exclusivity violation observed in a run.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FirstViolation {
+    /// Milliseconds from run start.
+    pub at_ms: f64,
+    pub zone: ZoneId,
+    pub robot: RobotId,
+    /// Occupancy count that exceeded the limit.
+    pub observed: usize,
 }
 
 impl ZoneMetrics {
-    fn new(zones_total: usize) -> Self {
+    fn new(zones_total: usize, total_tasks: usize) -> Self {
         Self {
             occupancy: AtomicUsize::new(0),
             max_occupancy: AtomicUsize::new(0),
             zone_violation: AtomicBool::new(false),
-            per_zone_occupancy: init_zone_counters(zones_total),
+            per_zone_occupancy: ZoneCounters::new(zones_total, total_tasks),
+            max_waits_us: Mutex::new(HashMap::new()),
+            hold_us: Mutex::new(BTreeMap::new()),
+            first_violation: OnceLock::new(),
+            on_violation: Mutex::new(None),
+        }
+    }
+
+    /// Register a callback fired the instant the first violation is seen,
+    /// with the zone and its observed occupancy; replaces any earlier one.
+    fn on_first_violation(&self, callback: impl Fn(ZoneId, usize) + Send + Sync + 'static) {
+        let mut guard = self.on_violation.lock().expect("callback mutex poisoned");
+        *guard = Some(Arc::new(callback));
+    }
+
+    fn flag_violation(&self, zone: ZoneId, robot: RobotId, observed: usize) {
+        self.zone_violation.store(true, Ordering::SeqCst);
+        let first = self
+            .first_violation
+            .set((Instant::now(), zone, robot, observed))
+            .is_ok();
+        if first {
+            // Clone out so the callback runs without the mutex held.
+            let callback = self
+                .on_violation
+                .lock()
+                .expect("callback mutex poisoned")
+                .clone();
+            if let Some(callback) = callback {
+                callback(zone, observed);
+            }
         }
     }
 
-    fn enter(&self, zone: u64, zones_total: usize) {
+    /// First violation relative to `start`, if any occurred.
+    fn first_violation(&self, start: Instant) -> Option<FirstViolation> {
+        self.first_violation
+            .get()
+            .map(|&(at, zone, robot, observed)| FirstViolation {
+                at_ms: at.saturating_duration_since(start).as_secs_f64() * 1000.0,
+                zone,
+                robot,
+                observed,
+            })
+    }
+
+    fn record_wait(&self, zone: ZoneId, robot: RobotId, waited_us: u64) {
+        let mut guard = self.max_waits_us.lock().expect("wait mutex poisoned");
+        let slot = guard.entry((zone, robot)).or_insert(0);
+        *slot = (*slot).max(waited_us);
+    }
+
+    /// Worst wait any single robot observed on any zone.
+    fn max_single_wait_us(&self) -> u64 {
+        let guard = self.max_waits_us.lock().expect("wait mutex poisoned");
+        guard.values().copied().max().unwrap_or(0)
+    }
+
+    fn record_hold(&self, zone: ZoneId, held_us: u64) {
+        let mut guard = self.hold_us.lock().expect("hold mutex poisoned");
+        let (total_us, holds) = guard.entry(zone).or_insert((0, 0));
+        *total_us += held_us;
+        *holds += 1;
+    }
+
+    /// Mean hold time per held zone, in milliseconds, by zone id.
+    fn avg_hold_ms(&self) -> Vec<(ZoneId, f64)> {
+        let guard = self.hold_us.lock().expect("hold mutex poisoned");
+        guard
+            .iter()
+            .map(|(&zone, &(total_us, holds))| (zone, total_us as f64 / holds as f64 / 1000.0))
+            .collect()
+    }
+
+    /// Tasks each held zone served per second over `elapsed_ms`, by zone id;
+    /// the rates sum to the run's throughput.
+    fn serve_rate(&self, elapsed_ms: f64) -> Vec<(ZoneId, f64)> {
+        if elapsed_ms <= 0.0 {
+            return Vec::new();
+        }
+        let guard = self.hold_us.lock().expect("hold mutex poisoned");
+        guard
+            .iter()
+            .map(|(&zone, &(_, holds))| (zone, holds as f64 / (elapsed_ms / 1000.0)))
+            .collect()
+    }
+
+    /// Percentage of `elapsed` each held zone was occupied, by zone id.
+    ///
+    /// Zones nobody entered are left out so huge zone counts stay cheap.
+    fn utilization_pct(&self, elapsed: Duration) -> Vec<(ZoneId, f64)> {
+        let elapsed_us = elapsed.as_micros().max(1) as f64;
+        let guard = self.hold_us.lock().expect("hold mutex poisoned");
+        guard
+            .iter()
+            // Hold timers stop just after release, so back-to-back holds can
+            // overlap by a few microseconds; clamp rather than report >100%.
+            .map(|(&zone, &(held_us, _))| (zone, (held_us as f64 / elapsed_us * 100.0).min(100.0)))
+            .collect()
+    }
+
+    fn enter(&self, zone: u64, robot: RobotId, zones_total: usize) {
         let current = self.occupancy.fetch_add(1, Ordering::SeqCst) + 1;
         let zone_index = zone as usize;
         // Zone ids are 1-based; index 0 is unused.
         debug_assert!(zone_index <= zones_total, "zone index out of range");
-        let zone_count = self.per_zone_occupancy[zone_index].fetch_add(1, Ordering::SeqCst) + 1;
+        let zone_count = self.per_zone_occupancy.increment(zone);
         if zone_count > 1 {
-            self.zone_violation.store(true, Ordering::SeqCst);
+            self.flag_violation(zone, robot, zone_count);
         }
         let mut prev = self.max_occupancy.load(Ordering::SeqCst);
         while current > prev {
@@ -143,14 +726,14 @@ impl ZoneMetrics {
             }
         }
         if current > zones_total {
-            self.zone_violation.store(true, Ordering::SeqCst);
+            self.flag_violation(zone, robot, current);
         }
     }
 
     fn pre_release(&self, zone: u64, zones_total: usize) {
         let zone_index = zone as usize;
         debug_assert!(zone_index <= zones_total, "zone index out of range");
-        let zone_prev = self.per_zone_occupancy[zone_index].fetch_sub(1, Ordering::SeqCst);
+        let zone_prev = self.per_zone_occupancy.decrement(zone);
         debug_assert!(zone_prev > 0, "zone counter underflow");
         let occ_prev = self.occupancy.fetch_sub(1, Ordering::SeqCst);
         debug_assert!(occ_prev > 0, "occupancy counter underflow");
@@ -159,7 +742,7 @@ impl ZoneMetrics {
     fn revert_pre_release(&self, zone: u64, zones_total: usize) {
         let zone_index = zone as usize;
         debug_assert!(zone_index <= zones_total, "zone index out of range");
-        self.per_zone_occupancy[zone_index].fetch_add(1, Ordering::SeqCst);
+        self.per_zone_occupancy.increment(zone);
         self.occupancy.fetch_add(1, Ordering::SeqCst);
     }
 
@@ -172,124 +755,695 @@ impl ZoneMetrics {
     }
 }
 
+/// Parameters for a single benchmark run.
+#[derive(Clone, Debug)]
+pub struct BenchConfig {
+    pub robots: usize,
+    pub tasks_per_robot: usize,
+    pub zones_total: u64,
+    pub work_ms: u64,
+    pub validate: bool,
+    pub simulate_offline: bool,
+    /// Log each task's lifecycle to stderr, even in release builds.
+    pub trace: bool,
+    /// With `simulate_offline`, resume heartbeats after this many extra tasks.
+    pub offline_recover_after: Option<usize>,
+    /// Give up on a busy zone after this long and requeue the task instead.
+    pub zone_acquire_timeout_ms: Option<u64>,
+    /// Skip the queue and work sleeps; hammer a single zone instead.
+    pub contention_only: bool,
+    /// Stream per-task lifecycle events as JSON Lines while the run proceeds.
+    pub events: Option<Arc<EventSink>>,
+    /// How results are printed; does not affect the run itself.
+    pub format: OutputFormat,
+    /// Busy-wait for zones instead of blocking, to compare CPU cost.
+    pub spin_zones: bool,
+    /// Which end of the queue robots take tasks from.
+    pub queue_discipline: QueueDiscipline,
+    /// Spawns past this many robot threads fail as if the OS refused them.
+    pub thread_limit: Option<usize>,
+    /// Sample each task's zone from these weights (zone `i + 1` gets entry `i`).
+    pub zone_weights: Option<Vec<u64>>,
+    /// Seed for every randomized choice in the run.
+    pub seed: u64,
+    /// Cap each robot's task consumption at this many tasks per second.
+    pub max_rate: Option<f64>,
+    /// Write zone hold spans here as a Chrome Trace Event JSON file.
+    pub trace_out: Option<PathBuf>,
+    /// Queue a prefix of this canonical task set instead of generating tasks.
+    /// Tasks here may carry `depends_on`; tasks drawn from `task_source`
+    /// skip the queue, so their dependencies are not enforced.
+    pub fixed_tasks: Option<Arc<Vec<Task>>>,
+    /// Bring one extra robot online this long after the run starts.
+    pub add_robot_after_ms: Option<u64>,
+    /// Travel time between tasks; per zone of distance when tasks carry zones.
+    pub travel_ms: u64,
+    /// Seed for injected faults: slow releases and late heartbeats. Implies
+    /// invariant validation.
+    pub chaos: Option<u64>,
+    /// Work time by target zone (zone `i + 1` gets entry `i`, cycling when
+    /// shorter than the zone count); overrides `work_ms`.
+    pub work_ms_per_zone: Option<Vec<u64>>,
+    /// Run every robot on the calling thread in fixed round-robin order.
+    pub deterministic: bool,
+    /// Report stranded tasks as an error rather than a warning.
+    pub strict_leftover: bool,
+    /// Extra attempts `run_benchmark` makes when a run cannot start.
+    pub retries: u32,
+    /// Report each processed task as failed with this probability.
+    pub fail_rate: Option<f64>,
+    /// Send a `TaskOutcome` here for every processed task.
+    pub outcomes: Option<mpsc::Sender<TaskOutcome>>,
+    /// Record every seeded random draw (zone picks, chaos, failures) here.
+    pub rng_log: Option<Arc<RngLog>>,
+    /// Robot `i` takes tasks for zone `i % zones + 1` first and only falls
+    /// back to the queue head when none are queued. Ignored with a
+    /// `task_source`, whose tasks are not queued.
+    pub affinity: bool,
+    /// Pull tasks from this source as robots need them instead of queueing
+    /// the synthetic workload up front; each robot still stops after
+    /// `tasks_per_robot`, so an endless source is fine.
+    pub task_source: Option<Arc<dyn TaskSource>>,
+    /// Queue exactly this many tasks instead of `robots * tasks_per_robot`;
+    /// robots then drain the shared queue with no personal share.
+    pub total_tasks: Option<usize>,
+}
+
+impl BenchConfig {
+    /// Tasks a run queues: `total_tasks`, else one share per robot.
+    pub fn task_count(&self) -> usize {
+        self.total_tasks
+            .unwrap_or(self.robots * self.tasks_per_robot)
+    }
+}
+
+impl Default for BenchConfig {
+    fn default() -> Self {
+        Self {
+            robots: 4,
+            tasks_per_robot: 25,
+            zones_total: 2,
+            work_ms: 5,
+            validate: false,
+            simulate_offline: false,
+            trace: false,
+            offline_recover_after: None,
+            zone_acquire_timeout_ms: None,
+            contention_only: false,
+            events: None,
+            format: OutputFormat::Csv,
+            spin_zones: false,
+            queue_discipline: QueueDiscipline::Fifo,
+            thread_limit: None,
+            zone_weights: None,
+            seed: DEFAULT_SEED,
+            max_rate: None,
+            trace_out: None,
+            fixed_tasks: None,
+            add_robot_after_ms: None,
+            travel_ms: 0,
+            chaos: None,
+            work_ms_per_zone: None,
+            deterministic: false,
+            strict_leftover: false,
+            task_source: None,
+            retries: 0,
+            affinity: false,
+            fail_rate: None,
+            outcomes: None,
+            rng_log: None,
+            total_tasks: None,
+        }
+    }
+}
+
 /// Aggregated metrics from a single benchmark run.
-struct BenchResult {
-    robots: usize,
-    tasks_per_robot: usize,
-    zones_total: u64,
-    total_tasks: usize,
-    elapsed_ms: f64,
-    throughput: f64,
-    avg_zone_wait_us: f64,
-    cpu_user_s: Option<f64>,
-    cpu_sys_s: Option<f64>,
-    leftover: usize,
-    max_occupancy: usize,
-    zone_violation: bool,
-    duplicate_tasks: bool,
-    offline_count: usize,
-}
-
-fn benchmark_once(
-    robots: usize,
-    tasks_per_robot: usize,
-    zones_total: u64,
-    work_ms: u64,
-    validate: bool,
-    simulate_offline: bool,
-) -> BenchResult {
+pub struct BenchResult {
+    pub robots: usize,
+    pub tasks_per_robot: usize,
+    pub zones_total: u64,
+    pub total_tasks: usize,
+    pub elapsed_ms: f64,
+    pub throughput: f64,
+    pub avg_zone_wait_us: f64,
+    /// Mean zone wait for waits begun in the first tenth of the run, when
+    /// every robot rushes for a zone at once.
+    pub startup_avg_wait_us: f64,
+    /// Mean zone wait for waits begun after the first tenth of the run.
+    pub steady_avg_wait_us: f64,
+    /// Longest single zone wait by any robot; a starvation signal.
+    pub max_single_zone_wait_us: u64,
+    /// Share of the run each entered zone was held, as `(zone, percent)`.
+    pub zone_utilization_pct: Vec<(ZoneId, f64)>,
+    /// Mean hold time of each entered zone, as `(zone, ms)`.
+    pub zone_avg_hold_ms: Vec<(ZoneId, f64)>,
+    /// Tasks each entered zone served per second, as `(zone, rate)`.
+    pub zone_rate: Vec<(ZoneId, f64)>,
+    /// Order-independent hash of every completed task id; equal for runs
+    /// that covered the same task set.
+    pub task_checksum: u64,
+    /// Offline timeout the observed heartbeat cadence suggests.
+    pub suggested_timeout_ms: Option<f64>,
+    /// Condvar waits that returned with nothing to do, per primitive.
+    pub queue_spurious_wakeups: u64,
+    pub zone_spurious_wakeups: u64,
+    pub cpu_user_s: Option<f64>,
+    pub cpu_sys_s: Option<f64>,
+    pub leftover: usize,
+    /// Stranded task ids in queue order; only collected in validate mode.
+    pub leftover_ids: Vec<TaskId>,
+    /// False when tasks were stranded that no offline robot accounts for,
+    /// which points at an accounting bug.
+    pub leftover_expected: bool,
+    /// Robot whose thread finished last.
+    pub straggler_robot: Option<RobotId>,
+    /// How far the straggler finished behind the median robot.
+    pub straggler_lag_ms: f64,
+    /// Spread of robot finish times; near zero when load is balanced.
+    pub completion_time_stddev_ms: f64,
+    /// Tasks finished by the robot added mid-run, if one was added.
+    pub late_robot_tasks: Option<usize>,
+    /// Robots the health monitor knew about when the run ended.
+    pub tracked_robots: usize,
+    /// First task each robot popped, by robot id; shows the startup race.
+    pub first_task_per_robot: Vec<Option<TaskId>>,
+    /// Tasks each robot completed, by robot id.
+    pub per_robot_completed: Vec<usize>,
+    /// Completed tasks reported as failed (only under `fail_rate`); the
+    /// rest succeeded.
+    pub failed_tasks: usize,
+    /// Faults injected by `--chaos`: zone holds stretched before release.
+    pub chaos_slow_releases: usize,
+    /// Faults injected by `--chaos`: heartbeats held back past the timeout.
+    pub chaos_heartbeat_delays: usize,
+    pub max_occupancy: usize,
+    pub zone_violation: bool,
+    pub first_violation: Option<FirstViolation>,
+    pub duplicate_tasks: bool,
+    pub offline_count: usize,
+    /// Robots whose heartbeat cleared an earlier offline mark.
+    pub recovered_count: usize,
+    /// Tasks pushed back after a zone acquisition timed out.
+    pub requeued_tasks: usize,
+    pub throughput_series: Vec<usize>,
+    /// Completions too late for `throughput_series`, which covers only the
+    /// first `THROUGHPUT_MAX_BUCKETS` buckets.
+    pub throughput_series_overflow: usize,
+    /// True when the run was stopped early via `RunHandle::cancel`.
+    pub canceled: bool,
+}
+
+/// Handle to a benchmark running on a background thread.
+#[allow(dead_code)]
+pub struct RunHandle {
+    cancel: Arc<AtomicBool>,
+    handle: thread::JoinHandle<BenchResult>,
+}
+
+#[allow(dead_code)]
+impl RunHandle {
+    /// Ask robots to stop before fetching their next task.
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::SeqCst);
+    }
+
+    /// Wait for the run to finish and return its (possibly partial) result.
+    pub fn join(self) -> BenchResult {
+        self.handle
+            .join()
+            .expect("benchmark runner thread panicked")
+    }
+}
+
+/// Start a benchmark without blocking; cancel or join it via the handle.
+#[allow(dead_code)]
+pub fn spawn_benchmark(config: BenchConfig) -> RunHandle {
+    let cancel = Arc::new(AtomicBool::new(false));
+    let runner_cancel = Arc::clone(&cancel);
+    let handle = thread::spawn(move || benchmark_run(&config, runner_cancel));
+    RunHandle { cancel, handle }
+}
+
+fn benchmark_once(config: &BenchConfig) -> BenchResult {
+    benchmark_run(config, Arc::new(AtomicBool::new(false)))
+}
+
+fn benchmark_run(config: &BenchConfig, cancel: Arc<AtomicBool>) -> BenchResult {
+    try_benchmark_run(config, cancel).expect("benchmark could not start")
+}
+
+/// One benchmark attempt. `Err` means the run never started: either the OS
+/// refused a resource it needed, which is worth retrying, or the task set
+/// has dependencies no robot could satisfy (`InvalidInput`). Invariant
+/// violations are reported in the result and never turn into errors.
+fn try_benchmark_run(config: &BenchConfig, cancel: Arc<AtomicBool>) -> io::Result<BenchResult> {
+    let BenchConfig {
+        robots,
+        tasks_per_robot,
+        zones_total,
+        work_ms,
+        validate,
+        simulate_offline,
+        trace,
+        offline_recover_after,
+        zone_acquire_timeout_ms,
+        contention_only: _,
+        ref events,
+        format: _,
+        spin_zones,
+        queue_discipline,
+        thread_limit,
+        ref zone_weights,
+        seed,
+        max_rate,
+        ref trace_out,
+        ref fixed_tasks,
+        add_robot_after_ms,
+        travel_ms,
+        chaos,
+        ref work_ms_per_zone,
+        deterministic,
+        strict_leftover: _,
+        ref task_source,
+        retries: _,
+        affinity,
+        fail_rate,
+        ref outcomes,
+        ref rng_log,
+        total_tasks: budget,
+    } = *config;
+    if deterministic {
+        return benchmark_deterministic(config, &cancel);
+    }
+    // Chaos runs exist to check the invariants, so always track them.
+    let validate = validate || chaos.is_some();
     debug_assert!(robots > 0, "robots must be > 0");
     debug_assert!(tasks_per_robot > 0, "tasks_per_robot must be > 0");
     debug_assert!(zones_total > 0, "zones_total must be > 0");
     let zones_len = zones_total as usize;
-    let queue = Arc::new(TaskQueue::new());
-    let zones = Arc::new(ZoneAccess::new());
+    let queue = Arc::new(TaskQueue::new_with_discipline(queue_discipline));
+    let zones = Arc::new(if spin_zones {
+        ZoneAccess::new_spinning()
+    } else {
+        ZoneAccess::new()
+    });
     let monitor = Arc::new(HealthMonitor::new());
     let stop_flag = Arc::new(AtomicBool::new(false));
 
-    let total_tasks = robots * tasks_per_robot;
-    for id in 0..total_tasks {
-        queue
-            .push(Task::new(id as u64, format!("bench-{id}")))
-            .expect("task queue closed");
-    }
+    let total_tasks = config.task_count();
+    let tasks = match (task_source, fixed_tasks) {
+        // Streamed: robots draw from the source; the queue only holds requeues.
+        (Some(_), _) => Vec::new(),
+        (None, Some(canonical)) => {
+            debug_assert!(canonical.len() >= total_tasks, "fixed task set too small");
+            canonical.iter().take(total_tasks).cloned().collect()
+        }
+        (None, None) => bench_tasks(total_tasks, zone_weights.as_deref(), seed, rng_log.as_ref()),
+    };
+    check_bench_tasks(&queue, &tasks, zones_total)?;
+    queue.push_many(tasks).expect("task queue closed");
     let total_tasks = queue.len();
+    let drawn = Arc::new(AtomicUsize::new(0));
 
-    // Total wait time across all zone acquisitions for averaging.
-    let zone_wait_us = Arc::new(std::sync::atomic::AtomicU64::new(0));
-    let zone_metrics = Arc::new(ZoneMetrics::new(zones_len));
+    // Total wait time across all zone acquisitions for averaging, in
+    // nanoseconds so sub-microsecond waits are not truncated to zero.
+    let zone_wait_ns = Arc::new(std::sync::atomic::AtomicU64::new(0));
+    let zone_metrics = Arc::new(ZoneMetrics::new(zones_len, config.task_count()));
+    if validate {
+        // Page immediately rather than waiting for the end-of-run summary.
+        zone_metrics.on_first_violation(|zone, observed| {
+            eprintln!("# alert,zone_violation,zone={zone},observed={observed}");
+        });
+    }
+    let series = Arc::new(ThroughputSeries::new());
     let duplicate_tasks = Arc::new(AtomicBool::new(false));
+    let recovered = Arc::new(AtomicUsize::new(0));
+    let requeued = Arc::new(AtomicUsize::new(0));
+    let chaos_slow_releases = Arc::new(AtomicUsize::new(0));
+    let chaos_heartbeat_delays = Arc::new(AtomicUsize::new(0));
     let seen_tasks = if validate {
         Some(Arc::new(Mutex::new(HashSet::new())))
     } else {
         None
     };
+    let trace_spans = trace_out.as_ref().map(|_| Arc::new(Mutex::new(Vec::new())));
 
-    for robot_id in 0..robots {
-        monitor.register_robot(robot_id as u64);
-    }
+    monitor.register_robots(0..robots as RobotId);
 
     let monitor_thread = spawn_health_monitor(
         Arc::clone(&monitor),
         Arc::clone(&stop_flag),
         Duration::from_millis(BENCH_OFFLINE_TIMEOUT_MS),
         Duration::from_millis(100),
-    );
+    )?;
 
-    let mut handles = Vec::new();
-    let cpu_start = cpu_times_seconds();
-    let start = Instant::now();
-    for robot_id in 0..robots {
+    // With a late robot or a global budget nobody has a fixed share; robots
+    // drain the queue until the last task completes and closes it. A
+    // streamed source may never run dry, so it keeps the fixed share.
+    let shared = add_robot_after_ms.is_some() || budget.is_some();
+    let quota = if shared && task_source.is_none() {
+        usize::MAX
+    } else {
+        tasks_per_robot
+    };
+    let outstanding = Arc::new(AtomicUsize::new(total_tasks));
+    let gate = Arc::new(StartGate::new());
+    let make_robot = |robot_id: usize| {
+        let gate = Arc::clone(&gate);
+        let outstanding = Arc::clone(&outstanding);
+        let task_source = task_source.clone();
+        let drawn = Arc::clone(&drawn);
+        let chaos_slow_releases = Arc::clone(&chaos_slow_releases);
+        let chaos_heartbeat_delays = Arc::clone(&chaos_heartbeat_delays);
+        let work_ms_per_zone = work_ms_per_zone.clone();
         let queue = Arc::clone(&queue);
         let zones = Arc::clone(&zones);
-        let zone_wait_us = Arc::clone(&zone_wait_us);
+        let zone_wait_ns = Arc::clone(&zone_wait_ns);
         let monitor = Arc::clone(&monitor);
         let zone_metrics = Arc::clone(&zone_metrics);
         let duplicate_tasks = Arc::clone(&duplicate_tasks);
+        let series = Arc::clone(&series);
+        let cancel = Arc::clone(&cancel);
+        let recovered = Arc::clone(&recovered);
+        let requeued = Arc::clone(&requeued);
         let seen_tasks = seen_tasks.as_ref().map(Arc::clone);
-        handles.push(thread::spawn(move || {
+        let trace_spans = trace_spans.as_ref().map(Arc::clone);
+        let events = events.as_ref().map(Arc::clone);
+        let outcomes = outcomes.clone();
+        let rng_log = rng_log.as_ref().map(Arc::clone);
+        let robot = robot_id as RobotId;
+        move || {
+            let start = gate.arrive();
+            let since_start = move || start.elapsed().as_micros() as u64;
             let stop_after = if simulate_offline && robots > 1 && robot_id == 0 {
                 tasks_per_robot / 2
             } else {
                 usize::MAX
             };
+            let resume_after = offline_recover_after
+                .map(|extra| stop_after.saturating_add(extra))
+                .unwrap_or(usize::MAX);
             let mut completed = 0usize;
-            while completed < tasks_per_robot {
-                let task = queue.pop_blocking_or_closed().expect("task queue closed");
+            let mut consecutive_requeues = 0usize;
+            let mut limiter = max_rate.map(RateLimiter::new);
+            let mut checksum = 0u64;
+            let mut first_task = None;
+            let mut waits = Vec::new();
+            let mut last_zone: Option<ZoneId> = None;
+            let home_zone = (robot_id as u64 % zones_total) + 1;
+            // Per-robot streams keep each robot's faults independent of scheduling.
+            let mut chaos_rng = chaos.map(|seed| {
+                SplitMix64::new(seed ^ robot)
+                    .logged(rng_log.as_ref(), format!("chaos:robot={robot}"))
+            });
+            let mut fail_rng = SplitMix64::new(seed ^ FAIL_RATE_SALT ^ robot)
+                .logged(rng_log.as_ref(), format!("fail:robot={robot}"));
+            let mut failed = 0usize;
+            while completed < quota {
+                if cancel.load(Ordering::SeqCst) {
+                    break;
+                }
+                if let Some(limiter) = limiter.as_mut() {
+                    limiter.take();
+                }
+                let next = match task_source.as_ref() {
+                    // Requeued tasks go first so a busy zone's task is retried.
+                    // Streamed tasks cannot be checked up front, so one aimed
+                    // at a missing zone is skipped as it is drawn.
+                    Some(source) => queue.try_pop().or_else(|| {
+                        loop {
+                            let task = source.next()?;
+                            if let Some(err) = zone_range_error(&task, zones_total) {
+                                log_warn!("skipping streamed {err}");
+                                continue;
+                            }
+                            drawn.fetch_add(1, Ordering::SeqCst);
+                            break Some(task);
+                        }
+                    }),
+                    None if affinity => queue
+                        .pop_for_zone(home_zone, zones_total)
+                        .or_else(|| queue.pop_blocking_or_closed()),
+                    None => queue.pop_blocking_or_closed(),
+                };
+                let Some(task) = next else {
+                    break;
+                };
+                first_task.get_or_insert(task.id);
+                let popped_at = Instant::now();
+                if trace {
+                    trace_event(start, robot_id, task.id, "popped", "");
+                }
+                if let Some(events) = events.as_ref() {
+                    events.emit(&BenchEvent::Popped {
+                        t_us: since_start(),
+                        robot,
+                        task: task.id,
+                    });
+                }
+                let zone = task.target_zone(zones_total);
+                // One trip per task: tasks with an explicit zone pay `travel_ms`
+                // per zone of distance, the rest a flat `travel_ms`.
+                if travel_ms > 0
+                    && let Some(from) = last_zone
+                {
+                    let legs = if task.zone.is_some() {
+                        from.abs_diff(zone)
+                    } else {
+                        1
+                    };
+                    thread::sleep(Duration::from_millis(travel_ms * legs));
+                }
+                let wait_start = Instant::now();
+                let wait_began_us = since_start();
+                let acquired = match zone_acquire_timeout_ms {
+                    // Bounded requeues guarantee progress on a permanently busy zone.
+                    Some(ms) if consecutive_requeues < ZONE_REQUEUE_LIMIT => {
+                        let timeout = Duration::from_millis(ms);
+                        zones.acquire_timeout(zone, robot_id as u64, timeout)
+                    }
+                    _ => {
+                        zones.acquire(zone, robot_id as u64);
+                        true
+                    }
+                };
+                let waited_for = wait_start.elapsed();
+                zone_wait_ns.fetch_add(waited_for.as_nanos() as u64, Ordering::SeqCst);
+                let waited = waited_for.as_micros() as u64;
+                zone_metrics.record_wait(zone, robot, waited);
+                waits.push((wait_began_us, waited));
+                if !acquired {
+                    // Busy aisle: put the task back at the tail and try another.
+                    consecutive_requeues += 1;
+                    requeued.fetch_add(1, Ordering::SeqCst);
+                    if trace {
+                        let detail = format!(",zone={zone}");
+                        trace_event(start, robot_id, task.id, "requeued", &detail);
+                    }
+                    if let Some(events) = events.as_ref() {
+                        events.emit(&BenchEvent::Requeued {
+                            t_us: since_start(),
+                            robot,
+                            task: task.id,
+                            zone,
+                        });
+                    }
+                    queue.push(task).expect("task queue closed");
+                    continue;
+                }
+                consecutive_requeues = 0;
+                // A task counts as consumed once its zone is held, so requeues
+                // are not mistaken for duplicate deliveries.
                 if let Some(seen) = seen_tasks.as_ref() {
                     let mut guard = seen.lock().expect("seen mutex poisoned");
                     if !guard.insert(task.id) {
                         duplicate_tasks.store(true, Ordering::SeqCst);
                     }
                 }
-                let zone = (task.id % zones_total) + 1;
-                let wait_start = Instant::now();
-                zones.acquire(zone, robot_id as u64);
-                let waited = wait_start.elapsed().as_micros() as u64;
-                zone_wait_us.fetch_add(waited, Ordering::SeqCst);
-                zone_metrics.enter(zone, zones_len);
+                zone_metrics.enter(zone, robot, zones_len);
+                let held_from = Instant::now();
+                let held_from_us = since_start();
+                if trace {
+                    let detail = format!(",zone={zone},wait_us={waited}");
+                    trace_event(start, robot_id, task.id, "acquired", &detail);
+                }
+                if let Some(events) = events.as_ref() {
+                    events.emit(&BenchEvent::Acquired {
+                        t_us: since_start(),
+                        robot,
+                        task: task.id,
+                        zone,
+                        wait_us: waited,
+                    });
+                }
+                let work_ms = work_ms_per_zone.as_ref().map_or(work_ms, |per_zone| {
+                    per_zone[(zone as usize - 1) % per_zone.len()]
+                });
                 if work_ms > 0 {
                     thread::sleep(Duration::from_millis(work_ms));
                 }
+                if let Some(rng) = chaos_rng.as_mut()
+                    && rng.below(CHAOS_SLOW_RELEASE_ODDS) == 0
+                {
+                    chaos_slow_releases.fetch_add(1, Ordering::SeqCst);
+                    thread::sleep(Duration::from_millis(CHAOS_SLOW_RELEASE_MS));
+                }
                 zone_metrics.pre_release(zone, zones_len);
-                let released = zones.release(zone, robot_id as u64);
+                let released = release_with_retry(&zones, zone, robot_id as u64, || {
+                    zones.release(zone, robot_id as u64)
+                });
                 if !released {
                     log_dev!("[ZONE] bench release failed zone={zone} robot={robot_id}");
                     zone_metrics.revert_pre_release(zone, zones_len);
                 }
+                let held_us = held_from.elapsed().as_micros() as u64;
+                zone_metrics.record_hold(zone, held_us);
+                if let Some(spans) = trace_spans.as_ref() {
+                    spans
+                        .lock()
+                        .expect("trace span mutex poisoned")
+                        .push(ZoneSpan {
+                            robot,
+                            zone,
+                            task: task.id,
+                            begin_us: held_from_us,
+                            end_us: held_from_us + held_us,
+                        });
+                }
+                if trace {
+                    let detail = format!(",zone={zone},held_us={held_us}");
+                    trace_event(start, robot_id, task.id, "released", &detail);
+                }
+                if let Some(events) = events.as_ref() {
+                    events.emit(&BenchEvent::Released {
+                        t_us: since_start(),
+                        robot,
+                        task: task.id,
+                        zone,
+                        held_us,
+                    });
+                }
+                let success = fail_rate.is_none_or(|rate| !fail_rng.chance(rate));
+                failed += usize::from(!success);
+                if let Some(outcomes) = outcomes.as_ref() {
+                    // A dropped receiver only means nobody is listening.
+                    let _ = outcomes.send(TaskOutcome {
+                        task_id: task.id,
+                        robot,
+                        success,
+                        duration: popped_at.elapsed(),
+                    });
+                }
+                // Unblocks queued tasks that depend on this one; a lock-free
+                // no-op when the run has no dependencies.
+                queue.complete(task.id);
                 completed += 1;
-                // Optionally stop heartbeats early to simulate offline detection.
-                if completed <= stop_after {
-                    monitor.heartbeat(robot_id as u64);
+                last_zone = Some(zone);
+                checksum = checksum.wrapping_add(task_id_hash(task.id));
+                series.record(start.elapsed());
+                if task_source.is_none() && outstanding.fetch_sub(1, Ordering::SeqCst) == 1 {
+                    queue.close();
+                }
+                if let Some(rng) = chaos_rng.as_mut()
+                    && rng.below(CHAOS_HEARTBEAT_DELAY_ODDS) == 0
+                {
+                    // Go quiet long enough to be marked offline; the heartbeat
+                    // below then exercises recovery.
+                    chaos_heartbeat_delays.fetch_add(1, Ordering::SeqCst);
+                    thread::sleep(Duration::from_millis(
+                        BENCH_OFFLINE_TIMEOUT_MS + CHAOS_HEARTBEAT_OVERSHOOT_MS,
+                    ));
+                }
+                // Optionally stop heartbeats early to simulate offline detection,
+                // then resume them to exercise recovery.
+                if (completed <= stop_after || completed > resume_after)
+                    && monitor.heartbeat(robot_id as u64)
+                {
+                    recovered.fetch_add(1, Ordering::SeqCst);
                 }
             }
-        }));
-    }
+            RobotOutcome {
+                finished: Instant::now(),
+                checksum,
+                completed,
+                first_task,
+                waits,
+                failed,
+            }
+        }
+    };
+    let jobs: Vec<_> = (0..robots).map(make_robot).collect();
+    let threads = RobotThreads::spawn_limited(jobs, thread_limit);
 
-    for handle in handles {
-        handle.join().expect("benchmark thread panicked");
-    }
-    if simulate_offline {
+    // Every robot thread is parked at the gate; the measured window starts now.
+    gate.wait_for(threads.thread_count());
+    let cpu_start = cpu_times_seconds();
+    let start = Instant::now();
+    gate.open(start);
+
+    // A robot coming online mid-run: tracked from the moment it joins, then
+    // it takes tasks from the shared queue like everyone else.
+    let late_robot = add_robot_after_ms.map(|ms| {
+        let job = make_robot(robots);
+        let monitor = Arc::clone(&monitor);
+        thread::Builder::new()
+            .name(format!("robot-{robots}"))
+            .spawn(move || {
+                thread::sleep(Duration::from_millis(ms));
+                monitor.register_robot(robots as RobotId);
+                job()
+            })
+            .expect("failed to spawn late robot thread")
+    });
+
+    let mut task_checksum = 0u64;
+    let mut robot_results = threads.join();
+    let late_robot_tasks = late_robot.map(|handle| {
+        let outcome = handle.join().expect("late robot thread panicked");
+        let completed = outcome.completed;
+        robot_results.push(outcome);
+        completed
+    });
+    let first_task_per_robot: Vec<Option<TaskId>> = robot_results
+        .iter()
+        .map(|outcome| outcome.first_task)
+        .collect();
+    let per_robot_completed: Vec<usize> = robot_results
+        .iter()
+        .map(|outcome| outcome.completed)
+        .collect();
+    let failed_tasks: usize = robot_results.iter().map(|outcome| outcome.failed).sum();
+    let waits: Vec<(u64, u64)> = robot_results
+        .iter()
+        .flat_map(|outcome| outcome.waits.iter().copied())
+        .collect();
+    let finish_ms: Vec<f64> = robot_results
+        .into_iter()
+        .map(|outcome| {
+            task_checksum = task_checksum.wrapping_add(outcome.checksum);
+            outcome
+                .finished
+                .saturating_duration_since(start)
+                .as_secs_f64()
+                * 1000.0
+        })
+        .collect();
+    // Utilization is measured against the robots' own window; the overall
+    // elapsed time also covers monitor shutdown.
+    let last_finish_ms = finish_ms.iter().copied().fold(0.0, f64::max);
+    // The run's length is only known now, so waits are bucketed afterwards.
+    let (startup_avg_wait_us, steady_avg_wait_us) =
+        phase_avg_wait_us(&waits, (last_finish_ms * 100.0) as u64);
+    let zone_utilization_pct =
+        zone_metrics.utilization_pct(Duration::from_secs_f64(last_finish_ms / 1000.0));
+    let (straggler_robot, straggler_lag_ms) = match straggler(&finish_ms) {
+        Some((robot, lag_ms)) => (Some(robot as RobotId), lag_ms),
+        None => (None, 0.0),
+    };
+    let completion_time_stddev_ms = stddev_ms(&finish_ms);
+    let canceled = cancel.load(Ordering::SeqCst);
+    // A recovering robot is heartbeating again, so there is nothing to wait for.
+    if simulate_offline && offline_recover_after.is_none() && !canceled {
         wait_for_offline(
             &monitor,
             BENCH_OFFLINE_TIMEOUT_MS,
@@ -301,23 +1455,43 @@ fn benchmark_once(
         .join()
         .expect("health monitor thread panicked");
 
-    // Drain any unexpected leftover tasks for validation reporting.
-    let mut leftover = 0usize;
-    while queue.try_pop().is_some() {
-        leftover += 1;
+    if let (Some(path), Some(spans)) = (trace_out.as_ref(), trace_spans.as_ref()) {
+        let spans = spans.lock().expect("trace span mutex poisoned");
+        if let Err(err) = chrome_trace::write(path, &spans) {
+            eprintln!("# warning,trace_out,{}: {err}", path.display());
+        }
     }
 
-    let elapsed_ms = start.elapsed().as_millis() as f64;
-    let throughput = if elapsed_ms > 0.0 {
-        (total_tasks as f64) / (elapsed_ms / 1000.0)
+    // A streamed run only counts what it drew; undrawn tasks stay with the
+    // source and are not leftovers.
+    let total_tasks = if task_source.is_some() {
+        drawn.load(Ordering::SeqCst)
     } else {
-        0.0
+        total_tasks
     };
-    let avg_zone_wait = if total_tasks > 0 {
-        zone_wait_us.load(Ordering::SeqCst) as f64 / total_tasks as f64
-    } else {
+    // Counting is O(1); the queue is dropped with the run, so only validate
+    // mode pays to drain it and list the stranded ids.
+    let leftover = queue.len();
+    let mut leftover_ids = Vec::new();
+    if validate {
+        while let Some(task) = queue.try_pop() {
+            leftover_ids.push(task.id);
+        }
+    }
+    // Robot 0 is the one that goes silent in offline mode.
+    let offline_unfinished = (simulate_offline && robots > 1)
+        .then(|| tasks_per_robot.saturating_sub(per_robot_completed[0]));
+    let leftover_expected = leftover_is_expected(leftover, offline_unfinished);
+
+    let elapsed_ms = start.elapsed().as_millis() as f64;
+    // Canceled runs leave tasks behind, so rate only the work actually done.
+    let completed_tasks = total_tasks - leftover;
+    let throughput = if elapsed_ms > 0.0 {
+        (completed_tasks as f64) / (elapsed_ms / 1000.0)
+    } else {
         0.0
     };
+    let avg_zone_wait = avg_wait_us(zone_wait_ns.load(Ordering::SeqCst), completed_tasks);
 
     let (cpu_user_s, cpu_sys_s) = match (cpu_start, cpu_times_seconds()) {
         (Some((user_start, sys_start)), Some((user_end, sys_end))) => {
@@ -326,7 +1500,7 @@ fn benchmark_once(
         _ => (None, None),
     };
 
-    BenchResult {
+    Ok(BenchResult {
         robots,
         tasks_per_robot,
         zones_total,
@@ -334,18 +1508,325 @@ fn benchmark_once(
         elapsed_ms,
         throughput,
         avg_zone_wait_us: avg_zone_wait,
+        startup_avg_wait_us,
+        steady_avg_wait_us,
+        max_single_zone_wait_us: zone_metrics.max_single_wait_us(),
+        zone_utilization_pct,
+        zone_avg_hold_ms: zone_metrics.avg_hold_ms(),
+        zone_rate: zone_metrics.serve_rate(elapsed_ms),
+        task_checksum,
+        suggested_timeout_ms: monitor
+            .suggested_timeout()
+            .map(|timeout| timeout.as_secs_f64() * 1000.0),
+        queue_spurious_wakeups: queue.spurious_wakeups(),
+        zone_spurious_wakeups: zones.spurious_wakeups(),
         cpu_user_s,
         cpu_sys_s,
         leftover,
+        leftover_ids,
+        leftover_expected,
+        straggler_robot,
+        straggler_lag_ms,
+        completion_time_stddev_ms,
+        late_robot_tasks,
+        tracked_robots: monitor.last_seen_ms_ago().len(),
+        first_task_per_robot,
+        per_robot_completed,
+        failed_tasks,
+        chaos_slow_releases: chaos_slow_releases.load(Ordering::SeqCst),
+        chaos_heartbeat_delays: chaos_heartbeat_delays.load(Ordering::SeqCst),
         max_occupancy: zone_metrics.max_occupancy(),
         zone_violation: zone_metrics.has_violation(),
+        first_violation: zone_metrics.first_violation(start),
         duplicate_tasks: duplicate_tasks.load(Ordering::SeqCst),
         offline_count: monitor.offline_robots().len(),
+        recovered_count: recovered.load(Ordering::SeqCst),
+        requeued_tasks: requeued.load(Ordering::SeqCst),
+        throughput_series: series.snapshot(),
+        throughput_series_overflow: series.overflow(),
+        canceled,
+    })
+}
+
+/// `--deterministic`: robots take turns on the calling thread, one task per
+/// turn in robot order, so task assignment is identical on every run.
+///
+/// Scheduling knobs with nothing to schedule (offline simulation, chaos,
+/// travel, rate caps, late robots, zone timeouts, tracing, affinity) are
+/// ignored.
+fn benchmark_deterministic(config: &BenchConfig, cancel: &AtomicBool) -> io::Result<BenchResult> {
+    let robots = config.robots;
+    let zones_total = config.zones_total;
+    let zones_len = zones_total as usize;
+    let total_tasks = config.task_count();
+    let rounds = total_tasks.div_ceil(robots);
+    let queue = TaskQueue::new_with_discipline(config.queue_discipline);
+    let tasks = match (config.task_source.as_ref(), config.fixed_tasks.as_ref()) {
+        (Some(source), _) => std::iter::from_fn(|| source.next())
+            .take(total_tasks)
+            .collect(),
+        (None, Some(canonical)) => canonical.iter().take(total_tasks).cloned().collect(),
+        (None, None) => bench_tasks(
+            total_tasks,
+            config.zone_weights.as_deref(),
+            config.seed,
+            config.rng_log.as_ref(),
+        ),
+    };
+    check_bench_tasks(&queue, &tasks, zones_total)?;
+    queue.push_many(tasks).expect("task queue closed");
+    let total_tasks = queue.len();
+    let zones = ZoneAccess::new();
+    let monitor = HealthMonitor::new();
+    monitor.register_robots(0..robots as RobotId);
+    let zone_metrics = ZoneMetrics::new(zones_len, total_tasks);
+    let series = ThroughputSeries::new();
+    let mut seen = HashSet::new();
+    let mut duplicate_tasks = false;
+    let mut per_robot_completed = vec![0usize; robots];
+    let mut first_task_per_robot = vec![None; robots];
+    let mut finish_ms = vec![0.0; robots];
+    let mut task_checksum = 0u64;
+    // Same per-robot streams as the threaded run.
+    let mut fail_rngs: Vec<SplitMix64> = (0..robots as RobotId)
+        .map(|robot| {
+            SplitMix64::new(config.seed ^ FAIL_RATE_SALT ^ robot)
+                .logged(config.rng_log.as_ref(), format!("fail:robot={robot}"))
+        })
+        .collect();
+    let mut failed_tasks = 0usize;
+
+    let cpu_start = cpu_times_seconds();
+    let start = Instant::now();
+    'rounds: for _ in 0..rounds {
+        for robot_id in 0..robots {
+            if cancel.load(Ordering::SeqCst) {
+                break 'rounds;
+            }
+            let robot = robot_id as RobotId;
+            let Some(task) = queue.try_pop() else {
+                break 'rounds;
+            };
+            let popped_at = Instant::now();
+            first_task_per_robot[robot_id].get_or_insert(task.id);
+            let zone = task.target_zone(zones_total);
+            // Turns never overlap, so the zone is always free.
+            let acquired = zones.try_acquire(zone, robot);
+            debug_assert!(acquired, "zone {zone} busy in a deterministic run");
+            zone_metrics.record_wait(zone, robot, 0);
+            duplicate_tasks |= !seen.insert(task.id);
+            zone_metrics.enter(zone, robot, zones_len);
+            let held_from = Instant::now();
+            let work_ms = config
+                .work_ms_per_zone
+                .as_ref()
+                .map_or(config.work_ms, |per_zone| {
+                    per_zone[(zone as usize - 1) % per_zone.len()]
+                });
+            if work_ms > 0 {
+                thread::sleep(Duration::from_millis(work_ms));
+            }
+            zone_metrics.pre_release(zone, zones_len);
+            zones.release(zone, robot);
+            zone_metrics.record_hold(zone, held_from.elapsed().as_micros() as u64);
+            monitor.heartbeat(robot);
+            let success = config
+                .fail_rate
+                .is_none_or(|rate| !fail_rngs[robot_id].chance(rate));
+            failed_tasks += usize::from(!success);
+            if let Some(outcomes) = config.outcomes.as_ref() {
+                let _ = outcomes.send(TaskOutcome {
+                    task_id: task.id,
+                    robot,
+                    success,
+                    duration: popped_at.elapsed(),
+                });
+            }
+            queue.complete(task.id);
+            per_robot_completed[robot_id] += 1;
+            task_checksum = task_checksum.wrapping_add(task_id_hash(task.id));
+            series.record(start.elapsed());
+            finish_ms[robot_id] = start.elapsed().as_secs_f64() * 1000.0;
+        }
+    }
+    let elapsed_ms = start.elapsed().as_millis() as f64;
+    let (cpu_user_s, cpu_sys_s) = match (cpu_start, cpu_times_seconds()) {
+        (Some((user_start, sys_start)), Some((user_end, sys_end))) => {
+            (Some(user_end - user_start), Some(sys_end - sys_start))
+        }
+        _ => (None, None),
+    };
+
+    let leftover = queue.len();
+    let mut leftover_ids = Vec::new();
+    if config.validate {
+        while let Some(task) = queue.try_pop() {
+            leftover_ids.push(task.id);
+        }
+    }
+    let completed_tasks = total_tasks - leftover;
+    let throughput = if elapsed_ms > 0.0 {
+        completed_tasks as f64 / (elapsed_ms / 1000.0)
+    } else {
+        0.0
+    };
+    let last_finish_ms = finish_ms.iter().copied().fold(0.0, f64::max);
+    let (straggler_robot, straggler_lag_ms) = match straggler(&finish_ms) {
+        Some((robot, lag_ms)) => (Some(robot as RobotId), lag_ms),
+        None => (None, 0.0),
+    };
+    Ok(BenchResult {
+        robots,
+        tasks_per_robot: config.tasks_per_robot,
+        zones_total,
+        total_tasks,
+        elapsed_ms,
+        throughput,
+        avg_zone_wait_us: 0.0,
+        startup_avg_wait_us: 0.0,
+        steady_avg_wait_us: 0.0,
+        max_single_zone_wait_us: zone_metrics.max_single_wait_us(),
+        zone_utilization_pct: zone_metrics
+            .utilization_pct(Duration::from_secs_f64(last_finish_ms / 1000.0)),
+        zone_avg_hold_ms: zone_metrics.avg_hold_ms(),
+        zone_rate: zone_metrics.serve_rate(elapsed_ms),
+        task_checksum,
+        suggested_timeout_ms: monitor
+            .suggested_timeout()
+            .map(|timeout| timeout.as_secs_f64() * 1000.0),
+        queue_spurious_wakeups: queue.spurious_wakeups(),
+        zone_spurious_wakeups: zones.spurious_wakeups(),
+        cpu_user_s,
+        cpu_sys_s,
+        leftover,
+        leftover_ids,
+        // Deterministic runs never take a robot offline.
+        leftover_expected: leftover_is_expected(leftover, None),
+        straggler_robot,
+        straggler_lag_ms,
+        completion_time_stddev_ms: stddev_ms(&finish_ms),
+        late_robot_tasks: None,
+        tracked_robots: monitor.last_seen_ms_ago().len(),
+        first_task_per_robot,
+        per_robot_completed,
+        failed_tasks,
+        chaos_slow_releases: 0,
+        chaos_heartbeat_delays: 0,
+        max_occupancy: zone_metrics.max_occupancy(),
+        zone_violation: zone_metrics.has_violation(),
+        first_violation: zone_metrics.first_violation(start),
+        duplicate_tasks,
+        offline_count: 0,
+        recovered_count: 0,
+        requeued_tasks: 0,
+        throughput_series: series.snapshot(),
+        throughput_series_overflow: series.overflow(),
+        canceled: cancel.load(Ordering::SeqCst),
+    })
+}
+
+/// One robot's stay in a zone, in milliseconds from the start of the demo.
+struct TimelineSpan {
+    robot: RobotId,
+    zone: ZoneId,
+    task: TaskId,
+    enter_ms: u128,
+    leave_ms: u128,
+}
+
+/// Render zone occupancy spans as a Graphviz digraph: robots and zones are
+/// nodes, and each stay is a `robot -> zone` edge labeled with its task and
+/// time window, listed in start order.
+fn timeline_dot(spans: &[TimelineSpan]) -> String {
+    let mut spans: Vec<&TimelineSpan> = spans.iter().collect();
+    spans.sort_by_key(|span| (span.enter_ms, span.robot));
+    let robots: BTreeSet<RobotId> = spans.iter().map(|s| s.robot).collect();
+    let zones: BTreeSet<ZoneId> = spans.iter().map(|s| s.zone).collect();
+
+    let mut dot = String::from("digraph timeline {\n    rankdir=LR;\n");
+    for robot in &robots {
+        dot.push_str(&format!("    \"robot-{robot}\" [shape=ellipse];\n"));
+    }
+    for zone in &zones {
+        dot.push_str(&format!("    \"zone-{zone}\" [shape=box];\n"));
+    }
+    for span in spans {
+        dot.push_str(&format!(
+            "    \"robot-{}\" -> \"zone-{}\" [label=\"task {} @ {}-{}ms\"];\n",
+            span.robot, span.zone, span.task, span.enter_ms, span.leave_ms
+        ));
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+/// Outcome of the narrated demo, as printed under `DEMO SUMMARY`.
+#[derive(Debug)]
+pub struct DemoSummary {
+    pub robots: usize,
+    pub tasks_total: usize,
+    pub per_robot_done: Vec<usize>,
+    pub max_zone_occupancy: usize,
+    pub zone_violation: bool,
+    pub offline_robots: HashSet<RobotId>,
+    /// Robots were still running at the deadline and were told to stop.
+    pub aborted: bool,
+}
+
+/// Knobs for the narrated demo.
+#[derive(Clone, Debug)]
+pub struct DemoOptions {
+    /// Stop robots still running after this long; the summary reports `aborted=true`.
+    pub timeout: Option<Duration>,
+    /// Have robot 1 stop heartbeating partway through to show offline detection.
+    pub simulate_offline: bool,
+}
+
+impl Default for DemoOptions {
+    fn default() -> Self {
+        Self {
+            timeout: None,
+            simulate_offline: true,
+        }
+    }
+}
+
+/// Run the narrated demo; with `timeline`, also write a DOT schedule there.
+pub fn run_demo(timeline: Option<&Path>, options: &DemoOptions) {
+    let (summary, spans) = demo_run(options, 0);
+    println!("DEMO SUMMARY");
+    println!(
+        "robots={} tasks_total={}",
+        summary.robots, summary.tasks_total
+    );
+    println!("tasks_per_robot_done={:?}", summary.per_robot_done);
+    println!("max_zone_occupancy_observed={}", summary.max_zone_occupancy);
+    println!("zone_violation={}", summary.zone_violation);
+    println!("offline_robots={:?}", summary.offline_robots);
+    println!("aborted={}", summary.aborted);
+
+    if let Some(path) = timeline {
+        match std::fs::write(path, timeline_dot(&spans)) {
+            Ok(()) => println!("timeline={}", path.display()),
+            Err(err) => eprintln!("timeline error: cannot write {}: {err}", path.display()),
+        }
     }
 }
 
-/// Run the default demo showing queueing, zoning, and offline detection.
-pub fn run_demo() {
+/// Run the demo without printing the summary, for callers that inspect it.
+#[allow(dead_code)]
+pub fn run_demo_collect() -> DemoSummary {
+    demo_run(&DemoOptions::default(), 0).0
+}
+
+// Run the demo's robots to completion (or the deadline) and collect the
+// summary and spans. `withheld_tasks` leaves tasks out of the queue so robots
+// waiting for them hang, which lets tests exercise the timeout.
+fn demo_run(options: &DemoOptions, withheld_tasks: usize) -> (DemoSummary, Vec<TimelineSpan>) {
+    let DemoOptions {
+        timeout,
+        simulate_offline,
+    } = *options;
     log_dev!("[DEMO] start");
 
     let queue = Arc::new(TaskQueue::new());
@@ -353,14 +1834,14 @@ pub fn run_demo() {
     let monitor = Arc::new(HealthMonitor::new());
 
     let robots = 3;
-    let tasks_per_robot = 3;
+    let tasks_per_robot: usize = 3;
     let zones_total = 2;
 
     // Track per-robot completions for the final summary.
     let per_robot_tasks = Arc::new((0..robots).map(|_| AtomicUsize::new(0)).collect::<Vec<_>>());
-    let zone_metrics = Arc::new(ZoneMetrics::new(zones_total));
+    let zone_metrics = Arc::new(ZoneMetrics::new(zones_total, robots * tasks_per_robot));
 
-    for id in 0..(robots * tasks_per_robot) {
+    for id in 0..(robots * tasks_per_robot).saturating_sub(withheld_tasks) {
         queue
             .push(Task::new(id as u64, format!("deliver-{id}")))
             .expect("task queue closed");
@@ -372,9 +1853,7 @@ pub fn run_demo() {
     );
 
     let stop_flag = Arc::new(AtomicBool::new(false));
-    for robot_id in 0..robots {
-        monitor.register_robot(robot_id as u64);
-    }
+    monitor.register_robots(0..robots as RobotId);
 
     let monitor_thread = {
         let monitor = Arc::clone(&monitor);
@@ -397,54 +1876,94 @@ pub fn run_demo() {
             .expect("failed to spawn health monitor")
     };
 
-    let mut handles = Vec::new();
+    let spans = Arc::new(Mutex::new(Vec::new()));
+    let (done_tx, done_rx) = mpsc::channel();
+    let start = Instant::now();
+    let mut jobs = Vec::with_capacity(robots);
     for robot_id in 0..robots {
         let queue = Arc::clone(&queue);
         let zones = Arc::clone(&zones);
         let monitor = Arc::clone(&monitor);
         let per_robot_tasks = Arc::clone(&per_robot_tasks);
         let zone_metrics = Arc::clone(&zone_metrics);
+        let spans = Arc::clone(&spans);
+        let stop_flag = Arc::clone(&stop_flag);
+        let done_tx = done_tx.clone();
         let name = format!("robot-{robot_id}");
-        let handle = thread::Builder::new()
-            .name(name.clone())
-            .spawn(move || {
-                let mut completed = 0;
-                // Robot 1 stops heartbeats mid-demo to trigger offline detection.
-                let stop_heartbeat_after = if robot_id == 1 { 2 } else { usize::MAX };
-                while completed < tasks_per_robot {
-                    let task = queue.pop_blocking_or_closed().expect("task queue closed");
-                    per_robot_tasks[robot_id].fetch_add(1, Ordering::SeqCst);
-                    log_dev!("[QUEUE] {name} fetched task {}", task.id);
-                    let zone = (task.id % zones_total as u64) + 1;
-                    zones.acquire(zone, robot_id as u64);
-                    zone_metrics.enter(zone, zones_total);
-                    log_dev!("[ZONE] {name} entered zone {zone} for task {}", task.id);
-                    thread::sleep(Duration::from_millis(80));
-                    zone_metrics.pre_release(zone, zones_total);
-                    let released = zones.release(zone, robot_id as u64);
-                    if !released {
-                        log_dev!("[ZONE] {name} failed to release zone {zone}");
-                        zone_metrics.revert_pre_release(zone, zones_total);
-                    }
-                    log_dev!("[ZONE] {name} left zone {zone} for task {}", task.id);
-                    completed += 1;
-                    if completed <= stop_heartbeat_after {
-                        monitor.heartbeat(robot_id as u64);
-                        log_dev!("[HEALTH] {name} heartbeat");
-                    } else {
-                        log_dev!("[HEALTH] {name} stops heartbeats");
-                    }
+        jobs.push(move || {
+            let mut completed = 0;
+            // Robot 1 stops heartbeats mid-demo to trigger offline detection.
+            let stop_heartbeat_after = if simulate_offline && robot_id == 1 {
+                2
+            } else {
+                usize::MAX
+            };
+            while completed < tasks_per_robot && !stop_flag.load(Ordering::SeqCst) {
+                // A closed queue means the demo was aborted.
+                let Some(task) = queue.pop_blocking_or_closed() else {
+                    log_dev!("[QUEUE] {name} stopping: queue closed");
+                    break;
+                };
+                per_robot_tasks[robot_id].fetch_add(1, Ordering::SeqCst);
+                log_dev!("[QUEUE] {name} fetched task {}", task.id);
+                let zone = task.target_zone(zones_total as u64);
+                zones.acquire(zone, robot_id as u64);
+                zone_metrics.enter(zone, robot_id as u64, zones_total);
+                let enter_ms = start.elapsed().as_millis();
+                log_dev!("[ZONE] {name} entered zone {zone} for task {}", task.id);
+                thread::sleep(Duration::from_millis(80));
+                zone_metrics.pre_release(zone, zones_total);
+                let released = zones.release(zone, robot_id as u64);
+                if !released {
+                    log_dev!("[ZONE] {name} failed to release zone {zone}");
+                    zone_metrics.revert_pre_release(zone, zones_total);
                 }
-            })
-            .expect("failed to spawn robot thread");
-        handles.push(handle);
+                spans
+                    .lock()
+                    .expect("timeline mutex poisoned")
+                    .push(TimelineSpan {
+                        robot: robot_id as RobotId,
+                        zone,
+                        task: task.id,
+                        enter_ms,
+                        leave_ms: start.elapsed().as_millis(),
+                    });
+                log_dev!("[ZONE] {name} left zone {zone} for task {}", task.id);
+                completed += 1;
+                if completed <= stop_heartbeat_after {
+                    monitor.heartbeat(robot_id as u64);
+                    log_dev!("[HEALTH] {name} heartbeat");
+                } else {
+                    log_dev!("[HEALTH] {name} stops heartbeats");
+                }
+            }
+            // A robot still heartbeating is done, not offline, while it
+            // waits for the others to finish.
+            if completed <= stop_heartbeat_after {
+                monitor.sign_off(robot_id as u64);
+            }
+            let _ = done_tx.send(robot_id);
+        });
     }
-
-    let start = Instant::now();
-    for handle in handles {
-        handle.join().expect("robot thread panicked");
+    drop(done_tx);
+    let threads = RobotThreads::spawn(jobs);
+    let aborted = timeout.is_some_and(|timeout| {
+        let deadline = start + timeout;
+        let finished = (0..robots).all(|_| {
+            let left = deadline.saturating_duration_since(Instant::now());
+            done_rx.recv_timeout(left).is_ok()
+        });
+        !finished
+    });
+    if aborted {
+        log_warn!("demo deadline passed; closing the queue to stop robots");
+        stop_flag.store(true, Ordering::SeqCst);
+        queue.close();
+    }
+    threads.join();
+    if simulate_offline && !aborted {
+        wait_for_offline(&monitor, DEMO_OFFLINE_TIMEOUT_MS, DEMO_OFFLINE_MAX_WAIT_MS);
     }
-    wait_for_offline(&monitor, DEMO_OFFLINE_TIMEOUT_MS, DEMO_OFFLINE_MAX_WAIT_MS);
     stop_flag.store(true, Ordering::SeqCst);
     monitor_thread
         .join()
@@ -462,58 +1981,46 @@ pub fn run_demo() {
         start.elapsed().as_millis()
     );
 
-    let tasks_done: Vec<usize> = per_robot_tasks
-        .iter()
-        .map(|count| count.load(Ordering::SeqCst))
-        .collect();
-    println!("DEMO SUMMARY");
-    println!("robots={robots} tasks_total={}", robots * tasks_per_robot);
-    println!("tasks_per_robot_done={:?}", tasks_done);
-    println!(
-        "max_zone_occupancy_observed={}",
-        zone_metrics.max_occupancy()
-    );
-    println!("zone_violation={}", zone_metrics.has_violation());
-    println!("offline_robots={:?}", offline);
-}
-
-/// Run a single benchmark with optional parameter overrides.
-pub fn run_benchmark(
-    robots: Option<usize>,
-    tasks_per_robot: Option<usize>,
-    zones_total: Option<u64>,
-    work_ms: Option<u64>,
-    validate: bool,
-    simulate_offline: bool,
-) {
-    let robots = robots.unwrap_or(4);
-    let tasks_per_robot = tasks_per_robot.unwrap_or(25);
-    let zones_total = zones_total.unwrap_or(2);
-    let work_ms = work_ms.unwrap_or(5);
-    if robots == 0 {
-        eprintln!("benchmark error: robots must be > 0");
-        return;
-    }
-    if tasks_per_robot == 0 {
-        eprintln!("benchmark error: tasks_per_robot must be > 0");
-        return;
-    }
-    if zones_total == 0 {
-        eprintln!("benchmark error: zones must be > 0");
-        return;
-    }
-    let result = benchmark_once(
+    let summary = DemoSummary {
         robots,
-        tasks_per_robot,
-        zones_total,
-        work_ms,
-        validate,
-        simulate_offline,
-    );
+        tasks_total: robots * tasks_per_robot,
+        per_robot_done: per_robot_tasks
+            .iter()
+            .map(|count| count.load(Ordering::SeqCst))
+            .collect(),
+        max_zone_occupancy: zone_metrics.max_occupancy(),
+        zone_violation: zone_metrics.has_violation(),
+        offline_robots: offline,
+        aborted,
+    };
+    let spans = std::mem::take(&mut *spans.lock().expect("timeline mutex poisoned"));
+    (summary, spans)
+}
 
-    println!(
-        "robots,tasks_per_robot,zones,total_tasks,elapsed_ms,throughput_tasks_per_s,avg_zone_wait_us,cpu_user_s,cpu_sys_s,max_occupancy,zone_violation,duplicate_tasks,offline_robots"
-    );
+/// Print the CSV header shared by every benchmark-style command.
+fn print_csv_header(columns: &[&str]) {
+    println!("{}", columns.join(","));
+}
+
+/// Column names shared by the CSV and table formats.
+const RESULT_COLUMNS: [&str; 13] = [
+    "robots",
+    "tasks_per_robot",
+    "zones",
+    "total_tasks",
+    "elapsed_ms",
+    "throughput_tasks_per_s",
+    "avg_zone_wait_us",
+    "cpu_user_s",
+    "cpu_sys_s",
+    "max_occupancy",
+    "zone_violation",
+    "duplicate_tasks",
+    "offline_robots",
+];
+
+/// Format one result's cells in `RESULT_COLUMNS` order.
+fn result_cells(result: &BenchResult) -> Vec<String> {
     let cpu_user = result
         .cpu_user_s
         .map(|v| format!("{v:.4}"))
@@ -522,123 +2029,2770 @@ pub fn run_benchmark(
         .cpu_sys_s
         .map(|v| format!("{v:.4}"))
         .unwrap_or_else(|| "NA".to_string());
-    println!(
-        "{},{},{},{},{:.2},{:.2},{:.2},{},{},{},{},{},{}",
-        result.robots,
-        result.tasks_per_robot,
-        result.zones_total,
-        result.total_tasks,
-        result.elapsed_ms,
-        result.throughput,
-        result.avg_zone_wait_us,
+    vec![
+        result.robots.to_string(),
+        result.tasks_per_robot.to_string(),
+        result.zones_total.to_string(),
+        result.total_tasks.to_string(),
+        format!("{:.2}", result.elapsed_ms),
+        format!("{:.2}", result.throughput),
+        format!("{:.2}", result.avg_zone_wait_us),
         cpu_user,
         cpu_sys,
-        result.max_occupancy,
-        result.zone_violation,
-        result.duplicate_tasks,
-        result.offline_count
-    );
+        result.max_occupancy.to_string(),
+        result.zone_violation.to_string(),
+        result.duplicate_tasks.to_string(),
+        result.offline_count.to_string(),
+    ]
+}
+
+/// The binary-format record for a result; mirrors `result_cells`.
+fn result_record(result: &BenchResult) -> ResultRecord {
+    ResultRecord {
+        robots: result.robots as u64,
+        tasks_per_robot: result.tasks_per_robot as u64,
+        zones: result.zones_total,
+        total_tasks: result.total_tasks as u64,
+        elapsed_ms: result.elapsed_ms,
+        throughput: result.throughput,
+        avg_zone_wait_us: result.avg_zone_wait_us,
+        cpu_user_s: result.cpu_user_s,
+        cpu_sys_s: result.cpu_sys_s,
+        max_occupancy: result.max_occupancy as u64,
+        zone_violation: result.zone_violation,
+        duplicate_tasks: result.duplicate_tasks,
+        offline_robots: result.offline_count as u64,
+    }
+}
+
+/// Call `attempt` until it succeeds or `retries` extra tries are spent;
+/// returns the last outcome and how many attempts were made.
+fn with_retries<T>(
+    retries: u32,
+    mut attempt: impl FnMut() -> io::Result<T>,
+) -> (io::Result<T>, u32) {
+    let mut attempts = 1;
+    loop {
+        match attempt() {
+            // A bad task set fails the same way every time.
+            Err(err) if attempts <= retries && err.kind() != io::ErrorKind::InvalidInput => {
+                eprintln!("# warning,transient_failure,attempt={attempts}: {err}");
+                attempts += 1;
+            }
+            outcome => return (outcome, attempts),
+        }
+    }
+}
+
+/// Row order requested with `stress --sort-by <column>[:desc]`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SortKey {
+    pub column: String,
+    pub descending: bool,
+}
+
+/// Parse `<column>` or `<column>:desc` (also `:asc`); the column must be a
+/// result column or `speedup`.
+pub fn parse_sort_key(spec: &str) -> Result<SortKey, String> {
+    let (column, descending) = match spec.split_once(':') {
+        Some((column, "desc")) => (column, true),
+        Some((column, "asc")) => (column, false),
+        Some((_, order)) => return Err(format!("unknown order {order:?} (use asc or desc)")),
+        None => (spec, false),
+    };
+    if !RESULT_COLUMNS.contains(&column) && column != "speedup" {
+        return Err(format!(
+            "unknown column {column:?} (one of {}, speedup)",
+            RESULT_COLUMNS.join(", ")
+        ));
+    }
+    Ok(SortKey {
+        column: column.to_string(),
+        descending,
+    })
+}
+
+// Numeric cells compare as numbers, others as text; `NA` sorts last in
+// either direction.
+fn compare_cells(a: &str, b: &str, descending: bool) -> std::cmp::Ordering {
+    use std::cmp::Ordering;
+    let order = match (a.parse::<f64>(), b.parse::<f64>()) {
+        (Ok(a), Ok(b)) => a.total_cmp(&b),
+        _ if a == "NA" || b == "NA" => {
+            return match (a == "NA", b == "NA") {
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                _ => Ordering::Equal,
+            };
+        }
+        _ => a.cmp(b),
+    };
+    if descending { order.reverse() } else { order }
+}
+
+/// Throughput relative to `baseline`; `None` when the baseline did no work.
+fn speedup_ratio(throughput: f64, baseline: f64) -> Option<f64> {
+    (baseline > 0.0).then(|| throughput / baseline)
+}
+
+/// Render rows as a right-justified table with a dashed rule under the
+/// header. Fixed decimals per column keep decimal points aligned.
+fn render_table(columns: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = columns.iter().map(|name| name.len()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+    let line = |cells: &mut dyn Iterator<Item = &str>| -> String {
+        cells
+            .zip(&widths)
+            .map(|(cell, &width)| format!("{cell:>width$}"))
+            .collect::<Vec<_>>()
+            .join("  ")
+    };
+    let mut out = line(&mut columns.iter().copied());
+    out.push('\n');
+    let rule: Vec<String> = widths.iter().map(|&width| "-".repeat(width)).collect();
+    out.push_str(&rule.join("  "));
+    out.push('\n');
+    for row in rows {
+        out.push_str(&line(&mut row.iter().map(String::as_str)));
+        out.push('\n');
+    }
+    out
+}
+
+/// How result rows are written to stdout.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Streamed comma-separated rows (the default, for scripts).
+    #[default]
+    Csv,
+    /// One aligned table, printed once every row is known.
+    Table,
+    /// Length-prefixed binary records (see `results`), for bulk sweeps.
+    Binary,
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        match raw {
+            "csv" => Ok(Self::Csv),
+            "table" => Ok(Self::Table),
+            "binary" => Ok(Self::Binary),
+            other => Err(format!("unknown format: {other}")),
+        }
+    }
+}
+
+/// Writes results in the chosen format; CSV streams, tables buffer.
+struct ResultWriter {
+    format: OutputFormat,
+    columns: Vec<&'static str>,
+    rows: Vec<Vec<String>>,
+    // (column index, descending) when rows are held back for sorting.
+    sort: Option<(usize, bool)>,
+    pending: Vec<(ResultRecord, Vec<String>)>,
+}
+
+impl ResultWriter {
+    fn new(format: OutputFormat) -> Self {
+        Self::with_columns(format, RESULT_COLUMNS.to_vec())
+    }
+
+    /// Rows end with a `speedup` cell, given per row via `speedup_row`.
+    /// Binary records have no slot for it and carry the standard fields only.
+    fn with_speedup(format: OutputFormat) -> Self {
+        let mut columns = RESULT_COLUMNS.to_vec();
+        columns.push("speedup");
+        Self::with_columns(format, columns)
+    }
+
+    fn with_columns(format: OutputFormat, columns: Vec<&'static str>) -> Self {
+        match format {
+            OutputFormat::Csv => print_csv_header(&columns),
+            OutputFormat::Binary => {
+                results::write_header(&mut io::stdout().lock()).expect("write results header");
+            }
+            OutputFormat::Table => {}
+        }
+        Self {
+            format,
+            columns,
+            rows: Vec::new(),
+            sort: None,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Hold rows until `finish`, then print them ordered by `key`. The
+    /// column must be one this writer prints.
+    fn sort_by(&mut self, key: &SortKey) {
+        let index = self
+            .columns
+            .iter()
+            .position(|&name| name == key.column)
+            .expect("sort column validated by the caller");
+        self.sort = Some((index, key.descending));
+    }
+
+    fn row(&mut self, result: &BenchResult) {
+        self.write(result, result_cells(result));
+    }
+
+    fn speedup_row(&mut self, result: &BenchResult, speedup: Option<f64>) {
+        let mut cells = result_cells(result);
+        cells.push(speedup.map_or_else(|| "NA".to_string(), |ratio| format!("{ratio:.2}")));
+        self.write(result, cells);
+    }
+
+    fn write(&mut self, result: &BenchResult, cells: Vec<String>) {
+        let record = result_record(result);
+        if self.sort.is_some() {
+            self.pending.push((record, cells));
+        } else {
+            self.emit(&record, cells);
+        }
+    }
+
+    fn emit(&mut self, record: &ResultRecord, cells: Vec<String>) {
+        match self.format {
+            OutputFormat::Csv => println!("{}", cells.join(",")),
+            OutputFormat::Table => self.rows.push(cells),
+            OutputFormat::Binary => {
+                results::write_record(&mut io::stdout().lock(), record)
+                    .expect("write result record");
+            }
+        }
+    }
+
+    fn finish(mut self) {
+        if let Some((index, descending)) = self.sort {
+            let mut pending = std::mem::take(&mut self.pending);
+            // Stable, so ties keep sweep order.
+            pending.sort_by(|(_, a), (_, b)| compare_cells(&a[index], &b[index], descending));
+            for (record, cells) in pending {
+                self.emit(&record, cells);
+            }
+        }
+        if self.format == OutputFormat::Table {
+            print!("{}", render_table(&self.columns, &self.rows));
+        }
+    }
+}
+
+/// The stderr line for `leftover` stranded tasks; an error under
+/// `--strict-leftover`.
+fn leftover_line(leftover: usize, strict: bool) -> String {
+    if strict {
+        format!("# error,leftover,{leftover}")
+    } else {
+        format!("# warning,leftover_tasks,{leftover}")
+    }
+}
+
+/// The status `bench` exits with: 1 when `--strict-leftover` finds stranded
+/// tasks or throughput misses the `--min-throughput` floor, else 0. A run
+/// that produced no result cannot clear a floor.
+pub fn bench_exit_status(
+    result: Option<&BenchResult>,
+    strict_leftover: bool,
+    floor: Option<f64>,
+) -> i32 {
+    let failed = match result {
+        Some(result) => {
+            (strict_leftover && result.leftover > 0)
+                || floor.is_some_and(|floor| result.throughput < floor)
+        }
+        None => floor.is_some(),
+    };
+    i32::from(failed)
+}
+
+/// Emit leftover warnings and, in validate mode, invariant violations.
+fn report_result_warnings(result: &BenchResult, validate: bool, strict_leftover: bool) {
+    if result.canceled {
+        eprintln!("# warning,canceled");
+    }
     if result.leftover > 0 {
-        eprintln!("# warning,leftover_tasks,{}", result.leftover);
+        eprintln!("{}", leftover_line(result.leftover, strict_leftover));
+    }
+    if !result.leftover_ids.is_empty() {
+        let shown: Vec<String> = result
+            .leftover_ids
+            .iter()
+            .take(LEFTOVER_IDS_SHOWN)
+            .map(|id| id.to_string())
+            .collect();
+        let more = if result.leftover_ids.len() > LEFTOVER_IDS_SHOWN {
+            ";..."
+        } else {
+            ""
+        };
+        eprintln!("# warning,leftover_task_ids,{}{more}", shown.join(";"));
     }
     if validate {
         if result.zone_violation {
             eprintln!("# violation,zone_exclusivity");
         }
+        if let Some(first) = result.first_violation.as_ref() {
+            eprintln!(
+                "# violation,first_at_ms={:.3},zone={},robot={},observed={}",
+                first.at_ms, first.zone, first.robot, first.observed
+            );
+        }
         if result.duplicate_tasks {
             eprintln!("# violation,duplicate_tasks");
         }
+        if !result.leftover_expected {
+            eprintln!("# violation,unexpected_leftover,{}", result.leftover);
+        }
     }
 }
 
-/// Sweep multiple benchmark configurations and print CSV output.
-pub fn run_stress(
-    robot_sets: Option<Vec<usize>>,
-    task_sets: Option<Vec<usize>>,
-    zone_sets: Option<Vec<u64>>,
-    work_ms: Option<u64>,
-    validate: bool,
-    simulate_offline: bool,
-) {
-    let default_robot_sets = [1usize, 2, 4, 8, 12];
-    let default_task_sets = [10usize, 25, 50];
-    let default_zone_sets = [1u64, 2, 4];
-    let work_ms = work_ms.unwrap_or(5);
-
-    let robot_sets = robot_sets.unwrap_or_else(|| default_robot_sets.to_vec());
-    let task_sets = task_sets.unwrap_or_else(|| default_task_sets.to_vec());
-    let mut zone_sets = zone_sets.unwrap_or_else(|| default_zone_sets.to_vec());
-    if robot_sets.iter().any(|&robots| robots == 0) {
-        eprintln!("stress error: robot_sets must be > 0");
-        return;
+/// Run and report a single benchmark for the given configuration.
+///
+/// Returns the result, or `None` if the config was rejected or the run was
+/// `--contention-only` (which has no task result).
+pub fn run_benchmark(config: BenchConfig) -> Option<BenchResult> {
+    if config.robots == 0 {
+        eprintln!("benchmark error: robots must be > 0");
+        return None;
     }
-    if task_sets.iter().any(|&tasks| tasks == 0) {
-        eprintln!("stress error: task_sets must be > 0");
-        return;
+    if config.tasks_per_robot == 0 {
+        eprintln!("benchmark error: tasks_per_robot must be > 0");
+        return None;
     }
-    if zone_sets.iter().any(|&zones| zones == 0) {
-        let before = zone_sets.len();
-        zone_sets.retain(|&zones| zones > 0);
-        let dropped = before.saturating_sub(zone_sets.len());
-        if dropped > 0 {
-            eprintln!("stress warning: ignored {dropped} zone set(s) <= 0");
-        }
-        if zone_sets.is_empty() {
-            eprintln!("stress error: zones must be > 0");
-            return;
-        }
+    if config.zones_total == 0 {
+        eprintln!("benchmark error: zones must be > 0");
+        return None;
     }
-
-    println!(
-        "robots,tasks_per_robot,zones,total_tasks,elapsed_ms,throughput_tasks_per_s,avg_zone_wait_us,cpu_user_s,cpu_sys_s,max_occupancy,zone_violation,duplicate_tasks,offline_robots"
+    if let Some(err) = zone_weights_error(config.zone_weights.as_deref(), config.zones_total) {
+        eprintln!("benchmark error: {err}");
+        return None;
+    }
+    // Ties the report to the exact build; `unknown` outside a git checkout.
+    eprintln!(
+        "# manifest,version={},commit={},seed={},robots={},tasks_per_robot={},zones={},work_ms={}",
+        env!("CARGO_PKG_VERSION"),
+        env!("BLAZE_GIT_COMMIT"),
+        config.seed,
+        config.robots,
+        config.tasks_per_robot,
+        config.zones_total,
+        config.work_ms
     );
-    for robots in robot_sets {
-        for tasks_per_robot in task_sets.iter().copied() {
-            for zones_total in zone_sets.iter().copied() {
-                let result = benchmark_once(
+    if config.contention_only {
+        run_contention(&config);
+        return None;
+    }
+    let total_tasks = config.task_count();
+    if config.zones_total > total_tasks as u64 {
+        // Harmless, but at least this many zones can never see a task.
+        eprintln!(
+            "# warning,idle_zones,{}",
+            config.zones_total - total_tasks as u64
+        );
+    }
+    let validate = config.validate || config.chaos.is_some();
+    let (outcome, attempts) = with_retries(config.retries, || {
+        try_benchmark_run(&config, Arc::new(AtomicBool::new(false)))
+    });
+    if config.retries > 0 {
+        eprintln!("# attempts,{attempts}");
+    }
+    let result = match outcome {
+        Ok(result) => result,
+        Err(err) => {
+            eprintln!("benchmark error: {err} (after {attempts} attempts)");
+            return None;
+        }
+    };
+    if config.offline_recover_after.is_some() {
+        eprintln!(
+            "# offline_recovery,recovered_robots,{}",
+            result.recovered_count
+        );
+    }
+    if config.zone_acquire_timeout_ms.is_some() {
+        eprintln!("# zone_timeout,requeued_tasks,{}", result.requeued_tasks);
+    }
+    if let Some(seed) = config.chaos {
+        eprintln!(
+            "# chaos,seed={seed},events={},slow_releases={},heartbeat_delays={},recovered_robots={}",
+            result.chaos_slow_releases + result.chaos_heartbeat_delays,
+            result.chaos_slow_releases,
+            result.chaos_heartbeat_delays,
+            result.recovered_count
+        );
+    }
+    eprintln!(
+        "# fairness,max_single_zone_wait_us,{}",
+        result.max_single_zone_wait_us
+    );
+    eprintln!("# checksum,completed_tasks,{:016x}", result.task_checksum);
+    if let Some(suggested) = result.suggested_timeout_ms {
+        eprintln!(
+            "# health_timeout,configured_ms={BENCH_OFFLINE_TIMEOUT_MS},suggested_ms={suggested:.2}"
+        );
+    }
+    eprintln!(
+        "# spurious_wakeups,queue={},zones={}",
+        result.queue_spurious_wakeups, result.zone_spurious_wakeups
+    );
+    eprintln!("# zone_utilization,zone,pct");
+    for (zone, pct) in &result.zone_utilization_pct {
+        eprintln!("# zone_utilization,{zone},{pct:.2}");
+    }
+    eprintln!("# zone_hold,zone,avg_ms");
+    for (zone, avg_ms) in &result.zone_avg_hold_ms {
+        eprintln!("# zone_hold,{zone},{avg_ms:.2}");
+    }
+    eprintln!("# zone_rate,zone,rate");
+    for (zone, rate) in &result.zone_rate {
+        eprintln!("# zone_rate,{zone},{rate:.2}");
+    }
+    if let Some(robot) = result.straggler_robot {
+        eprintln!(
+            "# straggler,robot={robot},lag_ms={:.2}",
+            result.straggler_lag_ms
+        );
+    }
+    eprintln!(
+        "# completion_time,stddev_ms={:.2}",
+        result.completion_time_stddev_ms
+    );
+    eprintln!(
+        "# wait_phase,startup_avg_wait_us={:.2},steady_avg_wait_us={:.2}",
+        result.startup_avg_wait_us, result.steady_avg_wait_us
+    );
+    let processed: usize = result.per_robot_completed.iter().sum();
+    eprintln!(
+        "# outcomes,succeeded={},failed={}",
+        processed - result.failed_tasks,
+        result.failed_tasks
+    );
+    eprintln!("# robot_done,robot,completed");
+    for (robot, completed) in result.per_robot_completed.iter().enumerate() {
+        eprintln!("# robot_done,{robot},{completed}");
+    }
+    eprintln!("# first_task,robot,task");
+    for (robot, task) in result.first_task_per_robot.iter().enumerate() {
+        let task = task.map_or_else(|| "NA".to_string(), |id| id.to_string());
+        eprintln!("# first_task,{robot},{task}");
+    }
+    if let Some(tasks) = result.late_robot_tasks {
+        eprintln!(
+            "# late_robot,robot={},tasks={tasks},tracked_robots={}",
+            config.robots, result.tracked_robots
+        );
+    }
+
+    let mut writer = ResultWriter::new(config.format);
+    writer.row(&result);
+    writer.finish();
+    // Per-bucket completions go to stderr so stdout stays a single CSV table.
+    eprintln!("# throughput_series,bucket_ms,count");
+    for (index, count) in result.throughput_series.iter().enumerate() {
+        eprintln!(
+            "# throughput_series,{},{count}",
+            index as u64 * THROUGHPUT_BUCKET_MS
+        );
+    }
+    if result.throughput_series_overflow > 0 {
+        eprintln!(
+            "# throughput_series_overflow,{}",
+            result.throughput_series_overflow
+        );
+    }
+    report_result_warnings(&result, validate, config.strict_leftover);
+    Some(result)
+}
+
+/// Lock-contention metrics from a `--contention-only` run.
+pub struct ContentionResult {
+    pub robots: usize,
+    pub acquisitions: usize,
+    pub elapsed_ms: f64,
+    pub acquisitions_per_s: f64,
+    /// Acquisitions that found the zone busy and had to wait.
+    pub blocked: usize,
+    pub notifications: u64,
+    pub wasted_wakeups: u64,
+}
+
+/// Have every robot acquire and immediately release zone 1 `iterations` times.
+///
+/// No queue and no work sleep, so the figures isolate `ZoneAccess` itself.
+fn contention_once(robots: usize, iterations: usize, spinning: bool) -> ContentionResult {
+    let zones = if spinning {
+        ZoneAccess::new_spinning()
+    } else {
+        ZoneAccess::new()
+    };
+    contention_run(Arc::new(zones), robots, iterations)
+}
+
+fn contention_run(zones: Arc<ZoneAccess>, robots: usize, iterations: usize) -> ContentionResult {
+    let blocked = Arc::new(AtomicUsize::new(0));
+    let start = Instant::now();
+    let mut handles = Vec::with_capacity(robots);
+    for robot_id in 0..robots {
+        let zones = Arc::clone(&zones);
+        let blocked = Arc::clone(&blocked);
+        handles.push(thread::spawn(move || {
+            let robot = robot_id as RobotId;
+            for _ in 0..iterations {
+                if !zones.try_acquire(CONTENTION_ZONE, robot) {
+                    blocked.fetch_add(1, Ordering::Relaxed);
+                    zones.acquire(CONTENTION_ZONE, robot);
+                }
+                zones.release(CONTENTION_ZONE, robot);
+            }
+        }));
+    }
+    for handle in handles {
+        handle.join().expect("contention thread panicked");
+    }
+    let elapsed = start.elapsed();
+    let acquisitions = robots * iterations;
+    let (notifications, wasted_wakeups) = zones.wakeup_stats();
+    ContentionResult {
+        robots,
+        acquisitions,
+        elapsed_ms: elapsed.as_secs_f64() * 1000.0,
+        acquisitions_per_s: acquisitions as f64 / elapsed.as_secs_f64().max(f64::EPSILON),
+        blocked: blocked.load(Ordering::Relaxed),
+        notifications,
+        wasted_wakeups,
+    }
+}
+
+fn run_contention(config: &BenchConfig) {
+    let result = contention_once(config.robots, config.tasks_per_robot, config.spin_zones);
+    println!(
+        "robots,acquisitions,elapsed_ms,acquisitions_per_s,blocked,notifications,wasted_wakeups"
+    );
+    println!(
+        "{},{},{:.2},{:.2},{},{},{}",
+        result.robots,
+        result.acquisitions,
+        result.elapsed_ms,
+        result.acquisitions_per_s,
+        result.blocked,
+        result.notifications,
+        result.wasted_wakeups
+    );
+}
+
+/// Raw queue throughput from a `qbench` run.
+pub struct QueueBenchResult {
+    pub producers: usize,
+    pub consumers: usize,
+    pub pushed: u64,
+    pub popped: u64,
+    pub elapsed_ms: f64,
+    /// Pushes plus pops per second.
+    pub ops_per_s: f64,
+}
+
+/// Have `producers` threads push `tasks_per_producer` tasks each while
+/// `consumers` threads pop until the queue closes and drains.
+///
+/// No zones, work, or health monitoring, so the figures isolate the queue's
+/// mutex and condvar.
+fn queue_bench(producers: usize, consumers: usize, tasks_per_producer: usize) -> QueueBenchResult {
+    let queue = Arc::new(TaskQueue::new());
+    let start = Instant::now();
+    let consumer_handles: Vec<_> = (0..consumers)
+        .map(|_| {
+            let queue = Arc::clone(&queue);
+            thread::spawn(move || while queue.pop_blocking_or_closed().is_some() {})
+        })
+        .collect();
+    let producer_handles: Vec<_> = (0..producers)
+        .map(|producer| {
+            let queue = Arc::clone(&queue);
+            thread::spawn(move || {
+                let first = (producer * tasks_per_producer) as TaskId;
+                for id in first..first + tasks_per_producer as TaskId {
+                    queue
+                        .push(Task::new(id, String::new()))
+                        .expect("task queue closed");
+                }
+            })
+        })
+        .collect();
+    for handle in producer_handles {
+        handle.join().expect("producer thread panicked");
+    }
+    // Consumers finish what is queued, then see the close and exit.
+    queue.close();
+    for handle in consumer_handles {
+        handle.join().expect("consumer thread panicked");
+    }
+    let elapsed = start.elapsed();
+    let counts = queue.counts();
+    QueueBenchResult {
+        producers,
+        consumers,
+        pushed: counts.pushed,
+        popped: counts.popped,
+        elapsed_ms: elapsed.as_secs_f64() * 1000.0,
+        ops_per_s: (counts.pushed + counts.popped) as f64 / elapsed.as_secs_f64().max(f64::EPSILON),
+    }
+}
+
+/// `qbench`: queue-only microbenchmark, printed as one CSV row.
+pub fn run_qbench(producers: Option<usize>, consumers: Option<usize>, tasks: Option<usize>) {
+    let producers = producers.unwrap_or(2);
+    let consumers = consumers.unwrap_or(2);
+    let tasks = tasks.unwrap_or(10_000);
+    if producers == 0 || consumers == 0 {
+        eprintln!("qbench error: producers and consumers must be > 0");
+        return;
+    }
+    let result = queue_bench(producers, consumers, tasks);
+    println!("producers,consumers,pushed,popped,elapsed_ms,ops_per_s");
+    println!(
+        "{},{},{},{},{:.2},{:.2}",
+        result.producers,
+        result.consumers,
+        result.pushed,
+        result.popped,
+        result.elapsed_ms,
+        result.ops_per_s
+    );
+}
+
+/// Jain's fairness index over per-robot completion counts: 1.0 when every
+/// robot did the same work, down to `1/n` when one robot did all of it.
+/// `None` when nothing was completed.
+fn jain_index(counts: &[usize]) -> Option<f64> {
+    let sum: f64 = counts.iter().map(|&count| count as f64).sum();
+    let sum_squares: f64 = counts.iter().map(|&count| (count as f64).powi(2)).sum();
+    (sum_squares > 0.0).then(|| sum * sum / (counts.len() as f64 * sum_squares))
+}
+
+/// `fairness`: run one bench config and print Jain's index over how many
+/// tasks each robot completed, as one CSV row.
+pub fn run_fairness(config: BenchConfig) {
+    if config.robots == 0 || config.tasks_per_robot == 0 || config.zones_total == 0 {
+        eprintln!("fairness error: robots, tasks_per_robot, and zones must be > 0");
+        return;
+    }
+    let result = benchmark_once(&config);
+    report_result_warnings(&result, config.validate, false);
+    let counts = &result.per_robot_completed;
+    let index = jain_index(counts).map_or_else(|| "NA".to_string(), |index| format!("{index:.4}"));
+    println!("robots,completed_tasks,min_robot_tasks,max_robot_tasks,jain_index");
+    println!(
+        "{},{},{},{},{index}",
+        result.robots,
+        counts.iter().sum::<usize>(),
+        counts.iter().min().copied().unwrap_or(0),
+        counts.iter().max().copied().unwrap_or(0),
+    );
+}
+
+/// Sweep-level settings that apply across all stress configurations.
+#[derive(Clone, Debug, Default)]
+pub struct StressOptions {
+    /// Skip remaining configurations once the sweep has run this long.
+    pub max_duration: Option<Duration>,
+    /// Run exactly these configurations instead of the sets' cross product.
+    pub configs: Option<Vec<StressPoint>>,
+    /// Generate tasks once and give every configuration a prefix of them.
+    pub fixed_tasks: bool,
+    /// Add a `speedup` column: throughput relative to the first configuration.
+    pub baseline: bool,
+    /// Buffer every row and print them ordered by this column.
+    pub sort_by: Option<SortKey>,
+}
+
+/// One explicit stress configuration; `work_ms` falls back to the base config.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StressPoint {
+    pub robots: usize,
+    pub tasks_per_robot: usize,
+    pub zones_total: u64,
+    pub work_ms: Option<u64>,
+}
+
+/// Parse `robots,tasks,zones[,work_ms]` lines; blanks and `#` comments are skipped.
+pub fn parse_stress_configs(text: &str) -> Result<Vec<StressPoint>, String> {
+    let mut points = Vec::new();
+    for (index, raw) in text.lines().enumerate() {
+        let line = raw.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line_no = index + 1;
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        if !(3..=4).contains(&fields.len()) {
+            return Err(format!(
+                "line {line_no}: expected robots,tasks,zones[,work_ms]"
+            ));
+        }
+        let positive = |field: &str, name: &str| match field.parse::<u64>() {
+            Ok(value) if value > 0 => Ok(value),
+            _ => Err(format!("line {line_no}: invalid {name} '{field}'")),
+        };
+        let work_ms = match fields.get(3) {
+            Some(field) => Some(
+                field
+                    .parse()
+                    .map_err(|_| format!("line {line_no}: invalid work_ms '{field}'"))?,
+            ),
+            None => None,
+        };
+        points.push(StressPoint {
+            robots: positive(fields[0], "robots")? as usize,
+            tasks_per_robot: positive(fields[1], "tasks")? as usize,
+            zones_total: positive(fields[2], "zones")?,
+            work_ms,
+        });
+    }
+    if points.is_empty() {
+        return Err("no configurations listed".to_string());
+    }
+    Ok(points)
+}
+
+/// Whole-sweep totals for the closing `# digest` line.
+#[derive(Default)]
+struct SweepDigest {
+    configs: usize,
+    tasks: usize,
+    elapsed_ms: f64,
+    violations: usize,
+}
+
+impl SweepDigest {
+    fn add(&mut self, result: &BenchResult) {
+        self.configs += 1;
+        self.tasks += result.total_tasks - result.leftover;
+        self.elapsed_ms += result.elapsed_ms;
+        if result.zone_violation || result.duplicate_tasks {
+            self.violations += 1;
+        }
+    }
+
+    /// Completed tasks over the summed run time of every configuration.
+    fn throughput(&self) -> f64 {
+        if self.elapsed_ms > 0.0 {
+            self.tasks as f64 / (self.elapsed_ms / 1000.0)
+        } else {
+            0.0
+        }
+    }
+
+    fn line(&self) -> String {
+        format!(
+            "# digest,configs={},tasks={},throughput={:.2},violations={}",
+            self.configs,
+            self.tasks,
+            self.throughput(),
+            self.violations
+        )
+    }
+}
+
+/// Sweep multiple benchmark configurations and print CSV output.
+///
+/// `base` supplies `work_ms` and flags; robots, tasks, and zones come from the sets,
+/// or from `options.configs` when given. A `# digest` line on stderr closes the sweep.
+pub fn run_stress(
+    robot_sets: Option<Vec<usize>>,
+    task_sets: Option<Vec<usize>>,
+    zone_sets: Option<Vec<u64>>,
+    base: BenchConfig,
+    options: StressOptions,
+) {
+    let configs = match options.configs {
+        Some(configs) => configs,
+        None => match stress_cross_product(robot_sets, task_sets, zone_sets) {
+            Some(configs) => configs,
+            None => return,
+        },
+    };
+
+    let weights = base.zone_weights.as_deref();
+    if let Some(err) = configs
+        .iter()
+        .find_map(|point| zone_weights_error(weights, point.zones_total))
+    {
+        eprintln!("stress error: {err}");
+        return;
+    }
+
+    let mut base = base;
+    if options.fixed_tasks {
+        // Sized for the largest configuration; smaller ones take a prefix.
+        let max_total = configs
+            .iter()
+            .map(|point| point.robots * point.tasks_per_robot)
+            .max()
+            .unwrap_or(0);
+        let canonical = bench_tasks(
+            max_total,
+            base.zone_weights.as_deref(),
+            base.seed,
+            base.rng_log.as_ref(),
+        );
+        base.fixed_tasks = Some(Arc::new(canonical));
+    }
+
+    if let Some(key) = options.sort_by.as_ref()
+        && key.column == "speedup"
+        && !options.baseline
+    {
+        eprintln!("stress error: --sort-by speedup needs --baseline");
+        return;
+    }
+    let mut writer = if options.baseline {
+        ResultWriter::with_speedup(base.format)
+    } else {
+        ResultWriter::new(base.format)
+    };
+    if let Some(key) = options.sort_by.as_ref() {
+        writer.sort_by(key);
+    }
+    let mut baseline_throughput = None;
+    let mut digest = SweepDigest::default();
+    let sweep_start = Instant::now();
+    for (index, point) in configs.iter().enumerate() {
+        // The budget is checked between configurations; a running one always finishes.
+        if let Some(max_duration) = options.max_duration
+            && sweep_start.elapsed() >= max_duration
+        {
+            let skipped = &configs[index..];
+            eprintln!("# skipped,{}", skipped.len());
+            for point in skipped {
+                eprintln!(
+                    "# skipped_config,{},{},{}",
+                    point.robots, point.tasks_per_robot, point.zones_total
+                );
+            }
+            break;
+        }
+        let result = benchmark_once(&BenchConfig {
+            robots: point.robots,
+            tasks_per_robot: point.tasks_per_robot,
+            zones_total: point.zones_total,
+            work_ms: point.work_ms.unwrap_or(base.work_ms),
+            ..base.clone()
+        });
+        if options.baseline {
+            let baseline = *baseline_throughput.get_or_insert(result.throughput);
+            writer.speedup_row(&result, speedup_ratio(result.throughput, baseline));
+        } else {
+            writer.row(&result);
+        }
+        report_result_warnings(&result, base.validate || base.chaos.is_some(), false);
+        digest.add(&result);
+    }
+    writer.finish();
+    eprintln!("{}", digest.line());
+}
+
+// Expand the robot/task/zone sets (or their defaults) into every combination;
+// `None` after reporting an unusable set.
+fn stress_cross_product(
+    robot_sets: Option<Vec<usize>>,
+    task_sets: Option<Vec<usize>>,
+    zone_sets: Option<Vec<u64>>,
+) -> Option<Vec<StressPoint>> {
+    let default_robot_sets = [1usize, 2, 4, 8, 12];
+    let default_task_sets = [10usize, 25, 50];
+    let default_zone_sets = [1u64, 2, 4];
+
+    let robot_sets = robot_sets.unwrap_or_else(|| default_robot_sets.to_vec());
+    let task_sets = task_sets.unwrap_or_else(|| default_task_sets.to_vec());
+    let mut zone_sets = zone_sets.unwrap_or_else(|| default_zone_sets.to_vec());
+    if robot_sets.contains(&0) {
+        eprintln!("stress error: robot_sets must be > 0");
+        return None;
+    }
+    if task_sets.contains(&0) {
+        eprintln!("stress error: task_sets must be > 0");
+        return None;
+    }
+    if zone_sets.contains(&0) {
+        let before = zone_sets.len();
+        zone_sets.retain(|&zones| zones > 0);
+        let dropped = before.saturating_sub(zone_sets.len());
+        if dropped > 0 {
+            eprintln!("stress warning: ignored {dropped} zone set(s) <= 0");
+        }
+        if zone_sets.is_empty() {
+            eprintln!("stress error: zones must be > 0");
+            return None;
+        }
+    }
+
+    let mut configs = Vec::new();
+    for robots in robot_sets {
+        for tasks_per_robot in task_sets.iter().copied() {
+            for zones_total in zone_sets.iter().copied() {
+                configs.push(StressPoint {
                     robots,
                     tasks_per_robot,
                     zones_total,
-                    work_ms,
-                    validate,
-                    simulate_offline,
-                );
-                let cpu_user = result
-                    .cpu_user_s
-                    .map(|v| format!("{v:.4}"))
-                    .unwrap_or_else(|| "NA".to_string());
-                let cpu_sys = result
-                    .cpu_sys_s
-                    .map(|v| format!("{v:.4}"))
-                    .unwrap_or_else(|| "NA".to_string());
-                println!(
-                    "{},{},{},{},{:.2},{:.2},{:.2},{},{},{},{},{},{}",
-                    result.robots,
-                    result.tasks_per_robot,
-                    result.zones_total,
-                    result.total_tasks,
-                    result.elapsed_ms,
-                    result.throughput,
-                    result.avg_zone_wait_us,
-                    cpu_user,
-                    cpu_sys,
-                    result.max_occupancy,
-                    result.zone_violation,
-                    result.duplicate_tasks,
-                    result.offline_count
-                );
-                if result.leftover > 0 {
-                    eprintln!("# warning,leftover_tasks,{}", result.leftover);
-                }
-                if validate {
-                    if result.zone_violation {
-                        eprintln!("# violation,zone_exclusivity");
-                    }
-                    if result.duplicate_tasks {
-                        eprintln!("# violation,duplicate_tasks");
-                    }
+                    work_ms: None,
+                });
+            }
+        }
+    }
+    Some(configs)
+}
+
+/// Geometric robot counts (1, 2, 4, ...) capped by and always including `max_robots`.
+fn geometric_robot_sweep(max_robots: usize) -> Vec<usize> {
+    let mut counts = Vec::new();
+    let mut robots = 1usize;
+    while robots < max_robots {
+        counts.push(robots);
+        robots *= 2;
+    }
+    counts.push(max_robots);
+    counts
+}
+
+/// Pick the robot count after which throughput gains fall below `AUTOTUNE_MIN_GAIN`.
+fn knee_point(samples: &[(usize, f64)]) -> usize {
+    for pair in samples.windows(2) {
+        let (prev_robots, prev_throughput) = pair[0];
+        let (_, throughput) = pair[1];
+        if prev_throughput > 0.0
+            && (throughput - prev_throughput) / prev_throughput < AUTOTUNE_MIN_GAIN
+        {
+            return prev_robots;
+        }
+    }
+    samples.last().map(|&(robots, _)| robots).unwrap_or(1)
+}
+
+/// Run the autotune sweep and return its results with the recommended robot count.
+///
+/// `base.robots` is ignored; each sweep point overrides it.
+fn autotune_collect(max_robots: usize, base: &BenchConfig) -> (Vec<BenchResult>, usize) {
+    let results: Vec<BenchResult> = geometric_robot_sweep(max_robots)
+        .into_iter()
+        .map(|robots| {
+            benchmark_once(&BenchConfig {
+                robots,
+                ..base.clone()
+            })
+        })
+        .collect();
+    let samples: Vec<(usize, f64)> = results
+        .iter()
+        .map(|result| (result.robots, result.throughput))
+        .collect();
+    let recommended = knee_point(&samples);
+    (results, recommended)
+}
+
+/// Sweep robot counts geometrically and recommend where throughput levels off.
+pub fn run_autotune(max_robots: Option<usize>, base: BenchConfig) {
+    let max_robots = max_robots.unwrap_or(16);
+    if max_robots == 0 || base.tasks_per_robot == 0 || base.zones_total == 0 {
+        eprintln!("autotune error: max_robots, tasks_per_robot, and zones must be > 0");
+        return;
+    }
+
+    let (results, recommended) = autotune_collect(max_robots, &base);
+    let mut writer = ResultWriter::new(base.format);
+    for result in &results {
+        writer.row(result);
+        report_result_warnings(result, base.validate, false);
+    }
+    writer.finish();
+    eprintln!("# autotune,recommended_robots,{recommended}");
+}
+
+/// How long `detect_offline` took to flag a silent robot, over many trials.
+struct DetectionLatencyReport {
+    trials: usize,
+    mean_ms: f64,
+    p95_ms: f64,
+    max_ms: f64,
+}
+
+// One trial on a mock clock: the robot's last heartbeat lands `phase` into a
+// poll period, then the clock steps one poll at a time until the monitor
+// flags it. Returns the time from that heartbeat to detection.
+fn detection_latency_trial(timeout: Duration, poll: Duration, phase: Duration) -> Duration {
+    let clock = Arc::new(MockClock::new());
+    let monitor = HealthMonitor::new().with_clock(Arc::clone(&clock) as Arc<dyn Clock>);
+    monitor.register_robot(0);
+    clock.advance(phase);
+    monitor.heartbeat(0);
+    let mut since_stop = Duration::ZERO;
+    let mut step = poll - phase;
+    loop {
+        clock.advance(step);
+        since_stop += step;
+        if monitor.detect_offline(timeout).contains(&0) {
+            return since_stop;
+        }
+        step = poll;
+    }
+}
+
+/// Run `trials` detections with seeded stop phases within the poll period.
+fn detection_latency(
+    trials: usize,
+    timeout: Duration,
+    poll: Duration,
+    seed: u64,
+) -> DetectionLatencyReport {
+    let mut rng = SplitMix64::new(seed);
+    let poll_us = poll.as_micros().max(1) as u64;
+    let mut latencies_ms: Vec<f64> = (0..trials)
+        .map(|_| {
+            let phase = Duration::from_micros(rng.below(poll_us));
+            detection_latency_trial(timeout, poll, phase).as_secs_f64() * 1000.0
+        })
+        .collect();
+    latencies_ms.sort_by(f64::total_cmp);
+    let p95_index = (trials * 95).div_ceil(100).saturating_sub(1);
+    DetectionLatencyReport {
+        trials,
+        mean_ms: latencies_ms.iter().sum::<f64>() / trials.max(1) as f64,
+        p95_ms: latencies_ms.get(p95_index).copied().unwrap_or(0.0),
+        max_ms: latencies_ms.last().copied().unwrap_or(0.0),
+    }
+}
+
+/// `detection-latency`: how far past the timeout polling `detect_offline`
+/// flags a robot that stopped heartbeating, printed as one CSV row.
+///
+/// Runs on a mock clock, so the figures isolate the poll interval from
+/// scheduler noise.
+pub fn run_detection_latency(trials: Option<usize>, timeout_ms: Option<u64>, poll_ms: Option<u64>) {
+    let trials = trials.unwrap_or(200);
+    let timeout_ms = timeout_ms.unwrap_or(HEALTH_TIMEOUT_MS);
+    let poll_ms = poll_ms.unwrap_or(HEALTH_POLL_MS);
+    if trials == 0 || poll_ms == 0 {
+        eprintln!("detection-latency error: trials and poll_ms must be > 0");
+        return;
+    }
+    let report = detection_latency(
+        trials,
+        Duration::from_millis(timeout_ms),
+        Duration::from_millis(poll_ms),
+        DEFAULT_SEED,
+    );
+    println!("trials,timeout_ms,poll_ms,mean_latency_ms,p95_latency_ms,max_latency_ms");
+    println!(
+        "{},{timeout_ms},{poll_ms},{:.3},{:.3},{:.3}",
+        report.trials, report.mean_ms, report.p95_ms, report.max_ms
+    );
+}
+
+/// Robots that deliberately stop heartbeating during a health scenario.
+pub struct OfflinePlan {
+    /// Robots that go silent partway through the run.
+    pub stopped: HashSet<RobotId>,
+    /// How long planned robots heartbeat before going silent.
+    pub stop_after: Duration,
+}
+
+/// Offline detection accuracy measured against an `OfflinePlan`.
+struct HealthReport {
+    robots: usize,
+    planned_offline: usize,
+    true_positives: usize,
+    false_positives: usize,
+    false_negatives: usize,
+    avg_detection_latency_ms: f64,
+    max_detection_latency_ms: f64,
+}
+
+/// Run heartbeating robots per `plan` and score the monitor's verdicts.
+fn run_health_scenario(robots: usize, plan: &OfflinePlan, timeout: Duration) -> HealthReport {
+    let monitor = Arc::new(HealthMonitor::new());
+    let stop_flag = Arc::new(AtomicBool::new(false));
+    monitor.register_robots(0..robots as RobotId);
+
+    let start = Instant::now();
+    let mut handles = Vec::new();
+    for robot_id in 0..robots {
+        let robot_id = robot_id as RobotId;
+        let monitor = Arc::clone(&monitor);
+        let stop_flag = Arc::clone(&stop_flag);
+        let goes_silent = plan.stopped.contains(&robot_id);
+        let stop_after = plan.stop_after;
+        handles.push(thread::spawn(move || {
+            // Returns the last heartbeat instant so latency is measured from it.
+            let mut last_beat = Instant::now();
+            while !stop_flag.load(Ordering::SeqCst) {
+                if goes_silent && start.elapsed() >= stop_after {
+                    break;
                 }
+                monitor.heartbeat(robot_id);
+                last_beat = Instant::now();
+                thread::sleep(Duration::from_millis(HEALTH_HEARTBEAT_MS));
             }
+            (robot_id, last_beat)
+        }));
+    }
+
+    // Poll until every planned robot has had a full timeout window plus slack.
+    let run_for = plan.stop_after + timeout * 2;
+    let mut detected_at: HashMap<RobotId, Instant> = HashMap::new();
+    while start.elapsed() < run_for {
+        for robot in monitor.detect_offline(timeout) {
+            detected_at.entry(robot).or_insert_with(Instant::now);
+        }
+        thread::sleep(Duration::from_millis(HEALTH_POLL_MS));
+    }
+    stop_flag.store(true, Ordering::SeqCst);
+    let mut last_beats = HashMap::new();
+    for handle in handles {
+        let (robot, last_beat) = handle.join().expect("health robot thread panicked");
+        last_beats.insert(robot, last_beat);
+    }
+
+    let mut true_positives = 0usize;
+    let mut false_positives = 0usize;
+    let mut latencies_ms = Vec::new();
+    for (robot, at) in &detected_at {
+        if plan.stopped.contains(robot) {
+            true_positives += 1;
+            let last_beat = last_beats[robot];
+            latencies_ms.push(at.saturating_duration_since(last_beat).as_secs_f64() * 1000.0);
+        } else {
+            false_positives += 1;
         }
     }
+    let planned_offline = plan.stopped.len();
+    let avg_detection_latency_ms = if latencies_ms.is_empty() {
+        0.0
+    } else {
+        latencies_ms.iter().sum::<f64>() / latencies_ms.len() as f64
+    };
+    let max_detection_latency_ms = latencies_ms.iter().copied().fold(0.0, f64::max);
+
+    HealthReport {
+        robots,
+        planned_offline,
+        true_positives,
+        false_positives,
+        false_negatives: planned_offline.saturating_sub(true_positives),
+        avg_detection_latency_ms,
+        max_detection_latency_ms,
+    }
+}
+
+/// Run a short heartbeat scenario and print offline-detection accuracy as CSV.
+pub fn run_health(robots: Option<usize>, stopped: Option<Vec<RobotId>>) {
+    let robots = robots.unwrap_or(4);
+    let stopped = stopped.unwrap_or_else(|| vec![1]);
+    if robots == 0 {
+        eprintln!("health error: robots must be > 0");
+        return;
+    }
+    if let Some(robot) = stopped.iter().find(|&&robot| robot as usize >= robots) {
+        eprintln!("health error: offline robot {robot} out of range (robots={robots})");
+        return;
+    }
+    let plan = OfflinePlan {
+        stopped: stopped.into_iter().collect(),
+        stop_after: Duration::from_millis(HEALTH_STOP_AFTER_MS),
+    };
+    let report = run_health_scenario(robots, &plan, Duration::from_millis(HEALTH_TIMEOUT_MS));
+
+    println!(
+        "robots,planned_offline,true_positives,false_positives,false_negatives,avg_detection_latency_ms,max_detection_latency_ms"
+    );
+    println!(
+        "{},{},{},{},{},{:.2},{:.2}",
+        report.robots,
+        report.planned_offline,
+        report.true_positives,
+        report.false_positives,
+        report.false_negatives,
+        report.avg_detection_latency_ms,
+        report.max_detection_latency_ms
+    );
+}
+
+/// Observable outcome of a single deterministic simulation step.
+#[allow(dead_code)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StepOutcome {
+    /// The robot fetched a task and now targets its zone.
+    Fetched {
+        robot: RobotId,
+        task: TaskId,
+        zone: ZoneId,
+    },
+    /// The robot entered its target zone.
+    Entered { robot: RobotId, zone: ZoneId },
+    /// The target zone was occupied; the robot keeps waiting.
+    Blocked { robot: RobotId, zone: ZoneId },
+    /// The robot finished its task and left the zone.
+    Released {
+        robot: RobotId,
+        task: TaskId,
+        zone: ZoneId,
+    },
+    /// Every queued task waits on a dependency another robot is still
+    /// working on; the robot stays idle and tries again next step.
+    Waiting { robot: RobotId },
+    /// The queue was empty; the robot has no more work.
+    Finished { robot: RobotId },
+}
+
+/// How `Simulation::shutdown` treats work that is still queued.
+#[allow(dead_code)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShutdownMode {
+    /// Stop accepting tasks, then step robots until the queue drains.
+    Graceful,
+    /// Stop accepting tasks and stop robots where they are.
+    Immediate,
+}
+
+/// Per-robot state for the cooperative scheduler.
+enum RobotPhase {
+    Idle,
+    WaitingZone(Task, ZoneId),
+    InZone(Task, ZoneId),
+    Finished,
+}
+
+/// Single-threaded simulation where robots are advanced by explicit steps.
+///
+/// Robots are state machines instead of OS threads, so the caller chooses
+/// every interleaving and rare orderings become reproducible in tests.
+#[allow(dead_code)]
+pub struct Simulation {
+    queue: TaskQueue,
+    zones: ZoneAccess,
+    monitor: HealthMonitor,
+    zones_total: u64,
+    phases: Vec<RobotPhase>,
+    next_robot: usize,
+}
+
+#[allow(dead_code)]
+impl Simulation {
+    /// Create a simulation with `robots` idle robots and a preloaded queue.
+    pub fn new(robots: usize, tasks: impl IntoIterator<Item = Task>, zones_total: u64) -> Self {
+        debug_assert!(zones_total > 0, "zones_total must be > 0");
+        let queue = TaskQueue::new();
+        // One batch, so tasks may depend on ones listed after them.
+        queue
+            .push_many(tasks.into_iter().collect())
+            .expect("tasks have unknown or cyclic dependencies");
+        let monitor = HealthMonitor::new();
+        monitor.register_robots(0..robots as RobotId);
+        Self {
+            queue,
+            zones: ZoneAccess::new(),
+            monitor,
+            zones_total,
+            phases: (0..robots).map(|_| RobotPhase::Idle).collect(),
+            next_robot: 0,
+        }
+    }
+
+    /// Advance the next unfinished robot in round-robin order.
+    ///
+    /// Returns `None` once every robot has finished.
+    pub fn step(&mut self) -> Option<StepOutcome> {
+        let robots = self.phases.len();
+        for offset in 0..robots {
+            let robot = (self.next_robot + offset) % robots;
+            if !matches!(self.phases[robot], RobotPhase::Finished) {
+                self.next_robot = (robot + 1) % robots;
+                return Some(self.step_robot(robot));
+            }
+        }
+        None
+    }
+
+    /// Advance a specific robot by exactly one action.
+    pub fn step_robot(&mut self, robot: usize) -> StepOutcome {
+        let robot_id = robot as RobotId;
+        let phase = std::mem::replace(&mut self.phases[robot], RobotPhase::Finished);
+        let (next, outcome) = match phase {
+            RobotPhase::Idle => match self.queue.try_pop_for(robot_id) {
+                Some(task) => {
+                    let zone = task.target_zone(self.zones_total);
+                    let outcome = StepOutcome::Fetched {
+                        robot: robot_id,
+                        task: task.id,
+                        zone,
+                    };
+                    (RobotPhase::WaitingZone(task, zone), outcome)
+                }
+                // Tasks are left but none is ready: wait while another
+                // robot holds a task that may be the missing dependency.
+                None if self.queue.len() > 0 && self.others_working(robot) => {
+                    (RobotPhase::Idle, StepOutcome::Waiting { robot: robot_id })
+                }
+                None => (
+                    RobotPhase::Finished,
+                    StepOutcome::Finished { robot: robot_id },
+                ),
+            },
+            RobotPhase::WaitingZone(task, zone) => {
+                if self.zones.try_acquire(zone, robot_id) {
+                    let outcome = StepOutcome::Entered {
+                        robot: robot_id,
+                        zone,
+                    };
+                    (RobotPhase::InZone(task, zone), outcome)
+                } else {
+                    let outcome = StepOutcome::Blocked {
+                        robot: robot_id,
+                        zone,
+                    };
+                    (RobotPhase::WaitingZone(task, zone), outcome)
+                }
+            }
+            RobotPhase::InZone(task, zone) => {
+                let released = self.zones.release(zone, robot_id);
+                self.queue.ack(task.id);
+                self.queue.complete(task.id);
+                debug_assert!(released, "step release failed zone={zone} robot={robot}");
+                self.monitor.heartbeat(robot_id);
+                let outcome = StepOutcome::Released {
+                    robot: robot_id,
+                    task: task.id,
+                    zone,
+                };
+                (RobotPhase::Idle, outcome)
+            }
+            RobotPhase::Finished => (
+                RobotPhase::Finished,
+                StepOutcome::Finished { robot: robot_id },
+            ),
+        };
+        self.phases[robot] = next;
+        outcome
+    }
+
+    // Whether a robot other than `robot` is carrying a task.
+    fn others_working(&self, robot: usize) -> bool {
+        self.phases.iter().enumerate().any(|(other, phase)| {
+            other != robot && matches!(phase, RobotPhase::WaitingZone(..) | RobotPhase::InZone(..))
+        })
+    }
+
+    /// Queue another task; returns it back once `shutdown` has run.
+    pub fn submit(&self, task: Task) -> Result<(), Task> {
+        self.queue.push(task)
+    }
+
+    /// Close the queue to new tasks and wind the robots down; returns the
+    /// ids of tasks that will now never run.
+    ///
+    /// `Graceful` steps every robot until the queue is drained, so only
+    /// tasks no robot is left to take are abandoned. `Immediate` finishes
+    /// every robot on the spot, freeing held zones and abandoning both its
+    /// current task and everything still queued. The threaded bench and demo
+    /// always drain gracefully: robots run until the queue closes empty.
+    pub fn shutdown(&mut self, mode: ShutdownMode) -> Vec<TaskId> {
+        self.queue.close();
+        if mode == ShutdownMode::Graceful {
+            while self.step().is_some() {}
+        }
+        let mut abandoned = Vec::new();
+        for (robot, phase) in self.phases.iter_mut().enumerate() {
+            match std::mem::replace(phase, RobotPhase::Finished) {
+                RobotPhase::InZone(task, zone) => {
+                    self.zones.release(zone, robot as RobotId);
+                    abandoned.push(task.id);
+                }
+                RobotPhase::WaitingZone(task, _) => abandoned.push(task.id),
+                RobotPhase::Idle | RobotPhase::Finished => {}
+            }
+        }
+        while let Some(task) = self.queue.try_pop() {
+            abandoned.push(task.id);
+        }
+        abandoned
+    }
+
+    /// Zone controller shared by all simulated robots.
+    pub fn zones(&self) -> &ZoneAccess {
+        &self.zones
+    }
+
+    /// Stop a robot as if it crashed: it never acks its current task or
+    /// heartbeats again. A zone it held is freed so others can proceed.
+    pub fn halt_robot(&mut self, robot: usize) {
+        let phase = std::mem::replace(&mut self.phases[robot], RobotPhase::Finished);
+        if let RobotPhase::InZone(_, zone) = phase {
+            self.zones.release(zone, robot as RobotId);
+        }
+    }
+
+    /// Requeue unacked tasks held by robots the monitor now considers
+    /// offline; returns how many tasks were redelivered.
+    pub fn redeliver_offline(&self, timeout: Duration) -> usize {
+        self.monitor
+            .detect_offline(timeout)
+            .into_iter()
+            .map(|robot| self.queue.requeue_unacked_for(robot))
+            .sum()
+    }
+
+    /// True once every robot has observed an empty queue.
+    pub fn is_finished(&self) -> bool {
+        self.phases
+            .iter()
+            .all(|phase| matches!(phase, RobotPhase::Finished))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn explicit_task_zones_override_id_modulo() {
+        let text = "1,3,dock\n2,3,dock\n3,,default\n4,1,pick\n";
+        let tasks = crate::tasks_file::parse_tasks(text).expect("valid tasks file");
+        let expected: HashMap<TaskId, ZoneId> = tasks
+            .iter()
+            .map(|task| (task.id, task.target_zone(4)))
+            .collect();
+        assert_eq!(expected[&1], 3);
+        // No explicit zone: id 3 falls back to (3 % 4) + 1.
+        assert_eq!(expected[&3], 4);
+
+        let mut sim = Simulation::new(2, tasks, 4);
+        let mut processed = 0;
+        while let Some(outcome) = sim.step() {
+            if let StepOutcome::Released { task, zone, .. } = outcome {
+                assert_eq!(zone, expected[&task], "task {task} ran in the wrong zone");
+                processed += 1;
+            }
+        }
+        assert_eq!(processed, expected.len());
+    }
+
+    #[test]
+    fn graceful_shutdown_drains_while_immediate_abandons() {
+        let run = |mode| {
+            let tasks = (0..6).map(|id| Task::new(id, "shutdown"));
+            let mut sim = Simulation::new(2, tasks, 2);
+            let mut released = 0;
+            for _ in 0..4 {
+                if let Some(StepOutcome::Released { .. }) = sim.step() {
+                    released += 1;
+                }
+            }
+            let abandoned = sim.shutdown(mode);
+            assert!(sim.submit(Task::new(99, "late")).is_err());
+            assert!(sim.is_finished());
+            assert!(sim.zones().occupied_zones().is_empty());
+            (released, abandoned)
+        };
+
+        let (_, abandoned) = run(ShutdownMode::Graceful);
+        assert!(abandoned.is_empty(), "graceful abandoned {abandoned:?}");
+        let (released, abandoned) = run(ShutdownMode::Immediate);
+        assert!(!abandoned.is_empty());
+        // Nothing is lost: every task either ran or is reported abandoned.
+        assert_eq!(released + abandoned.len(), 6);
+    }
+
+    #[test]
+    fn stepped_dependent_runs_after_its_dependency() {
+        // B is listed first, but must wait for A to be released.
+        let tasks = vec![Task::new(2, "b").with_dependencies([1]), Task::new(1, "a")];
+        let mut sim = Simulation::new(2, tasks, 1);
+        let mut released = Vec::new();
+        let mut waited = false;
+        while let Some(outcome) = sim.step() {
+            match outcome {
+                StepOutcome::Released { task, .. } => released.push(task),
+                StepOutcome::Waiting { .. } => waited = true,
+                _ => {}
+            }
+        }
+        assert_eq!(released, vec![1, 2]);
+        assert!(waited, "robot 1 never waited on the dependency");
+        assert!(sim.is_finished());
+    }
+
+    #[test]
+    fn unacked_task_is_redelivered_after_robot_goes_offline() {
+        let mut sim = Simulation::new(2, vec![Task::new(5, "lost")], 1);
+        assert_eq!(
+            sim.step_robot(0),
+            StepOutcome::Fetched {
+                robot: 0,
+                task: 5,
+                zone: 1
+            }
+        );
+        // Robot 0 dies holding the task: no ack, no more heartbeats.
+        sim.halt_robot(0);
+        thread::sleep(Duration::from_millis(30));
+        assert_eq!(sim.redeliver_offline(Duration::from_millis(10)), 1);
+
+        assert_eq!(
+            sim.step_robot(1),
+            StepOutcome::Fetched {
+                robot: 1,
+                task: 5,
+                zone: 1
+            }
+        );
+        let mut released = Vec::new();
+        while let Some(outcome) = sim.step() {
+            if let StepOutcome::Released { robot, task, .. } = outcome {
+                released.push((robot, task));
+            }
+        }
+        assert_eq!(released, vec![(1, 5)]);
+        assert_eq!(sim.queue.in_flight_len(), 0);
+    }
+
+    #[test]
+    fn stepped_robots_respect_zone_exclusivity() {
+        // Both tasks map to the single zone, forcing contention.
+        let tasks = vec![Task::new(0, "a"), Task::new(1, "b")];
+        let mut sim = Simulation::new(2, tasks, 1);
+
+        assert!(matches!(
+            sim.step_robot(0),
+            StepOutcome::Fetched { robot: 0, .. }
+        ));
+        assert!(matches!(
+            sim.step_robot(1),
+            StepOutcome::Fetched { robot: 1, .. }
+        ));
+        assert_eq!(
+            sim.step_robot(0),
+            StepOutcome::Entered { robot: 0, zone: 1 }
+        );
+        // Robot 1 must not enter while robot 0 holds the zone.
+        assert_eq!(
+            sim.step_robot(1),
+            StepOutcome::Blocked { robot: 1, zone: 1 }
+        );
+        assert_eq!(sim.zones().occupied_zones().len(), 1);
+        assert!(matches!(
+            sim.step_robot(0),
+            StepOutcome::Released { robot: 0, .. }
+        ));
+        assert_eq!(
+            sim.step_robot(1),
+            StepOutcome::Entered { robot: 1, zone: 1 }
+        );
+
+        // Drain the rest round-robin, checking exclusivity after every step.
+        while sim.step().is_some() {
+            assert!(sim.zones().occupied_zones().len() <= 1);
+        }
+        assert!(sim.is_finished());
+        assert!(sim.zones().occupied_zones().is_empty());
+    }
+
+    #[test]
+    fn throughput_series_sums_to_completed_tasks() {
+        // 2 robots x 10 tasks at 5ms each spans several 10ms buckets.
+        let result = benchmark_once(&BenchConfig {
+            robots: 2,
+            tasks_per_robot: 10,
+            ..BenchConfig::default()
+        });
+        assert!(result.throughput_series.len() > 1);
+        let total: usize = result.throughput_series.iter().sum();
+        assert_eq!(total, result.total_tasks);
+        assert_eq!(result.throughput_series_overflow, 0);
+    }
+
+    #[test]
+    fn late_completions_overflow_instead_of_piling_into_the_last_bucket() {
+        let series = ThroughputSeries::new();
+        let span = Duration::from_millis(THROUGHPUT_BUCKET_MS * THROUGHPUT_MAX_BUCKETS as u64);
+        series.record(span - Duration::from_millis(1));
+        series.record(span);
+        series.record(span * 3);
+        let counts = series.snapshot();
+        assert_eq!(counts.len(), THROUGHPUT_MAX_BUCKETS);
+        assert_eq!(counts.last(), Some(&1));
+        assert_eq!(series.overflow(), 2);
+    }
+
+    #[test]
+    fn max_single_zone_wait_bounds_average_under_contention() {
+        // Four robots share one zone, so most acquisitions have to wait.
+        let result = benchmark_once(&BenchConfig {
+            robots: 4,
+            tasks_per_robot: 5,
+            zones_total: 1,
+            work_ms: 2,
+            ..BenchConfig::default()
+        });
+        assert!(result.max_single_zone_wait_us > 0);
+        assert!(result.max_single_zone_wait_us as f64 >= result.avg_zone_wait_us);
+    }
+
+    #[test]
+    fn huge_zone_count_uses_sparse_counters() {
+        assert!(matches!(
+            ZoneCounters::new(1_000_000, 10),
+            ZoneCounters::Sparse(_)
+        ));
+        let warning = sparse_counter_warning(1_000_000, 10).expect("warning");
+        assert!(warning.contains("global mutex"), "{warning}");
+        assert!(sparse_counter_warning(1_000_000, 2_000_000).is_none());
+        assert!(sparse_counter_warning(100, 10).is_none());
+        let result = benchmark_once(&BenchConfig {
+            robots: 2,
+            tasks_per_robot: 5,
+            zones_total: 1_000_000,
+            work_ms: 0,
+            validate: true,
+            ..BenchConfig::default()
+        });
+        assert_eq!(result.leftover, 0);
+        assert!(!result.zone_violation);
+    }
+
+    #[test]
+    fn first_violation_records_zone_robot_and_count() {
+        let start = Instant::now();
+        let metrics = ZoneMetrics::new(2, 3);
+        metrics.enter(1, 10, 2);
+        assert!(metrics.first_violation(start).is_none());
+        // A second robot in zone 1 is the first violation.
+        metrics.enter(1, 11, 2);
+        // Later violations must not overwrite the first one.
+        metrics.enter(2, 12, 2);
+        assert!(metrics.has_violation());
+        let first = metrics.first_violation(start).expect("violation recorded");
+        assert_eq!((first.zone, first.robot, first.observed), (1, 11, 2));
+        assert!(first.at_ms >= 0.0);
+    }
+
+    #[test]
+    fn violation_callback_fires_once_with_offending_zone() {
+        let metrics = ZoneMetrics::new(3, 3);
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&calls);
+        metrics.on_first_violation(move |zone, observed| {
+            sink.lock().unwrap().push((zone, observed));
+        });
+        metrics.enter(3, 1, 3);
+        assert!(calls.lock().unwrap().is_empty());
+        metrics.enter(3, 2, 3);
+        metrics.enter(3, 4, 3);
+        assert_eq!(*calls.lock().unwrap(), vec![(3, 2)]);
+    }
+
+    #[test]
+    fn barrier_start_lets_every_robot_begin_together() {
+        let path = std::env::temp_dir().join(format!("blaze-barrier-{}.jsonl", std::process::id()));
+        let sink = EventSink::open(path.to_str().expect("utf-8 temp path")).expect("sink");
+        let robots = 8;
+        benchmark_once(&BenchConfig {
+            robots,
+            tasks_per_robot: 3,
+            zones_total: 8,
+            work_ms: 1,
+            events: Some(Arc::new(sink)),
+            ..BenchConfig::default()
+        });
+        let text = std::fs::read_to_string(&path).expect("events file");
+        let _ = std::fs::remove_file(&path);
+
+        let mut first_pop_us: HashMap<u64, u64> = HashMap::new();
+        for line in text.lines() {
+            let event: serde_json::Value = serde_json::from_str(line).expect("valid JSON");
+            if event["type"] == "popped" {
+                let robot = event["robot"].as_u64().expect("robot");
+                let t_us = event["t_us"].as_u64().expect("t_us");
+                first_pop_us.entry(robot).or_insert(t_us);
+            }
+        }
+        assert_eq!(first_pop_us.len(), robots);
+        // Spawn order no longer matters: nobody starts more than a moment late.
+        let latest = first_pop_us.values().copied().max().expect("pops");
+        assert!(latest < 20_000, "a robot started {latest}us into the run");
+    }
+
+    #[test]
+    fn start_gate_balances_a_shared_drain() {
+        // Robots draining one queue, spawned 2ms apart to exaggerate spawn
+        // cost. Without the gate the first robot works alone for ~14ms.
+        let drain = |gated: bool| {
+            let robots = 8;
+            let queue = Arc::new(TaskQueue::new());
+            let tasks = (0..400).map(|id| Task::new(id, "drain")).collect();
+            queue.push_many(tasks).expect("task queue closed");
+            let gate = Arc::new(StartGate::new());
+            let handles: Vec<_> = (0..robots)
+                .map(|_| {
+                    let queue = Arc::clone(&queue);
+                    let gate = Arc::clone(&gate);
+                    let handle = thread::spawn(move || {
+                        if gated {
+                            gate.arrive();
+                        }
+                        let mut done = 0usize;
+                        while queue.try_pop().is_some() {
+                            thread::sleep(Duration::from_micros(200));
+                            done += 1;
+                        }
+                        done
+                    });
+                    thread::sleep(Duration::from_millis(2));
+                    handle
+                })
+                .collect();
+            if gated {
+                gate.wait_for(robots);
+                gate.open(Instant::now());
+            }
+            let counts: Vec<usize> = handles
+                .into_iter()
+                .map(|handle| handle.join().expect("robot panicked"))
+                .collect();
+            assert_eq!(counts.iter().sum::<usize>(), 400);
+            counts
+        };
+        let spread = |counts: &[usize]| counts.iter().max().unwrap() - counts.iter().min().unwrap();
+        let gated = drain(true);
+        let ungated = drain(false);
+        assert!(
+            spread(&gated) < spread(&ungated),
+            "gated {gated:?} vs ungated {ungated:?}"
+        );
+        assert!(
+            jain_index(&gated) > jain_index(&ungated),
+            "gated {gated:?} vs ungated {ungated:?}"
+        );
+    }
+
+    #[test]
+    fn straggler_is_the_slowest_robot() {
+        // Robot 2 does far longer work than the others.
+        let finish_ms = [40.0, 42.0, 95.0, 41.0];
+        let (robot, lag_ms) = straggler(&finish_ms).expect("robots");
+        assert_eq!(robot, 2);
+        assert!((lag_ms - 53.0).abs() < 1e-9, "lag {lag_ms}");
+        assert!(straggler(&[]).is_none());
+
+        let result = benchmark_once(&BenchConfig {
+            robots: 3,
+            tasks_per_robot: 2,
+            work_ms: 0,
+            ..BenchConfig::default()
+        });
+        assert!(result.straggler_robot.is_some_and(|robot| robot < 3));
+        assert!(result.straggler_lag_ms >= 0.0);
+    }
+
+    #[test]
+    fn fixed_tasks_give_equal_totals_the_same_prefix() {
+        let canonical = Arc::new(bench_tasks(40, Some(&[3, 1]), 9, None));
+        let run = |robots, tasks_per_robot| {
+            benchmark_once(&BenchConfig {
+                robots,
+                tasks_per_robot,
+                zones_total: 2,
+                work_ms: 0,
+                zone_weights: Some(vec![3, 1]),
+                fixed_tasks: Some(Arc::clone(&canonical)),
+                ..BenchConfig::default()
+            })
+        };
+        let prefix_checksum = canonical[..20]
+            .iter()
+            .fold(0u64, |sum, task| sum.wrapping_add(task_id_hash(task.id)));
+
+        let narrow = run(2, 10);
+        let wide = run(4, 5);
+        assert_eq!((narrow.total_tasks, narrow.leftover), (20, 0));
+        assert_eq!(narrow.task_checksum, prefix_checksum);
+        assert_eq!(wide.task_checksum, prefix_checksum);
+        assert_ne!(run(4, 10).task_checksum, prefix_checksum);
+    }
+
+    #[test]
+    fn dependent_task_is_never_popped_before_its_dependency_is_released() {
+        let path = std::env::temp_dir().join(format!("blaze-deps-{}.jsonl", std::process::id()));
+        let sink = EventSink::open(path.to_str().expect("utf-8 temp path")).expect("sink");
+        // B sits ahead of A, so an idle second robot would grab it first.
+        let tasks = vec![
+            Task::new(2, "b").with_dependencies([1]).with_zone(2),
+            Task::new(1, "a").with_zone(1),
+        ];
+        let result = benchmark_once(&BenchConfig {
+            robots: 2,
+            tasks_per_robot: 1,
+            zones_total: 2,
+            work_ms: 10,
+            fixed_tasks: Some(Arc::new(tasks)),
+            events: Some(Arc::new(sink)),
+            ..BenchConfig::default()
+        });
+        let text = std::fs::read_to_string(&path).expect("events file");
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(result.leftover, 0);
+
+        let at = |kind: &str, task: u64| {
+            text.lines()
+                .map(|line| serde_json::from_str::<serde_json::Value>(line).expect("valid JSON"))
+                .find(|event| event["type"] == kind && event["task"] == task)
+                .and_then(|event| event["t_us"].as_u64())
+                .unwrap_or_else(|| panic!("no {kind} event for task {task}"))
+        };
+        assert!(at("popped", 2) >= at("released", 1));
+    }
+
+    #[test]
+    fn truncated_fixed_tasks_report_the_missing_dependency() {
+        // Only the first task fits the budget; its dependency is cut off.
+        let tasks = vec![Task::new(2, "b").with_dependencies([1]), Task::new(1, "a")];
+        for deterministic in [false, true] {
+            let config = BenchConfig {
+                robots: 1,
+                tasks_per_robot: 1,
+                fixed_tasks: Some(Arc::new(tasks.clone())),
+                deterministic,
+                ..BenchConfig::default()
+            };
+            let err = try_benchmark_run(&config, Arc::new(AtomicBool::new(false)))
+                .err()
+                .expect("run should not start");
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+            assert_eq!(err.to_string(), "task 2 depends on unknown task 1");
+        }
+    }
+
+    #[test]
+    fn fixed_task_outside_the_zone_range_is_rejected() {
+        let tasks = vec![Task::new(0, "a").with_zone(9)];
+        for deterministic in [false, true] {
+            let config = BenchConfig {
+                robots: 1,
+                tasks_per_robot: 1,
+                zones_total: 4,
+                fixed_tasks: Some(Arc::new(tasks.clone())),
+                deterministic,
+                ..BenchConfig::default()
+            };
+            let err = try_benchmark_run(&config, Arc::new(AtomicBool::new(false)))
+                .err()
+                .expect("run should not start");
+            assert_eq!(err.to_string(), "task 0 targets zone 9 outside 1..=4");
+        }
+    }
+
+    #[test]
+    fn late_robot_takes_work_and_is_tracked() {
+        let result = benchmark_once(&BenchConfig {
+            robots: 2,
+            tasks_per_robot: 20,
+            zones_total: 4,
+            work_ms: 5,
+            add_robot_after_ms: Some(20),
+            validate: true,
+            ..BenchConfig::default()
+        });
+        assert!(result.late_robot_tasks.is_some_and(|tasks| tasks > 0));
+        assert_eq!(result.tracked_robots, 3);
+        assert_eq!(result.leftover, 0);
+        assert!(!result.duplicate_tasks);
+    }
+
+    #[test]
+    fn first_tasks_are_distinct_pushed_ids() {
+        let robots = 4;
+        let result = benchmark_once(&BenchConfig {
+            robots,
+            tasks_per_robot: 5,
+            work_ms: 0,
+            ..BenchConfig::default()
+        });
+        let firsts: Vec<TaskId> = result
+            .first_task_per_robot
+            .iter()
+            .map(|task| task.expect("every robot popped a task"))
+            .collect();
+        assert_eq!(firsts.len(), robots);
+        let distinct: HashSet<TaskId> = firsts.iter().copied().collect();
+        assert_eq!(distinct.len(), robots);
+        assert!(firsts.iter().all(|&id| id < result.total_tasks as TaskId));
+    }
+
+    #[test]
+    fn travel_time_spreads_robots_out() {
+        let run = |travel_ms| {
+            benchmark_once(&BenchConfig {
+                robots: 4,
+                tasks_per_robot: 8,
+                zones_total: 1,
+                work_ms: 2,
+                travel_ms,
+                ..BenchConfig::default()
+            })
+        };
+        let direct = run(0);
+        // Long enough to outlast the monitor shutdown folded into elapsed_ms.
+        let travelling = run(25);
+        assert!(
+            travelling.elapsed_ms > direct.elapsed_ms,
+            "travel {} vs direct {}",
+            travelling.elapsed_ms,
+            direct.elapsed_ms
+        );
+        // Robots in transit are not queued on the single zone.
+        assert!(
+            travelling.avg_zone_wait_us < direct.avg_zone_wait_us,
+            "travel {} vs direct {}",
+            travelling.avg_zone_wait_us,
+            direct.avg_zone_wait_us
+        );
+    }
+
+    #[test]
+    fn explicit_zone_trips_are_not_charged_twice() {
+        // One robot, five tasks, all in zone 1: zoned tasks travel no
+        // distance, unzoned ones pay a flat 100ms for each of the four trips.
+        let run = |zoned: bool| {
+            let tasks = (0..5)
+                .map(|id| {
+                    let task = Task::new(id, format!("trip-{id}"));
+                    if zoned { task.with_zone(1) } else { task }
+                })
+                .collect();
+            benchmark_once(&BenchConfig {
+                robots: 1,
+                tasks_per_robot: 5,
+                zones_total: 1,
+                work_ms: 0,
+                travel_ms: 100,
+                fixed_tasks: Some(Arc::new(tasks)),
+                ..BenchConfig::default()
+            })
+        };
+        let flat = run(false);
+        let zoned = run(true);
+        assert!(
+            zoned.elapsed_ms + 200.0 < flat.elapsed_ms,
+            "zoned {} vs flat {}",
+            zoned.elapsed_ms,
+            flat.elapsed_ms
+        );
+    }
+
+    #[test]
+    fn sim_status_assembles_every_component() {
+        let queue = TaskQueue::new();
+        let zones = ZoneAccess::new();
+        let monitor = HealthMonitor::new();
+        queue
+            .push_many((0..5).map(|id| Task::new(id, "status")).collect())
+            .expect("task queue closed");
+        queue.try_pop().expect("task");
+        queue.try_pop().expect("task");
+        zones.acquire(3, 0);
+        zones.acquire(1, 1);
+        monitor.register_robots(0..3);
+        monitor.detect_offline(Duration::from_secs(60));
+        // Robot 2 stays silent well past the timeout; the others just spoke.
+        thread::sleep(Duration::from_millis(150));
+        monitor.heartbeat(0);
+        monitor.heartbeat(1);
+        monitor.detect_offline(Duration::from_millis(100));
+
+        assert_eq!(
+            sim_status(&queue, &zones, &monitor),
+            SimStatus {
+                queue_len: 3,
+                queue_pushed: 5,
+                queue_popped: 2,
+                occupied_zones: vec![1, 3],
+                offline_robots: vec![2],
+                online_robots: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn chaos_faults_keep_invariants() {
+        let result = benchmark_once(&BenchConfig {
+            robots: 3,
+            tasks_per_robot: 10,
+            zones_total: 2,
+            work_ms: 0,
+            chaos: Some(1),
+            ..BenchConfig::default()
+        });
+        // Fault draws are per robot and per task, so the seed fixes them.
+        assert_eq!(result.chaos_heartbeat_delays, 1);
+        assert!(result.chaos_slow_releases > 0);
+        assert_eq!(result.recovered_count, 1);
+        assert!(!result.zone_violation);
+        assert!(!result.duplicate_tasks);
+        assert_eq!(result.leftover, 0);
+    }
+
+    #[test]
+    fn affinity_cuts_the_worst_zone_wait() {
+        // Tasks grouped by zone: without affinity every robot piles onto
+        // zone 1, then zone 2, and so on.
+        let tasks: Vec<Task> = (0..40)
+            .map(|id| Task::new(id, "grouped").with_zone(id / 10 + 1))
+            .collect();
+        let run = |affinity| {
+            benchmark_once(&BenchConfig {
+                robots: 4,
+                tasks_per_robot: 10,
+                zones_total: 4,
+                work_ms: 5,
+                fixed_tasks: Some(Arc::new(tasks.clone())),
+                affinity,
+                ..BenchConfig::default()
+            })
+        };
+        let baseline = run(false);
+        let affine = run(true);
+        assert_eq!(affine.leftover, 0);
+        assert!(
+            affine.max_single_zone_wait_us < baseline.max_single_zone_wait_us,
+            "affinity {}us vs baseline {}us",
+            affine.max_single_zone_wait_us,
+            baseline.max_single_zone_wait_us
+        );
+    }
+
+    #[test]
+    fn zone_rates_sum_to_overall_throughput() {
+        // Ids 0..12 over three zones: four tasks land in each zone.
+        let result = benchmark_once(&BenchConfig {
+            robots: 2,
+            tasks_per_robot: 6,
+            zones_total: 3,
+            work_ms: 2,
+            ..BenchConfig::default()
+        });
+        let zones: Vec<ZoneId> = result.zone_rate.iter().map(|&(zone, _)| zone).collect();
+        assert_eq!(zones, vec![1, 2, 3]);
+        let rates: Vec<f64> = result.zone_rate.iter().map(|&(_, rate)| rate).collect();
+        assert!(rates.iter().all(|&rate| (rate - rates[0]).abs() < 1e-9));
+        let total: f64 = rates.iter().sum();
+        assert!(
+            (total - result.throughput).abs() <= result.throughput * 0.01,
+            "zone rates {total} vs throughput {}",
+            result.throughput
+        );
+    }
+
+    #[test]
+    fn per_zone_work_sets_each_zone_hold_time() {
+        let result = benchmark_once(&BenchConfig {
+            robots: 2,
+            tasks_per_robot: 6,
+            zones_total: 3,
+            // Zone 3 cycles back to the first entry.
+            work_ms_per_zone: Some(vec![2, 20]),
+            ..BenchConfig::default()
+        });
+        let holds: HashMap<ZoneId, f64> = result.zone_avg_hold_ms.iter().copied().collect();
+        assert_eq!(holds.len(), 3);
+        assert!(holds[&1] >= 2.0 && holds[&1] < 15.0, "zone 1 {}", holds[&1]);
+        assert!(holds[&2] >= 20.0, "zone 2 {}", holds[&2]);
+        assert!(holds[&3] < holds[&2], "zone 3 {}", holds[&3]);
+    }
+
+    #[test]
+    fn deterministic_runs_repeat_exactly() {
+        let config = BenchConfig {
+            robots: 3,
+            tasks_per_robot: 4,
+            zones_total: 2,
+            work_ms: 0,
+            deterministic: true,
+            ..BenchConfig::default()
+        };
+        let first = benchmark_once(&config);
+        let second = benchmark_once(&config);
+        assert_eq!(first.per_robot_completed, vec![4, 4, 4]);
+        assert_eq!(first.per_robot_completed, second.per_robot_completed);
+        assert_eq!(first.task_checksum, second.task_checksum);
+        // Round-robin over a FIFO queue: robot r opens with task r.
+        assert_eq!(first.first_task_per_robot, vec![Some(0), Some(1), Some(2)]);
+        assert_eq!(first.first_task_per_robot, second.first_task_per_robot);
+        assert!(!first.zone_violation);
+        assert_eq!(first.leftover, 0);
+    }
+
+    #[test]
+    fn balanced_fifo_run_scores_near_perfect_fairness() {
+        assert_eq!(jain_index(&[5, 5, 5, 5]), Some(1.0));
+        assert_eq!(jain_index(&[8, 0, 0, 0]), Some(0.25));
+        assert_eq!(jain_index(&[0, 0]), None);
+
+        let result = benchmark_once(&BenchConfig {
+            robots: 4,
+            tasks_per_robot: 10,
+            zones_total: 4,
+            work_ms: 2,
+            ..BenchConfig::default()
+        });
+        let index = jain_index(&result.per_robot_completed).expect("completed tasks");
+        assert!(
+            index > 0.95,
+            "index {index} for {:?}",
+            result.per_robot_completed
+        );
+    }
+
+    #[test]
+    fn fast_zone_waits_accumulate_in_nanoseconds() {
+        // Per-wait microsecond truncation turns each 400ns wait into 0.
+        assert_eq!(avg_wait_us(1000 * 400, 1000), 0.4);
+        assert_eq!(avg_wait_us(0, 0), 0.0);
+
+        let zones = ZoneAccess::new();
+        let (mut total_ns, mut truncated_us) = (0u64, 0u64);
+        for _ in 0..1000 {
+            let wait_start = Instant::now();
+            zones.acquire(1, 0);
+            let waited = wait_start.elapsed();
+            zones.release(1, 0);
+            total_ns += waited.as_nanos() as u64;
+            truncated_us += waited.as_micros() as u64;
+        }
+        assert!(total_ns > 0);
+        assert!(avg_wait_us(total_ns, 1000) > 0.0);
+        // Uncontended acquires are far below a microsecond each.
+        assert!(
+            truncated_us * 1000 < total_ns,
+            "{truncated_us}us vs {total_ns}ns"
+        );
+    }
+
+    #[test]
+    fn startup_waits_are_bucketed_apart_from_steady_state() {
+        // A cold-start pile-up followed by quiet steady-state handoffs.
+        let waits = [(0, 900), (50, 700), (99, 500), (100, 20), (400, 40)];
+        assert_eq!(phase_avg_wait_us(&waits, 100), (700.0, 30.0));
+        assert_eq!(phase_avg_wait_us(&[], 100), (0.0, 0.0));
+
+        // Sixteen robots rushing one zone all start waiting at the gate.
+        let result = benchmark_once(&BenchConfig {
+            robots: 16,
+            tasks_per_robot: 2,
+            zones_total: 1,
+            work_ms: 2,
+            ..BenchConfig::default()
+        });
+        assert!(result.startup_avg_wait_us > 0.0);
+        assert!(result.steady_avg_wait_us > 0.0);
+        assert_ne!(result.startup_avg_wait_us, result.steady_avg_wait_us);
+    }
+
+    #[test]
+    fn transient_failure_is_retried_until_the_run_starts() {
+        let config = BenchConfig {
+            robots: 2,
+            tasks_per_robot: 3,
+            work_ms: 0,
+            ..BenchConfig::default()
+        };
+        let mut calls = 0;
+        let mut flaky = || {
+            calls += 1;
+            if calls == 1 {
+                Err(io::Error::new(io::ErrorKind::WouldBlock, "no threads left"))
+            } else {
+                try_benchmark_run(&config, Arc::new(AtomicBool::new(false)))
+            }
+        };
+        let (outcome, attempts) = with_retries(2, &mut flaky);
+        assert_eq!(attempts, 2);
+        assert_eq!(outcome.expect("second attempt runs").leftover, 0);
+
+        // Without retries the first failure is final.
+        let (outcome, attempts) = with_retries(0, || -> io::Result<()> {
+            Err(io::Error::other("no threads left"))
+        });
+        assert_eq!(attempts, 1);
+        assert!(outcome.is_err());
+    }
+
+    #[test]
+    fn speedup_ratio_guards_a_zero_baseline() {
+        assert_eq!(speedup_ratio(250.0, 250.0), Some(1.0));
+        assert_eq!(speedup_ratio(500.0, 250.0), Some(2.0));
+        assert_eq!(speedup_ratio(500.0, 0.0), None);
+    }
+
+    #[test]
+    fn completion_stddev_grows_with_imbalance() {
+        assert!(stddev_ms(&[50.0, 50.0, 50.0, 50.0]) < 1e-9);
+        // One robot carrying extra work pulls the spread well above zero.
+        assert!(stddev_ms(&[50.0, 50.0, 50.0, 150.0]) > 40.0);
+        assert_eq!(stddev_ms(&[]), 0.0);
+
+        // With affinity robot `i` works only zone `i + 1`'s three tasks, so
+        // per-zone work times pin the extra work on robot 0.
+        let run = |zone_work: Vec<u64>| {
+            let tasks = (0..12)
+                .map(|id| Task::new(id, format!("task-{id}")).with_zone(id % 4 + 1))
+                .collect();
+            benchmark_once(&BenchConfig {
+                robots: 4,
+                tasks_per_robot: 3,
+                zones_total: 4,
+                affinity: true,
+                fixed_tasks: Some(Arc::new(tasks)),
+                work_ms_per_zone: Some(zone_work),
+                ..BenchConfig::default()
+            })
+        };
+        let balanced = run(vec![5, 5, 5, 5]);
+        let skewed = run(vec![40, 5, 5, 5]);
+        assert_eq!(skewed.per_robot_completed, vec![3, 3, 3, 3]);
+        // Robot 0 finishes ~105ms after the rest: a stddev near 45ms.
+        assert!(
+            skewed.completion_time_stddev_ms > balanced.completion_time_stddev_ms + 25.0,
+            "skewed {} vs balanced {}",
+            skewed.completion_time_stddev_ms,
+            balanced.completion_time_stddev_ms
+        );
+    }
+
+    #[test]
+    fn zone_utilization_tracks_contention() {
+        // Long holds keep handoff latency small next to the time held.
+        let busy = benchmark_once(&BenchConfig {
+            robots: 4,
+            tasks_per_robot: 3,
+            zones_total: 1,
+            work_ms: 20,
+            ..BenchConfig::default()
+        });
+        assert_eq!(busy.zone_utilization_pct.len(), 1);
+        let (zone, pct) = busy.zone_utilization_pct[0];
+        assert_eq!(zone, 1);
+        assert!(pct > 75.0 && pct <= 100.0, "single zone {pct}%");
+
+        let light = benchmark_once(&BenchConfig {
+            robots: 2,
+            tasks_per_robot: 32,
+            zones_total: 32,
+            work_ms: 1,
+            ..BenchConfig::default()
+        });
+        assert_eq!(light.zone_utilization_pct.len(), 32);
+        for (zone, pct) in &light.zone_utilization_pct {
+            assert!(*pct < 25.0, "zone {zone} at {pct}%");
+        }
+    }
+
+    #[test]
+    fn refused_robot_threads_still_complete_the_run() {
+        let result = benchmark_once(&BenchConfig {
+            robots: 6,
+            tasks_per_robot: 3,
+            work_ms: 1,
+            validate: true,
+            thread_limit: Some(2),
+            ..BenchConfig::default()
+        });
+        assert_eq!(result.total_tasks, 18);
+        assert_eq!(result.leftover, 0);
+        assert!(!result.zone_violation);
+        assert!(!result.duplicate_tasks);
+    }
+
+    #[test]
+    fn release_retry_frees_zone_after_transient_failure() {
+        let zones = ZoneAccess::new();
+        zones.acquire(1, 7);
+        let mut attempts = 0;
+        // First attempt fails spuriously; the retry must still free the zone.
+        let released = release_with_retry(&zones, 1, 7, || {
+            attempts += 1;
+            attempts > 1 && zones.release(1, 7)
+        });
+        assert!(released);
+        assert_eq!(attempts, 2);
+        assert_eq!(zones.owner(1), None);
+    }
+
+    #[test]
+    fn every_processed_task_reports_an_outcome() {
+        let (sender, receiver) = mpsc::channel();
+        let result = benchmark_once(&BenchConfig {
+            robots: 3,
+            tasks_per_robot: 10,
+            work_ms: 0,
+            fail_rate: Some(0.5),
+            outcomes: Some(sender),
+            ..BenchConfig::default()
+        });
+        let outcomes: Vec<TaskOutcome> = receiver.try_iter().collect();
+        assert_eq!(outcomes.len(), 30);
+        let failed = outcomes.iter().filter(|outcome| !outcome.success).count();
+        assert_eq!(failed, result.failed_tasks);
+        let ids: HashSet<TaskId> = outcomes.iter().map(|outcome| outcome.task_id).collect();
+        assert_eq!(ids.len(), 30);
+        assert!(outcomes.iter().all(|outcome| outcome.robot < 3));
+    }
+
+    #[test]
+    fn detection_latency_lands_within_one_poll_of_the_timeout() {
+        let (timeout, poll) = (Duration::from_millis(100), Duration::from_millis(5));
+        let report = detection_latency(200, timeout, poll, 3);
+        assert_eq!(report.trials, 200);
+        // Flagged only once strictly overdue, and by the next poll after that.
+        assert!(report.mean_ms >= 100.0, "mean {}", report.mean_ms);
+        assert!(report.mean_ms <= 105.0 + 1.0, "mean {}", report.mean_ms);
+        assert!(report.mean_ms <= report.p95_ms && report.p95_ms <= report.max_ms);
+        assert!(report.max_ms <= 105.0);
+    }
+
+    #[test]
+    fn rng_log_records_every_zone_pick_and_failure_draw() {
+        let path = std::env::temp_dir().join(format!("blaze-rng-{}.csv", std::process::id()));
+        let log = Arc::new(RngLog::create(&path).expect("rng log"));
+        benchmark_once(&BenchConfig {
+            robots: 2,
+            tasks_per_robot: 5,
+            zones_total: 3,
+            work_ms: 0,
+            zone_weights: Some(vec![1, 2, 1]),
+            fail_rate: Some(0.5),
+            rng_log: Some(log),
+            ..BenchConfig::default()
+        });
+        let text = std::fs::read_to_string(&path).expect("rng log file");
+        let _ = std::fs::remove_file(&path);
+
+        let mut lines = text.lines();
+        assert_eq!(lines.next(), Some("seq,context,value"));
+        let contexts: Vec<&str> = lines
+            .map(|line| line.split(',').nth(1).expect("context"))
+            .collect();
+        // One zone pick per task, then one failure draw per processed task.
+        assert_eq!(contexts.iter().filter(|&&c| c == "zone_pick").count(), 10);
+        assert_eq!(
+            contexts.iter().filter(|c| c.starts_with("fail:")).count(),
+            10
+        );
+        assert_eq!(contexts.len(), 20);
+    }
+
+    #[test]
+    fn canceled_run_returns_partial_result() {
+        // Long enough (~2.5s uncanceled) that cancel always lands mid-run.
+        let handle = spawn_benchmark(BenchConfig {
+            robots: 2,
+            tasks_per_robot: 500,
+            ..BenchConfig::default()
+        });
+        thread::sleep(Duration::from_millis(50));
+        handle.cancel();
+        let result = handle.join();
+        assert!(result.canceled);
+        assert!(result.leftover > 0);
+        assert!(result.leftover < result.total_tasks);
+        assert!(!result.zone_violation);
+    }
+
+    #[test]
+    fn bench_consumes_exactly_the_tasks_a_source_yields() {
+        // A user-supplied source handing out a fixed sequence.
+        struct Fixed(Mutex<Vec<Task>>);
+        impl TaskSource for Fixed {
+            fn next(&self) -> Option<Task> {
+                self.0.lock().unwrap().pop()
+            }
+        }
+        let ids: [TaskId; 5] = [42, 7, 1000, 3, 19];
+        let tasks = ids.iter().rev().map(|&id| Task::new(id, "fixed")).collect();
+        let source = Arc::new(Fixed(Mutex::new(tasks)));
+        // Quota exceeds the source, so robots stop when it runs dry.
+        let result = benchmark_once(&BenchConfig {
+            robots: 3,
+            tasks_per_robot: 4,
+            zones_total: 2,
+            work_ms: 0,
+            validate: true,
+            task_source: Some(source.clone()),
+            ..BenchConfig::default()
+        });
+        let expected = ids
+            .iter()
+            .fold(0u64, |sum, &id| sum.wrapping_add(task_id_hash(id)));
+        assert_eq!(result.task_checksum, expected);
+        assert_eq!((result.total_tasks, result.leftover), (5, 0));
+        assert_eq!(result.per_robot_completed.iter().sum::<usize>(), 5);
+        assert!(!result.duplicate_tasks);
+        assert!(source.next().is_none());
+
+        // An endless stream is bounded by each robot's share.
+        let endless = SyntheticTasks::new(usize::MAX, None, SplitMix64::new(1));
+        let result = benchmark_once(&BenchConfig {
+            robots: 2,
+            tasks_per_robot: 3,
+            work_ms: 0,
+            task_source: Some(Arc::new(endless)),
+            ..BenchConfig::default()
+        });
+        assert_eq!(result.per_robot_completed, vec![3, 3]);
+        assert_eq!(result.total_tasks, 6);
+    }
+
+    #[test]
+    fn strict_leftover_reports_stranded_tasks_as_an_error() {
+        let handle = spawn_benchmark(BenchConfig {
+            robots: 2,
+            tasks_per_robot: 500,
+            strict_leftover: true,
+            ..BenchConfig::default()
+        });
+        thread::sleep(Duration::from_millis(50));
+        handle.cancel();
+        let result = handle.join();
+        assert!(result.leftover > 0);
+        assert_eq!(
+            leftover_line(result.leftover, true),
+            format!("# error,leftover,{}", result.leftover)
+        );
+        assert_eq!(leftover_line(3, false), "# warning,leftover_tasks,3");
+        assert_eq!(bench_exit_status(Some(&result), true, None), 1);
+        assert_eq!(bench_exit_status(Some(&result), false, None), 0);
+    }
+
+    #[test]
+    fn bench_exit_status_gates_on_throughput_floor() {
+        let result = benchmark_once(&BenchConfig {
+            robots: 2,
+            tasks_per_robot: 3,
+            work_ms: 0,
+            ..BenchConfig::default()
+        });
+        assert_eq!(result.leftover, 0);
+        assert_eq!(bench_exit_status(Some(&result), true, None), 0);
+        assert_eq!(bench_exit_status(Some(&result), false, Some(0.0)), 0);
+        assert_eq!(bench_exit_status(Some(&result), false, Some(f64::MAX)), 1);
+        // No run: only a floor turns that into a failure.
+        assert_eq!(bench_exit_status(None, true, None), 0);
+        assert_eq!(bench_exit_status(None, false, Some(1.0)), 1);
+    }
+
+    #[test]
+    fn leftover_is_expected_only_when_an_offline_robot_explains_it() {
+        assert!(leftover_is_expected(0, None));
+        assert!(leftover_is_expected(3, Some(5)));
+        assert!(!leftover_is_expected(6, Some(5)));
+        assert!(!leftover_is_expected(3, None));
+
+        let offline = benchmark_once(&BenchConfig {
+            robots: 2,
+            tasks_per_robot: 4,
+            work_ms: 0,
+            simulate_offline: true,
+            ..BenchConfig::default()
+        });
+        // The silent robot only stops heartbeating; it still finishes its share.
+        assert_eq!(offline.per_robot_completed, vec![4, 4]);
+        assert_eq!(offline.leftover, 0);
+        assert!(offline.leftover_expected);
+
+        // Outside offline mode any stranded task is flagged.
+        let handle = spawn_benchmark(BenchConfig {
+            robots: 2,
+            tasks_per_robot: 500,
+            ..BenchConfig::default()
+        });
+        thread::sleep(Duration::from_millis(50));
+        handle.cancel();
+        let normal = handle.join();
+        assert!(normal.leftover > 0);
+        assert!(!normal.leftover_expected);
+    }
+
+    #[test]
+    fn leftover_is_counted_without_draining_by_default() {
+        let config = BenchConfig {
+            robots: 2,
+            tasks_per_robot: 50_000,
+            ..BenchConfig::default()
+        };
+        for validate in [false, true] {
+            let handle = spawn_benchmark(BenchConfig {
+                validate,
+                ..config.clone()
+            });
+            thread::sleep(Duration::from_millis(30));
+            handle.cancel();
+            let result = handle.join();
+            // Robots check for cancel before popping, so every popped task completed.
+            let completed: usize = result.throughput_series.iter().sum();
+            assert_eq!(result.leftover + completed, result.total_tasks);
+            if validate {
+                assert_eq!(result.leftover_ids.len(), result.leftover);
+            } else {
+                assert!(
+                    result.leftover_ids.is_empty(),
+                    "default mode drained the queue"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn offline_robot_recovers_within_one_run() {
+        // Robot 0 goes silent after 50 tasks for 45 tasks (~675ms), longer
+        // than the 500ms bench timeout plus the 100ms monitor poll.
+        let result = benchmark_once(&BenchConfig {
+            robots: 2,
+            tasks_per_robot: 100,
+            work_ms: 15,
+            simulate_offline: true,
+            offline_recover_after: Some(45),
+            ..BenchConfig::default()
+        });
+        assert!(result.recovered_count >= 1);
+        assert_eq!(result.offline_count, 0);
+    }
+
+    #[test]
+    fn zone_timeouts_requeue_tasks_until_all_complete() {
+        // One zone, four robots, and a timeout far below the 10ms hold time.
+        let result = benchmark_once(&BenchConfig {
+            robots: 4,
+            tasks_per_robot: 5,
+            zones_total: 1,
+            work_ms: 10,
+            validate: true,
+            zone_acquire_timeout_ms: Some(2),
+            ..BenchConfig::default()
+        });
+        assert!(result.requeued_tasks > 0);
+        assert_eq!(result.leftover, 0);
+        assert_eq!(result.throughput_series.iter().sum::<usize>(), 20);
+        assert!(!result.duplicate_tasks);
+        assert!(!result.zone_violation);
+    }
+
+    #[test]
+    fn contention_only_reports_rate_and_blocking() {
+        let zones = Arc::new(ZoneAccess::new());
+        // Hold the zone as an outside robot so every contender's first try blocks.
+        let holder = RobotId::MAX;
+        zones.acquire(CONTENTION_ZONE, holder);
+        let releaser = {
+            let zones = Arc::clone(&zones);
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(50));
+                assert!(zones.release(CONTENTION_ZONE, holder));
+            })
+        };
+        let result = contention_run(zones, 4, 500);
+        releaser.join().expect("releaser panicked");
+        assert_eq!(result.acquisitions, 2000);
+        assert!(result.acquisitions_per_s > 0.0);
+        assert!(result.blocked >= 4, "blocked={}", result.blocked);
+    }
+
+    #[test]
+    fn stress_configs_parse_tuples_and_reject_bad_lines() {
+        let points = parse_stress_configs("# r,t,z\n2,3,1,0\n\n4, 5, 2\n").expect("valid");
+        assert_eq!(
+            points,
+            vec![
+                StressPoint {
+                    robots: 2,
+                    tasks_per_robot: 3,
+                    zones_total: 1,
+                    work_ms: Some(0),
+                },
+                StressPoint {
+                    robots: 4,
+                    tasks_per_robot: 5,
+                    zones_total: 2,
+                    work_ms: None,
+                },
+            ]
+        );
+        let err = parse_stress_configs("1,1,1\n1,0,1\n").unwrap_err();
+        assert!(err.starts_with("line 2: invalid tasks"), "{err}");
+        assert!(parse_stress_configs("1,1\n").is_err());
+        assert!(parse_stress_configs("# nothing\n").is_err());
+    }
+
+    #[test]
+    fn zone_weights_skew_task_assignment() {
+        let zones = weighted_zones(&[20, 1, 1, 1], 1000, SplitMix64::new(7));
+        let zone_one = zones.iter().filter(|&&zone| zone == 1).count();
+        // Expected share is 20/23 (about 87%); allow generous sampling slack.
+        assert!(zone_one > 750, "zone 1 got {zone_one}/1000");
+        assert!(zones.iter().all(|&zone| (1..=4).contains(&zone)));
+        assert_eq!(
+            zones,
+            weighted_zones(&[20, 1, 1, 1], 1000, SplitMix64::new(7))
+        );
+        // A zero weight never receives tasks.
+        assert!(!weighted_zones(&[1, 0, 1], 200, SplitMix64::new(7)).contains(&2));
+
+        let result = benchmark_once(&BenchConfig {
+            robots: 2,
+            tasks_per_robot: 10,
+            zones_total: 4,
+            work_ms: 0,
+            validate: true,
+            zone_weights: Some(vec![20, 1, 1, 1]),
+            ..BenchConfig::default()
+        });
+        assert_eq!(result.leftover, 0);
+        assert!(!result.zone_violation);
+        assert_eq!(
+            zone_weights_error(Some(&[1, 2]), 3).as_deref(),
+            Some("--zone-weights lists 2 weights but zones=3")
+        );
+    }
+
+    #[test]
+    fn max_rate_caps_per_robot_consumption() {
+        let tasks_per_robot = 20;
+        let result = benchmark_once(&BenchConfig {
+            robots: 2,
+            tasks_per_robot,
+            zones_total: 2,
+            work_ms: 0,
+            max_rate: Some(100.0),
+            ..BenchConfig::default()
+        });
+        assert_eq!(result.leftover, 0);
+        // The first task is free; the other 19 each wait 10ms for a token.
+        let per_robot_rate = tasks_per_robot as f64 / (result.elapsed_ms / 1000.0);
+        assert!(per_robot_rate <= 110.0, "per-robot rate {per_robot_rate}/s");
+    }
+
+    #[test]
+    fn checksum_matches_for_the_same_task_set() {
+        let run = |robots, tasks_per_robot, zones_total| {
+            benchmark_once(&BenchConfig {
+                robots,
+                tasks_per_robot,
+                zones_total,
+                work_ms: 0,
+                ..BenchConfig::default()
+            })
+            .task_checksum
+        };
+        // The same 100 task ids, split and scheduled differently.
+        let four_robots = run(4, 25, 2);
+        assert_eq!(four_robots, run(2, 50, 3));
+        assert_eq!(four_robots, run(4, 25, 2));
+        assert_ne!(four_robots, run(3, 33, 2));
+    }
+
+    #[test]
+    fn trace_out_writes_begin_end_pair_per_task() {
+        let path = std::env::temp_dir().join(format!("blaze-trace-{}.json", std::process::id()));
+        benchmark_once(&BenchConfig {
+            robots: 3,
+            tasks_per_robot: 4,
+            zones_total: 2,
+            work_ms: 0,
+            trace_out: Some(path.clone()),
+            ..BenchConfig::default()
+        });
+        let text = std::fs::read_to_string(&path).expect("trace written");
+        let _ = std::fs::remove_file(&path);
+        let doc: serde_json::Value = serde_json::from_str(&text).expect("valid JSON");
+        let events = doc["traceEvents"].as_array().expect("traceEvents array");
+        let mut phases: HashMap<u64, Vec<String>> = HashMap::new();
+        for event in events {
+            let task = event["args"]["task"].as_u64().expect("task id");
+            let phase = event["ph"].as_str().expect("phase").to_string();
+            phases.entry(task).or_default().push(phase);
+        }
+        assert_eq!(phases.len(), 12);
+        assert!(phases.values().all(|phases| phases == &["B", "E"]));
+    }
+
+    #[test]
+    fn knee_point_stops_at_diminishing_returns() {
+        let samples = [(1, 100.0), (2, 190.0), (4, 360.0), (8, 380.0), (16, 385.0)];
+        // 4 -> 8 gains only ~5%, so 4 robots is the knee.
+        assert_eq!(knee_point(&samples), 4);
+        assert_eq!(knee_point(&[(1, 10.0), (2, 30.0)]), 2);
+    }
+
+    #[test]
+    fn autotune_recommends_a_swept_count() {
+        let base = BenchConfig {
+            tasks_per_robot: 2,
+            work_ms: 1,
+            ..BenchConfig::default()
+        };
+        let (results, recommended) = autotune_collect(4, &base);
+        let swept: Vec<usize> = results.iter().map(|result| result.robots).collect();
+        assert_eq!(swept, vec![1, 2, 4]);
+        assert!(swept.contains(&recommended));
+    }
+
+    #[test]
+    fn health_scenario_detects_planned_robots() {
+        let plan = OfflinePlan {
+            stopped: [1, 3].into_iter().collect(),
+            stop_after: Duration::from_millis(50),
+        };
+        let timeout = Duration::from_millis(HEALTH_TIMEOUT_MS);
+        let report = run_health_scenario(4, &plan, timeout);
+        assert_eq!(report.true_positives, plan.stopped.len());
+        assert_eq!(report.false_negatives, 0);
+        assert_eq!(report.false_positives, 0);
+        // Detection can never precede the timeout window.
+        assert!(report.avg_detection_latency_ms >= HEALTH_TIMEOUT_MS as f64);
+    }
+
+    #[test]
+    fn demo_timeout_aborts_robots_waiting_on_missing_tasks() {
+        let started = Instant::now();
+        let options = DemoOptions {
+            timeout: Some(Duration::from_millis(600)),
+            ..DemoOptions::default()
+        };
+        let (summary, _) = demo_run(&options, 2);
+        assert!(summary.aborted);
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert_eq!(summary.per_robot_done.iter().sum::<usize>(), 7);
+        assert!(!summary.zone_violation);
+
+        let options = DemoOptions {
+            timeout: Some(Duration::from_secs(10)),
+            ..DemoOptions::default()
+        };
+        let (summary, _) = demo_run(&options, 0);
+        assert!(!summary.aborted);
+    }
+
+    #[test]
+    fn demo_collect_reports_offline_robot_without_violation() {
+        let summary = run_demo_collect();
+        assert_eq!(
+            summary.per_robot_done.iter().sum::<usize>(),
+            summary.tasks_total
+        );
+        assert!(!summary.zone_violation);
+        assert!(!summary.offline_robots.is_empty());
+    }
 }
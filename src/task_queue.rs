@@ -1,48 +1,549 @@
-//! Thread-safe FIFO task queue with blocking and non-blocking consumers.
+//! Thread-safe FIFO (or LIFO) task queue with blocking and non-blocking
+//! consumers.
 
-use std::collections::VecDeque;
-use std::sync::{Condvar, Mutex};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use std::thread;
+use std::time::{Duration, Instant};
 
-use crate::types::Task;
+use crate::sync::{AtomicBool, AtomicU64, Condvar, Mutex};
+use crate::types::{RobotId, Task, TaskId, ZoneId};
 
-/// A minimal, synchronized FIFO queue for robot tasks.
+/// How pushes wake blocked consumers.
+///
+/// Both are safe because consumers re-check the queue after every wake:
+/// `One` avoids a thundering herd on single pushes, while `All` wakes every
+/// waiter at once, which suits bursty `push_many` loads.
+#[allow(dead_code)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NotifyStrategy {
+    /// Wake one consumer per pushed task.
+    One,
+    /// Wake every blocked consumer on each push.
+    All,
+}
+
+/// Which end of the queue consumers take from.
+///
+/// LIFO hands out the task pushed most recently, whose data is likeliest to
+/// still be warm in cache, at the cost of leaving older tasks waiting longer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum QueueDiscipline {
+    /// Oldest ready task first (the default).
+    #[default]
+    Fifo,
+    /// Newest ready task first.
+    Lifo,
+}
+
+impl std::str::FromStr for QueueDiscipline {
+    type Err = String;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        match raw {
+            "fifo" => Ok(Self::Fifo),
+            "lifo" => Ok(Self::Lifo),
+            other => Err(format!("unknown queue discipline: {other}")),
+        }
+    }
+}
+
+// Caps backoff at 2^6 spin hints between empty checks.
+const MAX_BACKOFF_SHIFT: u32 = 6;
+
+type EmptyCallback = Arc<dyn Fn() + Send + Sync>;
+
+/// Point-in-time queue totals from `TaskQueue::counts`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QueueCounts {
+    pub len: usize,
+    pub pushed: u64,
+    pub popped: u64,
+}
+
+/// A minimal, synchronized FIFO queue for robot tasks; see
+/// `new_with_discipline` for LIFO.
 pub struct TaskQueue {
     inner: Mutex<TaskQueueState>,
     available: Condvar,
+    // Signaled whenever a pop leaves the queue empty.
+    drained: Condvar,
+    notify: NotifyStrategy,
+    // Kept outside `inner` so the callback can run without the queue lock.
+    on_empty: Mutex<Option<EmptyCallback>>,
+    // Waits that returned with still nothing to take.
+    spurious_wakeups: AtomicU64,
+    // Set once dependency tracking starts; read without the lock so
+    // `complete` costs nothing on queues that never see dependencies.
+    tracks_dependencies: AtomicBool,
 }
 
 struct TaskQueueState {
     queue: VecDeque<Task>,
     closed: bool,
+    // Popped via `try_pop_for` but not yet acked, keyed by task id.
+    in_flight: HashMap<TaskId, (RobotId, Task)>,
+    // Tasks each budgeted robot may still take; unlisted robots are unlimited.
+    budgets: HashMap<RobotId, usize>,
+    // Lifetime totals; redeliveries count as pushes again.
+    pushed: u64,
+    popped: u64,
+    // Only filled while dependencies are tracked: every id pushed (or queued
+    // when tracking began), so dependencies on tasks that already left the
+    // queue still resolve, and every id passed to `complete`.
+    known: HashSet<TaskId>,
+    completed: HashSet<TaskId>,
+    tracking: bool,
+    discipline: QueueDiscipline,
+}
+
+impl TaskQueueState {
+    fn enqueue(&mut self, task: Task) {
+        if self.tracking {
+            self.known.insert(task.id);
+        }
+        self.queue.push_back(task);
+        self.pushed += 1;
+    }
+
+    // Take the first ready task in discipline order; tasks still waiting on
+    // dependencies are skipped and keep their place.
+    fn dequeue(&mut self) -> Option<Task> {
+        let index = self.next_ready()?;
+        let task = self.queue.remove(index)?;
+        self.popped += 1;
+        Some(task)
+    }
+
+    // Start recording ids, seeded with the tasks queued or in flight now.
+    // Tasks that left the queue before this are forgotten.
+    fn start_tracking(&mut self) {
+        if self.tracking {
+            return;
+        }
+        self.tracking = true;
+        let queued = self.queue.iter().map(|task| task.id);
+        let known: HashSet<TaskId> = queued.chain(self.in_flight.keys().copied()).collect();
+        self.known = known;
+    }
+
+    fn is_ready(&self, task: &Task) -> bool {
+        task.depends_on.iter().all(|id| self.completed.contains(id))
+    }
+
+    fn next_ready(&self) -> Option<usize> {
+        self.next_where(|_| true)
+    }
+
+    fn next_where(&self, accepts: impl Fn(&Task) -> bool) -> Option<usize> {
+        let ready = |task: &Task| self.is_ready(task) && accepts(task);
+        match self.discipline {
+            QueueDiscipline::Fifo => self.queue.iter().position(ready),
+            QueueDiscipline::Lifo => self.queue.iter().rposition(ready),
+        }
+    }
+
+    // Why `incoming` could not be enqueued, if some task in it could never
+    // become ready.
+    fn dependency_error(&self, incoming: &[Task]) -> Option<String> {
+        if let Some((task, id)) = self.unknown_dependency(incoming) {
+            return Some(format!("task {task} depends on unknown task {id}"));
+        }
+        self.cycle_through(incoming)
+            .map(|task| format!("task {task} is part of a dependency cycle"))
+    }
+
+    // Whether `id` was pushed while tracking, or is queued or in flight when
+    // tracking has not started yet.
+    fn is_known(&self, id: TaskId) -> bool {
+        if self.tracking {
+            self.known.contains(&id)
+        } else {
+            self.queue.iter().any(|task| task.id == id) || self.in_flight.contains_key(&id)
+        }
+    }
+
+    // The first task in `incoming` (with the missing id) that depends on an
+    // id never pushed or completed and not in the batch itself.
+    fn unknown_dependency(&self, incoming: &[Task]) -> Option<(TaskId, TaskId)> {
+        incoming.iter().find_map(|task| {
+            task.depends_on
+                .iter()
+                .find(|&&id| {
+                    !self.is_known(id)
+                        && !self.completed.contains(&id)
+                        && !incoming.iter().any(|other| other.id == id)
+                })
+                .map(|&id| (task.id, id))
+        })
+    }
+
+    // The first task in `incoming` that would wait, directly or through
+    // others, on itself once enqueued.
+    fn cycle_through(&self, incoming: &[Task]) -> Option<TaskId> {
+        let mut edges: HashMap<TaskId, &[TaskId]> = HashMap::new();
+        for task in self.queue.iter().chain(incoming) {
+            if !task.depends_on.is_empty() {
+                edges.insert(task.id, &task.depends_on);
+            }
+        }
+        incoming
+            .iter()
+            .filter(|task| !task.depends_on.is_empty())
+            .find(|task| {
+                let mut stack: Vec<TaskId> = task.depends_on.clone();
+                let mut visited = HashSet::new();
+                while let Some(id) = stack.pop() {
+                    if id == task.id {
+                        return true;
+                    }
+                    if visited.insert(id)
+                        && let Some(deps) = edges.get(&id)
+                    {
+                        stack.extend_from_slice(deps);
+                    }
+                }
+                false
+            })
+            .map(|task| task.id)
+    }
+
+    // Take one unit of `robot`'s budget; false once it is spent.
+    fn charge(&mut self, robot: RobotId) -> bool {
+        match self.budgets.get_mut(&robot) {
+            Some(0) => false,
+            Some(left) => {
+                *left -= 1;
+                true
+            }
+            None => true,
+        }
+    }
+
+    fn budget_spent(&self, robot: RobotId) -> bool {
+        self.budgets.get(&robot) == Some(&0)
+    }
 }
 
 impl TaskQueue {
     /// Create an empty task queue.
     pub fn new() -> Self {
+        Self::new_with_notify(NotifyStrategy::One)
+    }
+
+    /// Create an empty task queue with an explicit wakeup strategy.
+    pub fn new_with_notify(notify: NotifyStrategy) -> Self {
+        Self::build(notify, QueueDiscipline::Fifo)
+    }
+
+    /// Create an empty task queue that hands out tasks in `discipline` order.
+    pub fn new_with_discipline(discipline: QueueDiscipline) -> Self {
+        Self::build(NotifyStrategy::One, discipline)
+    }
+
+    fn build(notify: NotifyStrategy, discipline: QueueDiscipline) -> Self {
         Self {
             inner: Mutex::new(TaskQueueState {
                 queue: VecDeque::new(),
                 closed: false,
+                in_flight: HashMap::new(),
+                budgets: HashMap::new(),
+                pushed: 0,
+                popped: 0,
+                known: HashSet::new(),
+                completed: HashSet::new(),
+                tracking: false,
+                discipline,
             }),
             available: Condvar::new(),
+            drained: Condvar::new(),
+            notify,
+            on_empty: Mutex::new(None),
+            spurious_wakeups: AtomicU64::new(0),
+            tracks_dependencies: AtomicBool::new(false),
+        }
+    }
+
+    /// Track dependencies from now on: remember every pushed id and every
+    /// `complete`d one, so a task pushed later may depend on them.
+    ///
+    /// Tracking also starts by itself at the first push carrying
+    /// dependencies, but then only tasks still queued or in flight are
+    /// known; call this up front when dependents may arrive after the
+    /// tasks they wait on have finished.
+    #[allow(dead_code)]
+    pub fn track_dependencies(&self) {
+        let mut guard = self.inner.lock().expect("task queue mutex poisoned");
+        guard.start_tracking();
+        self.tracks_dependencies.store(true, Ordering::SeqCst);
+    }
+
+    /// Whether dependency tracking has started; lock-free.
+    pub fn tracks_dependencies(&self) -> bool {
+        self.tracks_dependencies.load(Ordering::SeqCst)
+    }
+
+    /// Register a callback fired when a pop drains the last task from an
+    /// open queue; replaces any earlier callback.
+    #[allow(dead_code)]
+    pub fn on_empty(&self, callback: impl Fn() + Send + Sync + 'static) {
+        let mut slot = self.on_empty.lock().expect("on_empty mutex poisoned");
+        *slot = Some(Arc::new(callback));
+    }
+
+    // Wake drain waiters if that pop emptied the queue; returns whether the
+    // empty callback should fire, which is for open queues only.
+    fn note_pop(&self, state: &TaskQueueState) -> bool {
+        if !state.queue.is_empty() {
+            return false;
+        }
+        self.drained.notify_all();
+        !state.closed
+    }
+
+    // Run the empty callback, if any, after the queue lock has been dropped.
+    fn fire_on_empty(&self) {
+        let callback = self
+            .on_empty
+            .lock()
+            .expect("on_empty mutex poisoned")
+            .clone();
+        if let Some(callback) = callback {
+            callback();
         }
     }
 
-    /// Push a task; returns the task back if the queue is closed.
-    pub fn push(&self, task: Task) -> Result<(), Task> {
+    // Wake consumers for `pushed` new tasks according to the strategy.
+    fn notify_pushed(&self, pushed: usize) {
+        match self.notify {
+            NotifyStrategy::One => {
+                for _ in 0..pushed {
+                    self.available.notify_one();
+                }
+            }
+            NotifyStrategy::All => self.available.notify_all(),
+        }
+    }
+
+    /// Push a task; returns the task back if the queue is closed or it
+    /// depends on itself or on an id unknown to dependency tracking (see
+    /// `track_dependencies`).
+    pub fn push(&self, mut task: Task) -> Result<(), Task> {
         let mut guard = self.inner.lock().expect("task queue mutex poisoned");
         if guard.closed {
             return Err(task);
         }
-        guard.queue.push_back(task);
-        self.available.notify_one();
+        if !task.depends_on.is_empty() {
+            guard.start_tracking();
+            self.tracks_dependencies.store(true, Ordering::SeqCst);
+            let incoming = std::slice::from_ref(&task);
+            if guard.dependency_error(incoming).is_some() {
+                return Err(task);
+            }
+        }
+        task.enqueued_at = Some(Instant::now());
+        guard.enqueue(task);
+        self.notify_pushed(1);
         Ok(())
     }
 
+    /// Push a batch under one lock; returns the batch back if the queue is
+    /// closed, a dependency names an id neither known to the queue nor in
+    /// the batch, or the batch's dependencies would form a cycle.
+    pub fn push_many(&self, tasks: Vec<Task>) -> Result<(), Vec<Task>> {
+        let mut guard = self.inner.lock().expect("task queue mutex poisoned");
+        if guard.closed {
+            return Err(tasks);
+        }
+        if tasks.iter().any(|task| !task.depends_on.is_empty()) {
+            guard.start_tracking();
+            self.tracks_dependencies.store(true, Ordering::SeqCst);
+            if guard.dependency_error(&tasks).is_some() {
+                return Err(tasks);
+            }
+        }
+        let pushed = tasks.len();
+        let now = Instant::now();
+        for mut task in tasks {
+            task.enqueued_at = Some(now);
+            guard.enqueue(task);
+        }
+        self.notify_pushed(pushed);
+        Ok(())
+    }
+
+    /// Explain why `push_many(tasks)` would reject the batch for its
+    /// dependencies, naming the offending task; `Ok` when it would not.
+    pub fn check_dependencies(&self, tasks: &[Task]) -> Result<(), String> {
+        let guard = self.inner.lock().expect("task queue mutex poisoned");
+        match guard.dependency_error(tasks) {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// Record `id` as finished so tasks depending on it become ready, and
+    /// wake consumers that may now have one to take.
+    ///
+    /// A lock-free no-op until dependency tracking starts, so callers can
+    /// report every completion without slowing queues that have none. Once
+    /// tracking, a task pushed later with `id` as a dependency is ready at
+    /// once.
+    pub fn complete(&self, id: TaskId) {
+        if !self.tracks_dependencies() {
+            return;
+        }
+        let mut guard = self.inner.lock().expect("task queue mutex poisoned");
+        guard.completed.insert(id);
+        let unblocked = guard.queue.iter().any(|task| task.depends_on.contains(&id));
+        drop(guard);
+        if unblocked {
+            self.available.notify_all();
+        }
+    }
+
     /// Try to pop immediately without blocking.
     pub fn try_pop(&self) -> Option<Task> {
         let mut guard = self.inner.lock().expect("task queue mutex poisoned");
-        guard.queue.pop_front()
+        let task = guard.dequeue()?;
+        let emptied = self.note_pop(&guard);
+        drop(guard);
+        if emptied {
+            self.fire_on_empty();
+        }
+        Some(task)
+    }
+
+    /// Pop the first queued task (in discipline order) whose category is in
+    /// `accepts`, leaving other non-matching tasks in place. Untagged tasks match any robot.
+    ///
+    /// Never blocks: when only other categories are queued this returns
+    /// `None` instead of waiting, so a filtered robot cannot park forever on a
+    /// queue it will never drain.
+    #[allow(dead_code)]
+    pub fn pop_matching(&self, accepts: &HashSet<String>) -> Option<Task> {
+        self.pop_first_where(|task| {
+            task.category
+                .as_ref()
+                .is_none_or(|category| accepts.contains(category))
+        })
+    }
+
+    /// Pop the next ready task targeting `zone` (see `Task::target_zone`),
+    /// skipping tasks for other zones. Never blocks.
+    pub fn pop_for_zone(&self, zone: ZoneId, zones_total: u64) -> Option<Task> {
+        self.pop_first_where(|task| task.target_zone(zones_total) == zone)
+    }
+
+    fn pop_first_where(&self, accepts: impl Fn(&Task) -> bool) -> Option<Task> {
+        let mut guard = self.inner.lock().expect("task queue mutex poisoned");
+        let index = guard.next_where(accepts)?;
+        let task = guard.queue.remove(index)?;
+        guard.popped += 1;
+        let emptied = self.note_pop(&guard);
+        drop(guard);
+        if emptied {
+            self.fire_on_empty();
+        }
+        Some(task)
+    }
+
+    /// Poll for a task up to `spins + 1` times, backing off exponentially and
+    /// yielding the CPU between empty checks. Returns `None` once the budget
+    /// is spent so the caller can fall back to `pop_blocking_or_closed`.
+    #[allow(dead_code)]
+    pub fn try_pop_backoff(&self, spins: u32) -> Option<Task> {
+        self.try_pop_backoff_with(spins, thread::yield_now)
+    }
+
+    // `on_yield` is the hook between checks; tests count calls through it.
+    fn try_pop_backoff_with(&self, spins: u32, mut on_yield: impl FnMut()) -> Option<Task> {
+        for attempt in 0..=spins {
+            if let Some(task) = self.try_pop() {
+                return Some(task);
+            }
+            if attempt == spins {
+                break;
+            }
+            for _ in 0..(1u32 << attempt.min(MAX_BACKOFF_SHIFT)) {
+                std::hint::spin_loop();
+            }
+            on_yield();
+        }
+        None
+    }
+
+    /// Pop without blocking and hold the task in flight for `robot` until it
+    /// is acked; unacked tasks can be redelivered with `requeue_unacked_for`.
+    /// Returns `None` once the robot's budget (see `set_budget`) is spent.
+    #[allow(dead_code)]
+    pub fn try_pop_for(&self, robot: RobotId) -> Option<Task> {
+        let mut guard = self.inner.lock().expect("task queue mutex poisoned");
+        if guard.next_ready().is_none() || !guard.charge(robot) {
+            return None;
+        }
+        let task = guard.dequeue()?;
+        guard.in_flight.insert(task.id, (robot, task.clone()));
+        let emptied = self.note_pop(&guard);
+        drop(guard);
+        if emptied {
+            self.fire_on_empty();
+        }
+        Some(task)
+    }
+
+    /// Mark an in-flight task as done; returns false if it was not in flight.
+    #[allow(dead_code)]
+    pub fn ack(&self, id: TaskId) -> bool {
+        let mut guard = self.inner.lock().expect("task queue mutex poisoned");
+        guard.in_flight.remove(&id).is_some()
+    }
+
+    /// Return every unacked task held by `robot` to the tail of the queue;
+    /// returns how many were redelivered. Closed queues keep them in flight.
+    #[allow(dead_code)]
+    pub fn requeue_unacked_for(&self, robot: RobotId) -> usize {
+        let mut guard = self.inner.lock().expect("task queue mutex poisoned");
+        if guard.closed {
+            return 0;
+        }
+        let mut ids: Vec<TaskId> = guard
+            .in_flight
+            .iter()
+            .filter(|(_, (holder, _))| *holder == robot)
+            .map(|(id, _)| *id)
+            .collect();
+        // Keep redelivery order deterministic.
+        ids.sort_unstable();
+        let now = Instant::now();
+        for id in &ids {
+            if let Some((_, mut task)) = guard.in_flight.remove(id) {
+                task.enqueued_at = Some(now);
+                guard.enqueue(task);
+            }
+        }
+        self.notify_pushed(ids.len());
+        ids.len()
+    }
+
+    /// Number of tasks popped for a robot and not yet acked.
+    #[allow(dead_code)]
+    pub fn in_flight_len(&self) -> usize {
+        let guard = self.inner.lock().expect("task queue mutex poisoned");
+        guard.in_flight.len()
+    }
+
+    /// Try to pop immediately, also reporting how long the task was queued.
+    #[allow(dead_code)]
+    pub fn pop_with_age(&self) -> Option<(Task, Duration)> {
+        let task = self.try_pop()?;
+        let age = task
+            .enqueued_at
+            .map(|stamped| stamped.elapsed())
+            .unwrap_or_default();
+        Some((task, age))
     }
 
     #[deprecated(note = "use pop_blocking_or_closed for shutdown-aware waits")]
@@ -54,24 +555,161 @@ impl TaskQueue {
     /// Block until a task is available or the queue is closed.
     pub fn pop_blocking_or_closed(&self) -> Option<Task> {
         let mut guard = self.inner.lock().expect("task queue mutex poisoned");
+        let mut woken = false;
         loop {
-            if let Some(task) = guard.queue.pop_front() {
+            if let Some(task) = guard.dequeue() {
+                let emptied = self.note_pop(&guard);
+                drop(guard);
+                if emptied {
+                    self.fire_on_empty();
+                }
                 return Some(task);
             }
             if guard.closed {
                 return None;
             }
+            if woken {
+                self.spurious_wakeups.fetch_add(1, Ordering::Relaxed);
+            }
             // Wait releases the lock and re-acquires it before returning.
             guard = self.available.wait(guard).expect("condvar wait failed");
+            woken = true;
+        }
+    }
+
+    /// Cap how many more tasks `robot` may take via the `*_for` pops, as if
+    /// the queue closed for that robot alone once the budget is spent.
+    #[allow(dead_code)]
+    pub fn set_budget(&self, robot: RobotId, tasks: usize) {
+        let mut guard = self.inner.lock().expect("task queue mutex poisoned");
+        guard.budgets.insert(robot, tasks);
+        // A robot blocked in `pop_blocking_for` may now be out of budget.
+        self.available.notify_all();
+    }
+
+    /// Block until a task is available for `robot`, or return `None` once the
+    /// queue is closed or the robot's budget is spent.
+    #[allow(dead_code)]
+    pub fn pop_blocking_for(&self, robot: RobotId) -> Option<Task> {
+        let mut guard = self.inner.lock().expect("task queue mutex poisoned");
+        let mut woken = false;
+        loop {
+            if guard.budget_spent(robot) {
+                return None;
+            }
+            if guard.next_ready().is_some() {
+                guard.charge(robot);
+                let task = guard.dequeue()?;
+                let emptied = self.note_pop(&guard);
+                drop(guard);
+                if emptied {
+                    self.fire_on_empty();
+                }
+                return Some(task);
+            }
+            if guard.closed {
+                return None;
+            }
+            if woken {
+                self.spurious_wakeups.fetch_add(1, Ordering::Relaxed);
+            }
+            guard = self.available.wait(guard).expect("condvar wait failed");
+            woken = true;
         }
     }
 
-    /// Close the queue and wake all blocked consumers.
+    /// Blocking pops woken with nothing to take: true spurious wakeups plus
+    /// wakes where another consumer took the task first.
+    pub fn spurious_wakeups(&self) -> u64 {
+        self.spurious_wakeups.load(Ordering::Relaxed)
+    }
+
+    /// Close the queue and wake all blocked consumers. Returns true only for
+    /// the call that actually closed it, so racing closers can tell who won.
     #[allow(dead_code)]
-    pub fn close(&self) {
+    pub fn close(&self) -> bool {
         let mut guard = self.inner.lock().expect("task queue mutex poisoned");
+        if guard.closed {
+            return false;
+        }
         guard.closed = true;
         self.available.notify_all();
+        true
+    }
+
+    /// Block until the queue is empty or `timeout` passes (`None` waits
+    /// forever). Returns whether it was empty before the timeout.
+    ///
+    /// Only the queue itself is watched: tasks popped but still being worked
+    /// on count as drained.
+    #[allow(dead_code)]
+    pub fn wait_until_empty(&self, timeout: Option<Duration>) -> bool {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let mut guard = self.inner.lock().expect("task queue mutex poisoned");
+        while !guard.queue.is_empty() {
+            guard = match deadline {
+                None => self.drained.wait(guard).expect("condvar wait failed"),
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return false;
+                    }
+                    self.drained
+                        .wait_timeout(guard, deadline - now)
+                        .expect("condvar wait failed")
+                        .0
+                }
+            };
+        }
+        true
+    }
+
+    /// Ids of queued tasks in pop order, without removing anything.
+    #[allow(dead_code)]
+    pub fn snapshot_ids(&self) -> Vec<TaskId> {
+        let guard = self.inner.lock().expect("task queue mutex poisoned");
+        guard.queue.iter().map(|task| task.id).collect()
+    }
+
+    /// Clone every queued task in pop order, without draining, as a
+    /// checkpoint for `import` (serializable with the `serde` feature).
+    ///
+    /// Dependencies already completed are dropped from the copies, so they
+    /// stay ready in a fresh queue that never saw those completions.
+    #[allow(dead_code)]
+    pub fn export(&self) -> Vec<Task> {
+        let guard = self.inner.lock().expect("task queue mutex poisoned");
+        guard
+            .queue
+            .iter()
+            .map(|task| {
+                let mut task = task.clone();
+                task.depends_on.retain(|id| !guard.completed.contains(id));
+                task
+            })
+            .collect()
+    }
+
+    /// Restore an `export`ed checkpoint by appending it in order, usually to
+    /// a fresh queue. Tasks are restamped as they are enqueued; returns them
+    /// back if the queue is closed or, like `push_many`, a dependency is
+    /// unknown. Dependencies on tasks that were popped but not completed at
+    /// export time are unknown to a fresh queue; `complete` them first or
+    /// push them again.
+    #[allow(dead_code)]
+    pub fn import(&self, tasks: Vec<Task>) -> Result<(), Vec<Task>> {
+        self.push_many(tasks)
+    }
+
+    /// Queue length and lifetime push/pop totals, read under one lock so
+    /// they always agree with each other.
+    pub fn counts(&self) -> QueueCounts {
+        let guard = self.inner.lock().expect("task queue mutex poisoned");
+        QueueCounts {
+            len: guard.queue.len(),
+            pushed: guard.pushed,
+            popped: guard.popped,
+        }
     }
 
     /// Current number of queued tasks.
@@ -81,6 +719,23 @@ impl TaskQueue {
     }
 }
 
+impl Default for TaskQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Summarize instead of dumping queued tasks.
+impl fmt::Debug for TaskQueue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let guard = self.inner.lock().expect("task queue mutex poisoned");
+        f.debug_struct("TaskQueue")
+            .field("len", &guard.queue.len())
+            .field("closed", &guard.closed)
+            .finish()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -91,6 +746,7 @@ mod tests {
     use std::time::Duration;
 
     #[test]
+    #[allow(clippy::while_let_loop)]
     fn tasks_are_consumed_once() {
         let queue = Arc::new(TaskQueue::new());
         let total_tasks = 100;
@@ -133,6 +789,59 @@ mod tests {
         assert_eq!(queue.len(), 0);
     }
 
+    #[test]
+    fn backoff_consumers_take_every_task_and_yield_when_empty() {
+        use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+        let queue = Arc::new(TaskQueue::new());
+        let total_tasks = 200u64;
+        let producing = Arc::new(AtomicBool::new(true));
+        let yields = Arc::new(AtomicUsize::new(0));
+        let seen: Arc<Mutex<HashSet<u64>>> = Arc::new(Mutex::new(HashSet::new()));
+
+        let mut handles = Vec::new();
+        for _ in 0..4 {
+            let queue = Arc::clone(&queue);
+            let producing = Arc::clone(&producing);
+            let yields = Arc::clone(&yields);
+            let seen = Arc::clone(&seen);
+            handles.push(thread::spawn(move || {
+                loop {
+                    let popped = queue.try_pop_backoff_with(8, || {
+                        yields.fetch_add(1, Ordering::SeqCst);
+                        thread::yield_now();
+                    });
+                    match popped {
+                        Some(task) => {
+                            let mut guard = seen.lock().expect("seen mutex poisoned");
+                            assert!(guard.insert(task.id));
+                        }
+                        None if !producing.load(Ordering::SeqCst) && queue.len() == 0 => break,
+                        None => {}
+                    }
+                }
+            }));
+        }
+
+        // Trickle tasks in so consumers regularly find the queue empty.
+        for id in 0..total_tasks {
+            queue
+                .push(Task::new(id, format!("task-{id}")))
+                .expect("task queue closed");
+            if id % 20 == 0 {
+                thread::sleep(Duration::from_millis(1));
+            }
+        }
+        producing.store(false, Ordering::SeqCst);
+        for handle in handles {
+            handle.join().expect("consumer thread panicked");
+        }
+
+        let guard = seen.lock().expect("seen mutex poisoned");
+        assert_eq!(guard.len(), total_tasks as usize);
+        assert!(yields.load(Ordering::SeqCst) > 0, "consumers never yielded");
+    }
+
     #[test]
     fn pop_blocking_wakes_on_push() {
         let queue = Arc::new(TaskQueue::new());
@@ -212,6 +921,45 @@ mod tests {
         assert_eq!(queue.len(), 0);
     }
 
+    #[test]
+    fn wait_until_empty_returns_after_the_last_pop() {
+        let queue = Arc::new(TaskQueue::new());
+        assert!(queue.wait_until_empty(None));
+        let tasks = (0..20)
+            .map(|id| Task::new(id, format!("task-{id}")))
+            .collect();
+        queue.push_many(tasks).expect("task queue closed");
+        assert!(!queue.wait_until_empty(Some(Duration::from_millis(10))));
+
+        let consumer_queue = Arc::clone(&queue);
+        let consumer = thread::spawn(move || {
+            while consumer_queue.try_pop().is_some() {
+                thread::sleep(Duration::from_millis(1));
+            }
+        });
+        assert!(queue.wait_until_empty(None));
+        assert_eq!(queue.counts().popped, 20);
+        consumer.join().expect("consumer thread panicked");
+    }
+
+    #[test]
+    fn discipline_picks_which_end_consumers_take_from() {
+        let drain = |discipline| {
+            let queue = TaskQueue::new_with_discipline(discipline);
+            for id in 0..5 {
+                queue
+                    .push(Task::new(id, format!("task-{id}")))
+                    .expect("task queue closed");
+            }
+            queue.close();
+            std::iter::from_fn(|| queue.pop_blocking_or_closed())
+                .map(|task| task.id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(drain(QueueDiscipline::Fifo), vec![0, 1, 2, 3, 4]);
+        assert_eq!(drain(QueueDiscipline::Lifo), vec![4, 3, 2, 1, 0]);
+    }
+
     #[test]
     fn pop_blocking_or_closed_unblocks_on_close() {
         let queue = Arc::new(TaskQueue::new());
@@ -237,6 +985,375 @@ mod tests {
         handle.join().expect("consumer thread panicked");
     }
 
+    #[test]
+    fn close_reports_only_the_first_effective_call() {
+        let queue = TaskQueue::new();
+        assert!(queue.close());
+        assert!(!queue.close());
+        assert!(queue.push(Task::new(1, "late")).is_err());
+    }
+
+    #[test]
+    fn pop_with_age_reports_queue_wait() {
+        let queue = TaskQueue::new();
+        queue.push(Task::new(1, "aged")).expect("task queue closed");
+        thread::sleep(Duration::from_millis(30));
+        let (task, age) = queue.pop_with_age().expect("task missing");
+        assert!(age >= Duration::from_millis(30), "age too small: {age:?}");
+        // The enqueue stamp must not affect equality.
+        assert_eq!(task, Task::new(1, "aged"));
+    }
+
+    #[test]
+    fn pop_matching_skips_other_categories() {
+        let queue = TaskQueue::new();
+        queue
+            .push_many(vec![
+                Task::new(1, "a").with_category("billing"),
+                Task::new(2, "b").with_category("search"),
+                Task::new(3, "c").with_category("billing"),
+                Task::new(4, "d"),
+            ])
+            .expect("task queue closed");
+        let accepts: HashSet<String> = ["search".to_string()].into();
+
+        assert_eq!(queue.pop_matching(&accepts).map(|t| t.id), Some(2));
+        // Untagged tasks are open to everyone.
+        assert_eq!(queue.pop_matching(&accepts).map(|t| t.id), Some(4));
+        // Only billing tasks remain: return rather than wait on them.
+        assert!(queue.pop_matching(&accepts).is_none());
+        assert_eq!(queue.snapshot_ids(), vec![1, 3]);
+    }
+
+    #[test]
+    fn notify_all_wakes_every_consumer_on_push_many() {
+        let queue = Arc::new(TaskQueue::new_with_notify(NotifyStrategy::All));
+        let consumers = 3;
+        let (ready_tx, ready_rx) = mpsc::channel();
+        let (done_tx, done_rx) = mpsc::channel();
+
+        let mut handles = Vec::new();
+        for _ in 0..consumers {
+            let queue = Arc::clone(&queue);
+            let ready_tx = ready_tx.clone();
+            let done_tx = done_tx.clone();
+            handles.push(thread::spawn(move || {
+                ready_tx.send(()).expect("ready");
+                let task = queue.pop_blocking_or_closed().expect("task queue closed");
+                done_tx.send(task.id).expect("done");
+            }));
+        }
+        for _ in 0..consumers {
+            ready_rx
+                .recv_timeout(Duration::from_secs(1))
+                .expect("ready recv");
+        }
+        // Give consumers a moment to park on the condvar.
+        thread::sleep(Duration::from_millis(20));
+
+        let batch = (0..consumers as u64)
+            .map(|id| Task::new(id, format!("batch-{id}")))
+            .collect();
+        queue.push_many(batch).expect("task queue closed");
+
+        let mut seen = HashSet::new();
+        for _ in 0..consumers {
+            let id = done_rx
+                .recv_timeout(Duration::from_secs(1))
+                .expect("done recv");
+            assert!(seen.insert(id));
+        }
+        for handle in handles {
+            handle.join().expect("consumer thread panicked");
+        }
+        assert_eq!(queue.len(), 0);
+    }
+
+    #[test]
+    fn default_matches_new_and_debug_shows_len() {
+        let queue = TaskQueue::default();
+        assert_eq!(queue.len(), 0);
+        queue.push(Task::new(1, "one")).expect("task queue closed");
+        queue.push(Task::new(2, "two")).expect("task queue closed");
+        assert_eq!(queue.try_pop().map(|task| task.id), Some(1));
+        assert_eq!(format!("{queue:?}"), "TaskQueue { len: 1, closed: false }");
+    }
+
+    #[test]
+    fn snapshot_ids_preserves_order_without_draining() {
+        let queue = TaskQueue::new();
+        for id in [3, 1, 2] {
+            queue
+                .push(Task::new(id, "snap"))
+                .expect("task queue closed");
+        }
+        assert_eq!(queue.snapshot_ids(), vec![3, 1, 2]);
+        assert_eq!(queue.len(), 3);
+    }
+
+    #[test]
+    fn export_then_import_restores_ids_and_order() {
+        let queue = TaskQueue::new();
+        for id in [5, 1, 9, 3] {
+            queue
+                .push(Task::new(id, format!("task-{id}")).with_zone(id % 2 + 1))
+                .expect("task queue closed");
+        }
+        let checkpoint = queue.export();
+        // Exporting leaves the queue untouched.
+        assert_eq!(queue.len(), 4);
+
+        let restored = TaskQueue::new();
+        restored
+            .import(checkpoint.clone())
+            .expect("task queue closed");
+        assert_eq!(restored.snapshot_ids(), vec![5, 1, 9, 3]);
+        assert_eq!(restored.export(), checkpoint);
+        assert_eq!(restored.try_pop().map(|task| task.zone), Some(Some(2)));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn exported_tasks_round_trip_through_json() {
+        let queue = TaskQueue::new();
+        queue
+            .push_many(vec![
+                Task::new(2, "b").with_category("cold"),
+                Task::new(7, "g"),
+            ])
+            .expect("task queue closed");
+        let json = serde_json::to_string(&queue.export()).expect("serialize checkpoint");
+        let tasks: Vec<Task> = serde_json::from_str(&json).expect("parse checkpoint");
+
+        let restored = TaskQueue::new();
+        restored.import(tasks).expect("task queue closed");
+        assert_eq!(restored.export(), queue.export());
+        assert_eq!(restored.snapshot_ids(), vec![2, 7]);
+    }
+
+    #[test]
+    fn dependent_task_waits_for_its_dependency_to_complete() {
+        let queue = Arc::new(TaskQueue::new());
+        queue
+            .push_many(vec![
+                Task::new(2, "b").with_dependencies([1]),
+                Task::new(1, "a"),
+            ])
+            .expect("task queue closed");
+        // B is at the front but not ready, so A jumps it.
+        assert_eq!(queue.try_pop().map(|task| task.id), Some(1));
+        assert!(queue.try_pop().is_none());
+        assert_eq!(queue.len(), 1);
+
+        let waiter = {
+            let queue = Arc::clone(&queue);
+            thread::spawn(move || queue.pop_blocking_or_closed())
+        };
+        thread::sleep(Duration::from_millis(20));
+        queue.complete(1);
+        let popped = waiter.join().expect("waiter panicked");
+        assert_eq!(popped.map(|task| task.id), Some(2));
+    }
+
+    #[test]
+    fn pop_for_zone_skips_other_zones() {
+        let queue = TaskQueue::new();
+        queue
+            .push_many(vec![
+                Task::new(1, "a").with_zone(1),
+                Task::new(2, "b").with_zone(2),
+                // No explicit zone: 4 % 2 + 1 targets zone 1.
+                Task::new(4, "c"),
+            ])
+            .expect("task queue closed");
+        assert_eq!(queue.pop_for_zone(2, 2).map(|task| task.id), Some(2));
+        assert!(queue.pop_for_zone(2, 2).is_none());
+        assert_eq!(queue.pop_for_zone(1, 2).map(|task| task.id), Some(1));
+        assert_eq!(queue.snapshot_ids(), vec![4]);
+    }
+
+    #[test]
+    fn dependency_cycles_are_rejected_at_push() {
+        let queue = TaskQueue::new();
+        assert!(
+            queue
+                .push(Task::new(1, "self").with_dependencies([1]))
+                .is_err()
+        );
+        queue.push(Task::new(1, "a")).expect("task queue closed");
+        // 2 -> 3 -> 4 -> 2 closes a loop inside the batch.
+        let rejected = queue
+            .push_many(vec![
+                Task::new(2, "b").with_dependencies([3, 1]),
+                Task::new(3, "c").with_dependencies([4]),
+                Task::new(4, "d").with_dependencies([2]),
+            ])
+            .expect_err("cycle accepted");
+        assert_eq!(rejected.len(), 3);
+        assert_eq!(queue.snapshot_ids(), vec![1]);
+    }
+
+    #[test]
+    fn dependencies_on_unknown_ids_are_rejected_at_push() {
+        let queue = TaskQueue::new();
+        assert!(
+            queue
+                .push(Task::new(1, "a").with_dependencies([99]))
+                .is_err()
+        );
+        // Forward references within one batch are fine.
+        queue
+            .push_many(vec![
+                Task::new(2, "b").with_dependencies([3]),
+                Task::new(3, "c"),
+            ])
+            .expect("known batch rejected");
+        assert_eq!(queue.try_pop().map(|task| task.id), Some(3));
+        // 3 has left the queue but is still known.
+        queue
+            .push(Task::new(4, "d").with_dependencies([3]))
+            .expect("popped dependency rejected");
+    }
+
+    #[test]
+    fn completion_before_the_dependent_push_still_counts() {
+        let queue = TaskQueue::new();
+        queue.track_dependencies();
+        queue.push(Task::new(1, "a")).expect("task queue closed");
+        let first = queue.try_pop().expect("task");
+        queue.complete(first.id);
+
+        queue
+            .push(Task::new(2, "b").with_dependencies([1]))
+            .expect("task queue closed");
+        queue.close();
+        assert_eq!(queue.pop_blocking_or_closed().map(|task| task.id), Some(2));
+    }
+
+    #[test]
+    fn untracked_queue_records_nothing_until_a_dependency_arrives() {
+        let queue = TaskQueue::new();
+        queue.push(Task::new(1, "a")).expect("task queue closed");
+        queue.push(Task::new(2, "b")).expect("task queue closed");
+        let first = queue.try_pop().expect("task");
+        queue.complete(first.id);
+        assert!(!queue.tracks_dependencies());
+        {
+            let guard = queue.inner.lock().unwrap();
+            assert!(guard.known.is_empty() && guard.completed.is_empty());
+        }
+
+        // Tracking starts here, knowing only the still-queued task 2: a
+        // dependent of the forgotten task 1 is refused rather than stranded.
+        assert!(
+            queue
+                .push(Task::new(3, "c").with_dependencies([1]))
+                .is_err()
+        );
+        assert!(queue.tracks_dependencies());
+        queue
+            .push(Task::new(4, "d").with_dependencies([2]))
+            .expect("queued dependency rejected");
+    }
+
+    #[test]
+    fn export_drops_completed_dependencies_for_a_fresh_queue() {
+        let queue = TaskQueue::new();
+        queue.push(Task::new(1, "a")).expect("task queue closed");
+        queue
+            .push(Task::new(2, "b").with_dependencies([1]))
+            .expect("task queue closed");
+        let first = queue.try_pop().expect("task");
+        queue.complete(first.id);
+
+        let checkpoint = queue.export();
+        assert!(checkpoint[0].depends_on.is_empty());
+        let restored = TaskQueue::new();
+        restored.import(checkpoint).expect("checkpoint rejected");
+        assert_eq!(restored.try_pop().map(|task| task.id), Some(2));
+    }
+
+    #[test]
+    fn on_empty_fires_once_when_last_task_popped() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let queue = TaskQueue::new();
+        queue.push(Task::new(1, "last")).expect("task queue closed");
+        let fired = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&fired);
+        queue.on_empty(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+        assert!(queue.try_pop().is_some());
+        assert!(queue.try_pop().is_none());
+        assert_eq!(fired.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn ack_clears_in_flight_and_requeue_redelivers_unacked() {
+        let queue = TaskQueue::new();
+        for id in 0..3 {
+            queue.push(Task::new(id, "ack")).expect("task queue closed");
+        }
+        let first = queue.try_pop_for(7).expect("task");
+        let second = queue.try_pop_for(7).expect("task");
+        let other = queue.try_pop_for(8).expect("task");
+        assert_eq!(queue.in_flight_len(), 3);
+        assert!(queue.ack(first.id));
+        assert!(!queue.ack(first.id));
+
+        assert_eq!(queue.requeue_unacked_for(7), 1);
+        assert_eq!(queue.snapshot_ids(), vec![second.id]);
+        // Robot 8's task is untouched.
+        assert_eq!(queue.in_flight_len(), 1);
+        assert!(queue.ack(other.id));
+    }
+
+    #[test]
+    fn budget_cuts_off_one_robot_while_others_continue() {
+        let queue = Arc::new(TaskQueue::new());
+        for id in 0..10 {
+            queue
+                .push(Task::new(id, "budget"))
+                .expect("task queue closed");
+        }
+        queue.set_budget(1, 3);
+
+        let budgeted = {
+            let queue = Arc::clone(&queue);
+            thread::spawn(move || {
+                let mut taken = 0;
+                while queue.pop_blocking_for(1).is_some() {
+                    taken += 1;
+                }
+                taken
+            })
+        };
+        // Robot 1 stops at its budget even though tasks remain.
+        assert_eq!(budgeted.join().expect("budgeted robot panicked"), 3);
+        assert_eq!(queue.len(), 7);
+
+        let mut taken = 0;
+        while queue.try_pop_for(2).is_some() {
+            taken += 1;
+        }
+        assert_eq!(taken, 7);
+        queue.close();
+        assert!(queue.pop_blocking_for(2).is_none());
+    }
+
+    #[test]
+    fn spurious_wakeups_stay_zero_without_contention() {
+        let queue = TaskQueue::new();
+        queue.push(Task::new(1, "a")).expect("open queue");
+        queue.push(Task::new(2, "b")).expect("open queue");
+        assert!(queue.pop_blocking_or_closed().is_some());
+        assert!(queue.pop_blocking_for(7).is_some());
+        queue.close();
+        assert!(queue.pop_blocking_or_closed().is_none());
+        assert_eq!(queue.spurious_wakeups(), 0);
+    }
+
     #[test]
     fn push_fails_after_close() {
         let queue = TaskQueue::new();
@@ -245,3 +1362,42 @@ mod tests {
         assert!(result.is_err());
     }
 }
+
+#[cfg(all(test, loom))]
+mod loom_tests {
+    use super::*;
+    use loom::sync::Arc;
+    use loom::thread;
+
+    #[test]
+    fn loom_two_consumers_take_each_task_once() {
+        loom::model(|| {
+            let queue = Arc::new(TaskQueue::new());
+            let consumers: Vec<_> = (0..2)
+                .map(|_| {
+                    let queue = Arc::clone(&queue);
+                    thread::spawn(move || {
+                        let mut taken = Vec::new();
+                        while let Some(task) = queue.pop_blocking_or_closed() {
+                            taken.push(task.id);
+                        }
+                        taken
+                    })
+                })
+                .collect();
+            for id in 0..2 {
+                queue
+                    .push(Task::new(id, "loom"))
+                    .expect("task queue closed");
+            }
+            queue.close();
+
+            let mut all: Vec<TaskId> = consumers
+                .into_iter()
+                .flat_map(|handle| handle.join().expect("loom consumer panicked"))
+                .collect();
+            all.sort_unstable();
+            assert_eq!(all, vec![0, 1]);
+        });
+    }
+}
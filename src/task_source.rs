@@ -0,0 +1,60 @@
+//! Pluggable task sources for the benchmark.
+//!
+//! A source hands out tasks one at a time, so a bench run can stream from a
+//! generator (possibly endless) instead of queueing every task up front.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::Mutex;
+
+use crate::types::Task;
+
+/// Yields tasks until exhausted; shared by every robot in a run, so `next`
+/// must hand each task out at most once.
+pub trait TaskSource: Send + Sync {
+    /// The next task, or `None` once the source is drained.
+    fn next(&self) -> Option<Task>;
+}
+
+impl fmt::Debug for dyn TaskSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TaskSource")
+    }
+}
+
+/// A fixed list served in order, e.g. tasks read by `tasks_file::load_tasks`.
+pub struct ListSource {
+    tasks: Mutex<VecDeque<Task>>,
+}
+
+impl ListSource {
+    pub fn new(tasks: Vec<Task>) -> Self {
+        Self {
+            tasks: Mutex::new(tasks.into()),
+        }
+    }
+}
+
+impl TaskSource for ListSource {
+    fn next(&self) -> Option<Task> {
+        self.tasks
+            .lock()
+            .expect("task source mutex poisoned")
+            .pop_front()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn list_source_yields_in_order_then_stops() {
+        let source = ListSource::new(vec![Task::new(3, "c"), Task::new(1, "a").with_zone(2)]);
+        assert_eq!(source.next().map(|task| task.id), Some(3));
+        let second = source.next().expect("second task");
+        assert_eq!((second.id, second.zone), (1, Some(2)));
+        assert!(source.next().is_none());
+        assert!(source.next().is_none());
+    }
+}
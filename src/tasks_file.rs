@@ -0,0 +1,160 @@
+//! Loader for task lists supplied as plain-text files.
+//!
+//! One task per line as `id,zone,description`. The zone may be left empty
+//! (or `-`) to fall back to the id-modulo rule. Blank lines and lines
+//! starting with `#` are ignored.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::types::{Task, TaskId, ZoneId};
+
+// Parse one line; `Ok(None)` for blanks and comments.
+fn parse_line(line_no: usize, raw: &str) -> Result<Option<Task>, String> {
+    let line = raw.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+    let mut fields = line.splitn(3, ',');
+    let id_field = fields.next().unwrap_or("").trim();
+    let id: TaskId = id_field
+        .parse()
+        .map_err(|_| format!("line {line_no}: invalid task id '{id_field}'"))?;
+    let zone_field = fields.next().unwrap_or("").trim();
+    let description = fields.next().unwrap_or("").trim();
+    let mut task = Task::new(id, description);
+    if !zone_field.is_empty() && zone_field != "-" {
+        let zone: ZoneId = zone_field
+            .parse()
+            .map_err(|_| format!("line {line_no}: invalid zone '{zone_field}'"))?;
+        if zone == 0 {
+            return Err(format!("line {line_no}: zones are numbered from 1"));
+        }
+        task = task.with_zone(zone);
+    }
+    Ok(Some(task))
+}
+
+/// Parse tasks from file contents; errors name the offending line.
+pub fn parse_tasks(text: &str) -> Result<Vec<Task>, String> {
+    let mut tasks = Vec::new();
+    for (index, raw) in text.lines().enumerate() {
+        if let Some(task) = parse_line(index + 1, raw)? {
+            tasks.push(task);
+        }
+    }
+    Ok(tasks)
+}
+
+/// Check every line, collecting all problems instead of stopping at the first.
+///
+/// Beyond format errors this flags duplicate ids and, when `zones_total` is
+/// given, explicit zones above it. Returns the task count when clean.
+pub fn validate_tasks(text: &str, zones_total: Option<ZoneId>) -> Result<usize, Vec<String>> {
+    let mut errors = Vec::new();
+    let mut first_seen: HashMap<TaskId, usize> = HashMap::new();
+    for (index, raw) in text.lines().enumerate() {
+        let line_no = index + 1;
+        let task = match parse_line(line_no, raw) {
+            Ok(Some(task)) => task,
+            Ok(None) => continue,
+            Err(err) => {
+                errors.push(err);
+                continue;
+            }
+        };
+        if let Some(first) = first_seen.insert(task.id, line_no) {
+            errors.push(format!(
+                "line {line_no}: duplicate task id {} (first on line {first})",
+                task.id
+            ));
+            // Keep pointing later duplicates at the original line.
+            first_seen.insert(task.id, first);
+        }
+        if let (Some(zone), Some(total)) = (task.zone, zones_total)
+            && zone > total
+        {
+            errors.push(format!(
+                "line {line_no}: zone {zone} out of range (zones=1..={total})"
+            ));
+        }
+    }
+    if errors.is_empty() {
+        Ok(first_seen.len())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Read and parse a tasks file.
+pub fn load_tasks(path: &Path) -> Result<Vec<Task>, String> {
+    let text =
+        fs::read_to_string(path).map_err(|err| format!("cannot read {}: {err}", path.display()))?;
+    parse_tasks(&text)
+}
+
+/// Validate a tasks file and report the outcome; false on any problem.
+pub fn run_validate(path: &Path, zones_total: Option<ZoneId>) -> bool {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) => {
+            eprintln!(
+                "validate-tasks error: cannot read {}: {err}",
+                path.display()
+            );
+            return false;
+        }
+    };
+    match validate_tasks(&text, zones_total) {
+        Ok(count) => {
+            println!("{}: {count} tasks ok", path.display());
+            true
+        }
+        Err(errors) => {
+            for err in &errors {
+                eprintln!("validate-tasks error: {err}");
+            }
+            eprintln!("{}: {} problems", path.display(), errors.len());
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_explicit_and_default_zones() {
+        let text = "# id,zone,description\n1,3,dock\n\n2,,shelf, aisle 4\n3,-,\n";
+        let tasks = parse_tasks(text).expect("valid tasks file");
+        assert_eq!(tasks.len(), 3);
+        assert_eq!(tasks[0].zone, Some(3));
+        assert_eq!(tasks[0].description, "dock");
+        assert_eq!(tasks[1].zone, None);
+        assert_eq!(tasks[1].description, "shelf, aisle 4");
+        assert_eq!(tasks[2].zone, None);
+    }
+
+    #[test]
+    fn rejects_bad_fields_with_line_number() {
+        let err = parse_tasks("1,2,ok\nx,1,bad").unwrap_err();
+        assert!(err.starts_with("line 2:"), "{err}");
+        let err = parse_tasks("1,0,zero").unwrap_err();
+        assert!(err.contains("numbered from 1"), "{err}");
+    }
+
+    #[test]
+    fn validate_reports_every_problem_line() {
+        let text = "1,1,a\n2,9,far\n1,2,again\nbad,1,x\n3,,ok\n";
+        let errors = validate_tasks(text, Some(4)).unwrap_err();
+        assert_eq!(errors.len(), 3, "{errors:?}");
+        assert!(errors[0].starts_with("line 2: zone 9 out of range"));
+        assert!(errors[1].starts_with("line 3: duplicate task id 1 (first on line 1)"));
+        assert!(errors[2].starts_with("line 4: invalid task id"));
+        // Without a zone count only the duplicate and the bad id remain.
+        assert_eq!(validate_tasks(text, None).unwrap_err().len(), 2);
+        assert_eq!(validate_tasks("1,1,a\n2,,b\n", Some(1)), Ok(2));
+    }
+}
@@ -1,19 +1,46 @@
 //! Shared identifiers and task model used across the system.
 
+use std::time::{Duration, Instant};
+
 /// Unique identifier for a task in the queue.
 pub type TaskId = u64;
 /// Unique identifier for a robot thread.
 pub type RobotId = u64;
 /// Unique identifier for a physical zone.
 pub type ZoneId = u64;
+/// Floor or section of a facility; zone ids repeat across sections.
+pub type SectionId = u64;
 
-/// Unit of work assigned to robots.
+/// How one processed task turned out, as reported by the robot that ran it.
 #[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TaskOutcome {
+    pub task_id: TaskId,
+    pub robot: RobotId,
+    pub success: bool,
+    /// From the pop to the zone release.
+    pub duration: Duration,
+}
+
+/// Unit of work assigned to robots.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Task {
     /// Stable task identifier for logging and validation.
     pub id: TaskId,
     /// Human-readable description for demo output.
     pub description: String,
+    /// Explicit destination zone; `None` falls back to the id-modulo rule.
+    pub zone: Option<ZoneId>,
+    /// Tenant category for `pop_matching`; `None` is accepted by every robot.
+    pub category: Option<String>,
+    /// Tasks that must be completed (see `TaskQueue::complete`) before the
+    /// queue hands this one out.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub depends_on: Vec<TaskId>,
+    /// Stamped by the queue on push; excluded from equality and from
+    /// serialized checkpoints.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub enqueued_at: Option<Instant>,
 }
 
 impl Task {
@@ -22,6 +49,49 @@ impl Task {
         Self {
             id,
             description: description.into(),
+            zone: None,
+            category: None,
+            depends_on: Vec::new(),
+            enqueued_at: None,
         }
     }
+
+    /// Pin the task to an explicit destination zone.
+    pub fn with_zone(mut self, zone: ZoneId) -> Self {
+        self.zone = Some(zone);
+        self
+    }
+
+    /// Tag the task with a tenant category.
+    #[allow(dead_code)]
+    pub fn with_category(mut self, category: impl Into<String>) -> Self {
+        self.category = Some(category.into());
+        self
+    }
+
+    /// Hold the task back until every task in `ids` is completed.
+    #[allow(dead_code)]
+    pub fn with_dependencies(mut self, ids: impl IntoIterator<Item = TaskId>) -> Self {
+        self.depends_on.extend(ids);
+        self
+    }
+
+    /// Zone this task must run in, given `zones_total` zones numbered from 1.
+    pub fn target_zone(&self, zones_total: u64) -> ZoneId {
+        self.zone.unwrap_or((self.id % zones_total) + 1)
+    }
 }
+
+// Equality compares every field except the enqueue timestamp, so a task
+// equals itself before and after a trip through the queue.
+impl PartialEq for Task {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+            && self.description == other.description
+            && self.zone == other.zone
+            && self.category == other.category
+            && self.depends_on == other.depends_on
+    }
+}
+
+impl Eq for Task {}
@@ -1,49 +1,320 @@
-//! Zone access control: ensures exclusive occupancy per zone.
+//! Zone access control: bounds occupancy per zone (exclusive by default).
 
-use std::collections::{HashMap, HashSet};
-use std::sync::{Condvar, Mutex};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant};
 
-use crate::types::{RobotId, ZoneId};
+use crate::sync::{AtomicU64, Condvar, Mutex, MutexGuard};
+use crate::types::{RobotId, SectionId, ZoneId};
+
+/// Outcome of `ZoneAccess::try_release`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReleaseResult {
+    /// The robot was inside the zone and has left it.
+    Released,
+    /// The zone is held, but not by this robot; `current` is its first occupant.
+    NotOwner { current: RobotId },
+    /// Nobody is inside the zone.
+    Unoccupied,
+}
+
+/// When a release wakes blocked acquirers.
+#[allow(dead_code)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ZoneNotifyPolicy {
+    /// Notify on every release.
+    Eager,
+    /// Notify at most once per `window`; waiters also re-check every
+    /// `window` on their own, so a skipped notification delays a waiter by
+    /// at most that long and never strands it.
+    Lazy { window: Duration },
+}
 
 /// Tracks zone ownership and blocks until zones become available.
 pub struct ZoneAccess {
-    occupied: Mutex<HashMap<ZoneId, RobotId>>,
+    state: Mutex<ZoneState>,
     available: Condvar,
+    // Wakeup accounting: notifications issued vs. wakes that found the zone busy.
+    notifications: AtomicU64,
+    wasted_wakeups: AtomicU64,
+    // Busy-wait with `yield_now` instead of sleeping on the condvar.
+    spinning: bool,
+    notify_policy: ZoneNotifyPolicy,
+}
+
+// Everything guarded by the zone lock, so reconfiguration is atomic with
+// respect to acquire/release.
+struct ZoneState {
+    // Robots inside each zone, in arrival order; empty zones are removed.
+    occupants: HashMap<ZoneId, Vec<RobotId>>,
+    // Robots allowed in a zone at once; zones not listed hold one robot.
+    capacity: HashMap<ZoneId, usize>,
+    // Zones sharing a group are mutually exclusive (e.g., a shared aisle).
+    exclusion_groups: Vec<HashSet<ZoneId>>,
+    // When each zone was last released, for least-recently-used picks.
+    last_released: HashMap<ZoneId, Instant>,
+    // When a release last notified, for `ZoneNotifyPolicy::Lazy`.
+    last_notified: Option<Instant>,
+    // When each current occupant entered its zone.
+    entered_at: HashMap<(ZoneId, RobotId), Instant>,
+}
+
+impl ZoneState {
+    // A zone is free when it has room and no other zone in its group is occupied.
+    fn is_free(&self, zone: ZoneId) -> bool {
+        let inside = self.occupants.get(&zone).map_or(0, Vec::len);
+        let capacity = self.capacity.get(&zone).copied().unwrap_or(1);
+        if inside >= capacity {
+            return false;
+        }
+        self.exclusion_groups
+            .iter()
+            .filter(|group| group.contains(&zone))
+            .all(|group| {
+                group
+                    .iter()
+                    .all(|member| *member == zone || !self.occupants.contains_key(member))
+            })
+    }
+
+    fn enter(&mut self, zone: ZoneId, robot: RobotId) {
+        self.occupants.entry(zone).or_default().push(robot);
+        self.entered_at.insert((zone, robot), Instant::now());
+    }
 }
 
 impl ZoneAccess {
     /// Create a new, empty zone-access controller.
     pub fn new() -> Self {
+        Self::with_exclusion_groups(Vec::new())
+    }
+
+    /// Create a controller where occupying any zone in a group blocks the
+    /// rest of that group.
+    pub fn with_exclusion_groups(groups: Vec<HashSet<ZoneId>>) -> Self {
         Self {
-            occupied: Mutex::new(HashMap::new()),
+            state: Mutex::new(ZoneState {
+                occupants: HashMap::new(),
+                capacity: HashMap::new(),
+                exclusion_groups: groups,
+                last_released: HashMap::new(),
+                last_notified: None,
+                entered_at: HashMap::new(),
+            }),
             available: Condvar::new(),
+            notifications: AtomicU64::new(0),
+            wasted_wakeups: AtomicU64::new(0),
+            spinning: false,
+            notify_policy: ZoneNotifyPolicy::Eager,
+        }
+    }
+
+    /// Create a controller whose releases wake waiters per `policy`.
+    /// Reconfiguration (capacity, exclusion groups) always notifies.
+    #[allow(dead_code)]
+    pub fn new_with_notify_policy(policy: ZoneNotifyPolicy) -> Self {
+        Self {
+            notify_policy: policy,
+            ..Self::new()
+        }
+    }
+
+    /// Create a controller whose blocking acquires spin on `try_acquire`,
+    /// yielding between attempts, instead of sleeping on a condvar.
+    ///
+    /// Exclusivity is unchanged; only the CPU cost of waiting differs.
+    pub fn new_spinning() -> Self {
+        Self {
+            spinning: true,
+            ..Self::new()
+        }
+    }
+
+    // Spin until `zone` is acquired or `deadline` passes.
+    fn spin_acquire(&self, zone: ZoneId, robot: RobotId, deadline: Option<Instant>) -> bool {
+        loop {
+            if self.try_acquire(zone, robot) {
+                return true;
+            }
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                return false;
+            }
+            std::thread::yield_now();
+        }
+    }
+
+    // Wake every waiter so each re-checks its zone against the new state.
+    fn notify_waiters(&self) {
+        self.available.notify_all();
+        self.notifications.fetch_add(1, Ordering::Relaxed);
+    }
+
+    // Release-time wakeup; the lazy policy skips it within the window.
+    fn notify_release(&self, state: &mut ZoneState) {
+        if let ZoneNotifyPolicy::Lazy { window } = self.notify_policy {
+            let now = Instant::now();
+            if state
+                .last_notified
+                .is_some_and(|at| now.duration_since(at) < window)
+            {
+                return;
+            }
+            state.last_notified = Some(now);
+        }
+        self.notify_waiters();
+    }
+
+    // Sleep until notified, `limit` passes, or (under the lazy policy) the
+    // window passes. Returns whether a notification rather than a timeout
+    // ended the wait.
+    fn wait_for_release<'a>(
+        &self,
+        guard: MutexGuard<'a, ZoneState>,
+        limit: Option<Duration>,
+    ) -> (MutexGuard<'a, ZoneState>, bool) {
+        let window = match self.notify_policy {
+            ZoneNotifyPolicy::Eager => None,
+            ZoneNotifyPolicy::Lazy { window } => Some(window),
+        };
+        let timeout = match (window, limit) {
+            (Some(window), Some(limit)) => Some(window.min(limit)),
+            (window, limit) => window.or(limit),
+        };
+        match timeout {
+            None => (
+                self.available.wait(guard).expect("condvar wait failed"),
+                true,
+            ),
+            Some(timeout) => {
+                let (guard, result) = self
+                    .available
+                    .wait_timeout(guard, timeout)
+                    .expect("condvar wait failed");
+                (guard, !result.timed_out())
+            }
         }
     }
 
     /// Acquire the zone for a robot, blocking until the zone is free.
     pub fn acquire(&self, zone: ZoneId, robot: RobotId) {
-        let mut guard = self.occupied.lock().expect("zone mutex poisoned");
+        if self.spinning {
+            self.spin_acquire(zone, robot, None);
+            return;
+        }
+        let mut guard = self.state.lock().expect("zone mutex poisoned");
+        let mut woken = false;
         loop {
-            if !guard.contains_key(&zone) {
-                guard.insert(zone, robot);
+            if guard.is_free(zone) {
+                guard.enter(zone, robot);
                 return;
             }
+            if woken {
+                self.wasted_wakeups.fetch_add(1, Ordering::Relaxed);
+            }
             // Wait releases the lock; on wake, re-check the condition.
-            guard = self.available.wait(guard).expect("condvar wait failed");
+            (guard, woken) = self.wait_for_release(guard, None);
+        }
+    }
+
+    /// Acquire the zone, waiting at most `timeout`; returns false on timeout.
+    pub fn acquire_timeout(&self, zone: ZoneId, robot: RobotId, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        if self.spinning {
+            return self.spin_acquire(zone, robot, Some(deadline));
+        }
+        let mut guard = self.state.lock().expect("zone mutex poisoned");
+        let mut woken = false;
+        loop {
+            if guard.is_free(zone) {
+                guard.enter(zone, robot);
+                return true;
+            }
+            if woken {
+                self.wasted_wakeups.fetch_add(1, Ordering::Relaxed);
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return false;
+            }
+            // Re-check after every wake; the deadline bounds the total wait.
+            // A timeout is not a notification, so it never counts as wasted.
+            (guard, woken) = self.wait_for_release(guard, Some(deadline - now));
+        }
+    }
+
+    /// Acquire whichever candidate zone is free, preferring the one released
+    /// longest ago (never-used zones first); blocks only while all are busy.
+    #[allow(dead_code)]
+    pub fn acquire_any_free(&self, candidates: &[ZoneId], robot: RobotId) -> ZoneId {
+        assert!(!candidates.is_empty(), "acquire_any_free needs candidates");
+        let mut guard = self.state.lock().expect("zone mutex poisoned");
+        let mut woken = false;
+        loop {
+            let pick = candidates
+                .iter()
+                .copied()
+                .filter(|&zone| guard.is_free(zone))
+                .min_by_key(|zone| guard.last_released.get(zone).copied());
+            if let Some(zone) = pick {
+                guard.enter(zone, robot);
+                return zone;
+            }
+            if self.spinning {
+                drop(guard);
+                std::thread::yield_now();
+                guard = self.state.lock().expect("zone mutex poisoned");
+                continue;
+            }
+            if woken {
+                self.wasted_wakeups.fetch_add(1, Ordering::Relaxed);
+            }
+            (guard, woken) = self.wait_for_release(guard, None);
+        }
+    }
+
+    /// Acquire the zone only if it is free right now; never blocks.
+    pub fn try_acquire(&self, zone: ZoneId, robot: RobotId) -> bool {
+        let mut guard = self.state.lock().expect("zone mutex poisoned");
+        if guard.is_free(zone) {
+            guard.enter(zone, robot);
+            return true;
+        }
+        false
+    }
+
+    /// Blocking acquires woken with their zone still busy; the same count as
+    /// the second half of `wakeup_stats`, named to match `TaskQueue`.
+    pub fn spurious_wakeups(&self) -> u64 {
+        self.wasted_wakeups.load(Ordering::Relaxed)
+    }
+
+    /// Acquire the zone like `acquire`, returning a guard that releases it on drop.
+    #[allow(dead_code)]
+    pub fn acquire_guard(&self, zone: ZoneId, robot: RobotId) -> ZoneGuard<'_> {
+        self.acquire(zone, robot);
+        ZoneGuard {
+            access: self,
+            zone,
+            robot,
         }
     }
 
     /// Release a zone; returns false if the caller is not the owner.
     pub fn release(&self, zone: ZoneId, robot: RobotId) -> bool {
-        let mut guard = self.occupied.lock().expect("zone mutex poisoned");
-        match guard.get(&zone) {
-            Some(owner) if *owner == robot => {
-                guard.remove(&zone);
-                // Wake all contenders so the next robot can acquire the zone.
-                self.available.notify_all();
-                true
-            }
-            Some(_) => {
+        match self.try_release(zone, robot) {
+            ReleaseResult::Released => true,
+            ReleaseResult::Unoccupied => {
+                // Releasing an unoccupied zone is also a caller error.
+                #[cfg(not(debug_assertions))]
+                {
+                    eprintln!("[ZONE] release on unoccupied zone: zone={zone}");
+                }
+                debug_assert!(false, "zone release on unoccupied zone: zone={zone}");
+                false
+            }
+            ReleaseResult::NotOwner { .. } => {
                 // Non-owner release indicates a logic error in the caller.
                 #[cfg(not(debug_assertions))]
                 {
@@ -55,22 +326,225 @@ impl ZoneAccess {
                 );
                 false
             }
-            None => {
-                // Releasing an unoccupied zone is also a caller error.
-                #[cfg(not(debug_assertions))]
-                {
-                    eprintln!("[ZONE] release on unoccupied zone: zone={zone}");
-                }
-                debug_assert!(false, "zone release on unoccupied zone: zone={zone}");
-                false
-            }
         }
     }
 
+    /// Release a zone, reporting why it failed instead of asserting; never
+    /// panics in any build profile.
+    pub fn try_release(&self, zone: ZoneId, robot: RobotId) -> ReleaseResult {
+        let mut guard = self.state.lock().expect("zone mutex poisoned");
+        let Some(inside) = guard.occupants.get_mut(&zone) else {
+            return ReleaseResult::Unoccupied;
+        };
+        let Some(position) = inside.iter().position(|occupant| *occupant == robot) else {
+            return ReleaseResult::NotOwner { current: inside[0] };
+        };
+        inside.remove(position);
+        if inside.is_empty() {
+            guard.occupants.remove(&zone);
+        }
+        guard.entered_at.remove(&(zone, robot));
+        guard.last_released.insert(zone, Instant::now());
+        // Wake all contenders so the next robot can acquire the zone.
+        self.notify_release(&mut guard);
+        ReleaseResult::Released
+    }
+
+    /// Let up to `capacity` robots share `zone` from now on.
+    ///
+    /// Lowering the capacity below the current occupancy evicts nobody; new
+    /// entries are refused until enough occupants have left.
+    #[allow(dead_code)]
+    pub fn add_zone_capacity(&self, zone: ZoneId, capacity: usize) {
+        let mut guard = self.state.lock().expect("zone mutex poisoned");
+        guard.capacity.insert(zone, capacity);
+        self.notify_waiters();
+    }
+
+    /// Make `group` mutually exclusive, replacing any existing group that
+    /// shares a zone with it. Current occupants are never evicted.
+    #[allow(dead_code)]
+    pub fn set_exclusion_group(&self, group: HashSet<ZoneId>) {
+        let mut guard = self.state.lock().expect("zone mutex poisoned");
+        guard
+            .exclusion_groups
+            .retain(|existing| existing.is_disjoint(&group));
+        if !group.is_empty() {
+            guard.exclusion_groups.push(group);
+        }
+        self.notify_waiters();
+    }
+
+    /// Current owner of a zone (its earliest occupant), if any.
+    pub fn owner(&self, zone: ZoneId) -> Option<RobotId> {
+        let guard = self.state.lock().expect("zone mutex poisoned");
+        guard
+            .occupants
+            .get(&zone)
+            .and_then(|inside| inside.first())
+            .copied()
+    }
+
+    /// How long the zone's current owner (see `owner`) has held it; `None`
+    /// for a free zone. A watchdog can flag implausibly long holds.
+    #[allow(dead_code)]
+    pub fn held_since(&self, zone: ZoneId) -> Option<Duration> {
+        let guard = self.state.lock().expect("zone mutex poisoned");
+        let owner = *guard.occupants.get(&zone)?.first()?;
+        guard
+            .entered_at
+            .get(&(zone, owner))
+            .map(|entered| entered.elapsed())
+    }
+
+    /// Wakeup efficiency as `(notifications, wasted_wakeups)`: condvar
+    /// notifications issued, and wakes that found the zone still busy.
+    #[allow(dead_code)]
+    pub fn wakeup_stats(&self) -> (u64, u64) {
+        (
+            self.notifications.load(Ordering::Relaxed),
+            self.wasted_wakeups.load(Ordering::Relaxed),
+        )
+    }
+
     /// Snapshot of zones that are currently occupied.
     pub fn occupied_zones(&self) -> HashSet<ZoneId> {
-        let guard = self.occupied.lock().expect("zone mutex poisoned");
-        guard.keys().copied().collect()
+        let guard = self.state.lock().expect("zone mutex poisoned");
+        guard.occupants.keys().copied().collect()
+    }
+}
+
+impl Default for ZoneAccess {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Summarize instead of dumping the ownership map.
+impl fmt::Debug for ZoneAccess {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let guard = self.state.lock().expect("zone mutex poisoned");
+        f.debug_struct("ZoneAccess")
+            .field("occupied", &guard.occupants.len())
+            .field("exclusion_groups", &guard.exclusion_groups.len())
+            .finish()
+    }
+}
+
+/// Zones namespaced by section (e.g. floor): `(section, zone)` keys, with
+/// each section run by its own `ZoneAccess`, so zone 3 on floor 1 and zone
+/// 3 on floor 2 never contend.
+///
+/// Sections are created on first use; `section` exposes one section's
+/// controller for its own occupancy and wakeup metrics.
+#[allow(dead_code)]
+#[derive(Debug, Default)]
+pub struct SectionedZoneAccess {
+    sections: Mutex<HashMap<SectionId, Arc<ZoneAccess>>>,
+}
+
+#[allow(dead_code)]
+impl SectionedZoneAccess {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The controller for `section`, created empty if not yet used.
+    pub fn section(&self, section: SectionId) -> Arc<ZoneAccess> {
+        let mut sections = self.sections.lock().expect("section map mutex poisoned");
+        Arc::clone(sections.entry(section).or_default())
+    }
+
+    /// Acquire `zone` on `section`, blocking only on that section.
+    pub fn acquire(&self, (section, zone): (SectionId, ZoneId), robot: RobotId) {
+        // The map lock is dropped before blocking so other sections proceed.
+        self.section(section).acquire(zone, robot);
+    }
+
+    /// Acquire like `acquire`, giving up after `timeout`.
+    pub fn acquire_timeout(
+        &self,
+        (section, zone): (SectionId, ZoneId),
+        robot: RobotId,
+        timeout: Duration,
+    ) -> bool {
+        self.section(section).acquire_timeout(zone, robot, timeout)
+    }
+
+    /// Acquire only if free right now; never blocks.
+    pub fn try_acquire(&self, (section, zone): (SectionId, ZoneId), robot: RobotId) -> bool {
+        self.section(section).try_acquire(zone, robot)
+    }
+
+    /// Release a zone; returns false if the caller is not the owner or the
+    /// section was never used (which is left uncreated).
+    pub fn release(&self, (section, zone): (SectionId, ZoneId), robot: RobotId) -> bool {
+        let access = self
+            .sections
+            .lock()
+            .expect("section map mutex poisoned")
+            .get(&section)
+            .map(Arc::clone);
+        access.is_some_and(|access| access.release(zone, robot))
+    }
+
+    /// Occupied zones in every section.
+    pub fn occupied_zones(&self) -> HashSet<(SectionId, ZoneId)> {
+        self.snapshot()
+            .into_iter()
+            .flat_map(|(section, access)| {
+                access
+                    .occupied_zones()
+                    .into_iter()
+                    .map(move |zone| (section, zone))
+            })
+            .collect()
+    }
+
+    /// Occupied zone count per section, for every section used so far.
+    pub fn occupancy_by_section(&self) -> BTreeMap<SectionId, usize> {
+        self.snapshot()
+            .into_iter()
+            .map(|(section, access)| (section, access.occupied_zones().len()))
+            .collect()
+    }
+
+    // Copy the map out so per-section locks are never taken under it.
+    fn snapshot(&self) -> Vec<(SectionId, Arc<ZoneAccess>)> {
+        let sections = self.sections.lock().expect("section map mutex poisoned");
+        sections
+            .iter()
+            .map(|(&section, access)| (section, Arc::clone(access)))
+            .collect()
+    }
+}
+
+/// Holds a zone for one robot and releases it exactly once when dropped,
+/// including while a panic unwinds.
+#[must_use = "dropping the guard releases the zone immediately"]
+#[derive(Debug)]
+pub struct ZoneGuard<'a> {
+    access: &'a ZoneAccess,
+    zone: ZoneId,
+    robot: RobotId,
+}
+
+#[allow(dead_code)]
+impl ZoneGuard<'_> {
+    pub fn zone(&self) -> ZoneId {
+        self.zone
+    }
+
+    pub fn robot(&self) -> RobotId {
+        self.robot
+    }
+}
+
+impl Drop for ZoneGuard<'_> {
+    fn drop(&mut self) {
+        // Never assert here: a second panic while unwinding would abort, and
+        // the zone may already have been released by hand.
+        let _ = self.access.try_release(self.zone, self.robot);
     }
 }
 
@@ -78,7 +552,7 @@ impl ZoneAccess {
 mod tests {
     use super::*;
     use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
-    use std::sync::{Arc, Barrier};
+    use std::sync::{Arc, Barrier, mpsc};
     use std::thread;
     use std::time::Duration;
 
@@ -132,6 +606,316 @@ mod tests {
         assert_eq!(max_occupancy.load(Ordering::SeqCst), 1);
     }
 
+    #[test]
+    fn spurious_wakeups_stay_zero_without_contention() {
+        let access = ZoneAccess::new();
+        access.acquire(1, 1);
+        assert!(access.release(1, 1));
+        assert!(access.acquire_timeout(2, 1, Duration::from_millis(5)));
+        assert!(access.release(2, 1));
+        assert_eq!(access.spurious_wakeups(), 0);
+    }
+
+    #[test]
+    fn zone_guard_releases_on_scope_end_and_unwind() {
+        let access = ZoneAccess::new();
+        {
+            let guard = access.acquire_guard(3, 1);
+            assert_eq!(guard.zone(), 3);
+            assert_eq!(access.owner(3), Some(1));
+            assert!(!access.try_acquire(3, 2));
+        }
+        assert_eq!(access.owner(3), None);
+
+        let unwound = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let _guard = access.acquire_guard(3, 2);
+            panic!("robot crashed inside the zone");
+        }));
+        assert!(unwound.is_err());
+        assert_eq!(access.owner(3), None);
+        assert!(access.try_acquire(3, 1));
+        assert!(access.release(3, 1));
+
+        // Released by hand first: the guard's own release is a quiet no-op.
+        let guard = access.acquire_guard(4, 1);
+        assert!(access.release(4, 1));
+        drop(guard);
+        assert_eq!(access.owner(4), None);
+    }
+
+    #[test]
+    fn spinning_variant_is_exclusive_under_contention() {
+        let access = Arc::new(ZoneAccess::new_spinning());
+        let contenders = 6;
+        let barrier = Arc::new(Barrier::new(contenders));
+        let occupancy = Arc::new(AtomicUsize::new(0));
+        let violation = Arc::new(AtomicBool::new(false));
+        let mut handles = Vec::new();
+        for robot_id in 0..contenders as u64 {
+            let access = Arc::clone(&access);
+            let barrier = Arc::clone(&barrier);
+            let occupancy = Arc::clone(&occupancy);
+            let violation = Arc::clone(&violation);
+            handles.push(thread::spawn(move || {
+                barrier.wait();
+                for _ in 0..20 {
+                    access.acquire(1, robot_id);
+                    if occupancy.fetch_add(1, Ordering::SeqCst) > 0 {
+                        violation.store(true, Ordering::SeqCst);
+                    }
+                    thread::sleep(Duration::from_micros(200));
+                    occupancy.fetch_sub(1, Ordering::SeqCst);
+                    assert!(access.release(1, robot_id));
+                }
+            }));
+        }
+        for handle in handles {
+            handle.join().expect("spinning thread panicked");
+        }
+        assert!(!violation.load(Ordering::SeqCst));
+        // Spinners never sleep on the condvar, so no wakeups are wasted.
+        assert_eq!(access.wakeup_stats().1, 0);
+    }
+
+    #[test]
+    fn try_acquire_fails_while_occupied() {
+        let access = ZoneAccess::new();
+        assert!(access.try_acquire(1, 1));
+        assert!(!access.try_acquire(1, 2));
+        assert!(access.release(1, 1));
+        assert!(access.try_acquire(1, 2));
+    }
+
+    #[test]
+    fn acquire_timeout_gives_up_while_occupied() {
+        let access = ZoneAccess::new();
+        access.acquire(1, 1);
+        let start = std::time::Instant::now();
+        assert!(!access.acquire_timeout(1, 2, Duration::from_millis(20)));
+        assert!(start.elapsed() >= Duration::from_millis(20));
+        assert!(access.release(1, 1));
+        assert!(access.acquire_timeout(1, 2, Duration::from_millis(20)));
+    }
+
+    #[test]
+    fn exclusion_group_blocks_sibling_zone() {
+        let group: HashSet<ZoneId> = [1, 2].into_iter().collect();
+        let access = Arc::new(ZoneAccess::with_exclusion_groups(vec![group]));
+        access.acquire(1, 1);
+        // Zone 2 is distinct but shares the group, so it must stay blocked.
+        assert!(!access.try_acquire(2, 2));
+        // Zones outside the group are unaffected.
+        assert!(access.try_acquire(3, 3));
+
+        let (done_tx, done_rx) = mpsc::channel();
+        let access_clone = Arc::clone(&access);
+        let handle = thread::spawn(move || {
+            access_clone.acquire(2, 2);
+            done_tx.send(()).expect("done");
+        });
+        assert!(
+            done_rx.recv_timeout(Duration::from_millis(50)).is_err(),
+            "zone 2 acquired while zone 1 was held"
+        );
+        assert!(access.release(1, 1));
+        done_rx
+            .recv_timeout(Duration::from_secs(1))
+            .expect("zone 2 never acquired");
+        handle.join().expect("group thread panicked");
+        assert!(access.release(2, 2));
+        assert!(access.release(3, 3));
+    }
+
+    #[test]
+    fn notify_all_wastes_wakeups_under_contention() {
+        let access = Arc::new(ZoneAccess::new());
+        let contenders = 8;
+        let barrier = Arc::new(Barrier::new(contenders));
+        let mut handles = Vec::new();
+        for robot_id in 0..contenders {
+            let access = Arc::clone(&access);
+            let barrier = Arc::clone(&barrier);
+            handles.push(thread::spawn(move || {
+                barrier.wait();
+                for _ in 0..20 {
+                    access.acquire(1, robot_id as u64);
+                    thread::sleep(Duration::from_millis(1));
+                    assert!(access.release(1, robot_id as u64));
+                }
+            }));
+        }
+        for handle in handles {
+            handle.join().expect("zone thread panicked");
+        }
+
+        let (notifications, wasted) = access.wakeup_stats();
+        assert_eq!(notifications, (contenders * 20) as u64);
+        // notify_all wakes every waiter but only one can win the zone.
+        assert!(wasted > 0, "expected wasted wakeups, got {wasted}");
+    }
+
+    #[test]
+    fn lazy_notify_coalesces_wakeups_under_churn() {
+        let churn = |policy| {
+            let access = Arc::new(ZoneAccess::new_with_notify_policy(policy));
+            let contenders = 4;
+            let rounds = 200;
+            let barrier = Arc::new(Barrier::new(contenders));
+            let occupancy = Arc::new(AtomicUsize::new(0));
+            let violation = Arc::new(AtomicBool::new(false));
+            let handles: Vec<_> = (0..contenders)
+                .map(|robot_id| {
+                    let access = Arc::clone(&access);
+                    let barrier = Arc::clone(&barrier);
+                    let occupancy = Arc::clone(&occupancy);
+                    let violation = Arc::clone(&violation);
+                    thread::spawn(move || {
+                        barrier.wait();
+                        for _ in 0..rounds {
+                            access.acquire(1, robot_id as u64);
+                            if occupancy.fetch_add(1, Ordering::SeqCst) > 0 {
+                                violation.store(true, Ordering::SeqCst);
+                            }
+                            occupancy.fetch_sub(1, Ordering::SeqCst);
+                            assert!(access.release(1, robot_id as u64));
+                        }
+                    })
+                })
+                .collect();
+            // Liveness: every contender finishes all of its rounds.
+            for handle in handles {
+                handle.join().expect("zone thread panicked");
+            }
+            assert!(!violation.load(Ordering::SeqCst), "zone shared");
+            assert!(access.occupied_zones().is_empty());
+            access.wakeup_stats().0
+        };
+
+        let eager = churn(ZoneNotifyPolicy::Eager);
+        let lazy = churn(ZoneNotifyPolicy::Lazy {
+            window: Duration::from_millis(5),
+        });
+        assert_eq!(eager, 800);
+        assert!(lazy < eager, "lazy {lazy} vs eager {eager} notifications");
+    }
+
+    #[test]
+    fn held_since_reports_the_owners_hold_time() {
+        let access = ZoneAccess::new();
+        assert_eq!(access.held_since(1), None);
+        access.acquire(1, 4);
+        thread::sleep(Duration::from_millis(40));
+        let held = access.held_since(1).expect("zone is held");
+        assert!(held >= Duration::from_millis(40), "held {held:?}");
+        assert!(access.release(1, 4));
+        assert_eq!(access.held_since(1), None);
+    }
+
+    #[test]
+    fn same_zone_on_two_floors_does_not_conflict() {
+        let floors = Arc::new(SectionedZoneAccess::new());
+        floors.acquire((1, 3), 1);
+        let other_floor = {
+            let floors = Arc::clone(&floors);
+            thread::spawn(move || floors.acquire_timeout((2, 3), 2, Duration::from_secs(1)))
+        };
+        assert!(other_floor.join().expect("floor thread panicked"));
+        // The same floor still conflicts.
+        assert!(!floors.try_acquire((1, 3), 3));
+
+        assert_eq!(floors.occupied_zones(), HashSet::from([(1, 3), (2, 3)]));
+        assert_eq!(
+            floors.occupancy_by_section(),
+            BTreeMap::from([(1, 1), (2, 1)])
+        );
+        assert!(floors.release((2, 3), 2));
+        assert!(floors.section(2).occupied_zones().is_empty());
+        assert!(floors.release((1, 3), 1));
+
+        // An unknown section has nothing to release and is not created.
+        assert!(!floors.release((9, 3), 1));
+        assert_eq!(
+            floors.occupancy_by_section(),
+            BTreeMap::from([(1, 0), (2, 0)])
+        );
+    }
+
+    #[test]
+    fn acquire_any_free_picks_the_free_candidate() {
+        let access = ZoneAccess::new();
+        access.acquire(1, 1);
+        access.acquire(2, 2);
+        // Zones 1 and 2 are held, so this must return 3 without blocking.
+        assert_eq!(access.acquire_any_free(&[1, 2, 3], 3), 3);
+        assert_eq!(access.owner(3), Some(3));
+    }
+
+    #[test]
+    fn acquire_any_free_prefers_least_recently_released() {
+        let access = ZoneAccess::new();
+        for zone in [1, 2] {
+            access.acquire(zone, 1);
+        }
+        assert!(access.release(2, 1));
+        thread::sleep(Duration::from_millis(2));
+        assert!(access.release(1, 1));
+        // Zone 2 was released first, so it is the least recently used.
+        assert_eq!(access.acquire_any_free(&[1, 2], 9), 2);
+    }
+
+    #[test]
+    fn raising_capacity_admits_a_blocked_robot() {
+        let access = Arc::new(ZoneAccess::new());
+        access.acquire(1, 1);
+
+        let (done_tx, done_rx) = mpsc::channel();
+        let access_clone = Arc::clone(&access);
+        let handle = thread::spawn(move || {
+            access_clone.acquire(1, 2);
+            done_tx.send(()).expect("done");
+        });
+        assert!(
+            done_rx.recv_timeout(Duration::from_millis(50)).is_err(),
+            "robot 2 entered a full zone"
+        );
+        // Widen the zone while robot 1 is still inside; robot 2 must get in.
+        access.add_zone_capacity(1, 2);
+        done_rx
+            .recv_timeout(Duration::from_secs(1))
+            .expect("robot 2 never acquired");
+        handle.join().expect("capacity thread panicked");
+        assert_eq!(access.owner(1), Some(1));
+        assert!(access.release(1, 1));
+        assert!(access.release(1, 2));
+    }
+
+    #[test]
+    fn lowering_capacity_keeps_occupants_but_blocks_entries() {
+        let access = ZoneAccess::new();
+        access.add_zone_capacity(1, 2);
+        assert!(access.try_acquire(1, 1));
+        assert!(access.try_acquire(1, 2));
+        access.add_zone_capacity(1, 1);
+        // Nobody is evicted, but the zone is over capacity until one leaves.
+        assert!(!access.try_acquire(1, 3));
+        assert!(access.release(1, 1));
+        assert!(!access.try_acquire(1, 3));
+        assert!(access.release(1, 2));
+        assert!(access.try_acquire(1, 3));
+    }
+
+    #[test]
+    fn set_exclusion_group_applies_to_later_entries() {
+        let access = ZoneAccess::new();
+        assert!(access.try_acquire(1, 1));
+        access.set_exclusion_group([1, 2].into_iter().collect());
+        assert!(!access.try_acquire(2, 2));
+        // Replacing the overlapping group lifts the 1/2 restriction.
+        access.set_exclusion_group([2, 3].into_iter().collect());
+        assert!(access.try_acquire(2, 2));
+        assert!(!access.try_acquire(3, 3));
+    }
+
     #[cfg(debug_assertions)]
     #[test]
     #[should_panic(expected = "zone release by non-owner")]
@@ -141,6 +925,20 @@ mod tests {
         let _ = access.release(1, 2);
     }
 
+    #[test]
+    fn try_release_reports_each_outcome_without_panicking() {
+        let access = ZoneAccess::new();
+        assert_eq!(access.try_release(1, 1), ReleaseResult::Unoccupied);
+        access.acquire(1, 1);
+        assert_eq!(
+            access.try_release(1, 2),
+            ReleaseResult::NotOwner { current: 1 }
+        );
+        assert!(access.occupied_zones().contains(&1));
+        assert_eq!(access.try_release(1, 1), ReleaseResult::Released);
+        assert!(access.occupied_zones().is_empty());
+    }
+
     #[cfg(not(debug_assertions))]
     #[test]
     fn release_by_non_owner_fails_and_keeps_zone() {
@@ -152,3 +950,35 @@ mod tests {
         assert!(access.release(1, 1));
     }
 }
+
+#[cfg(all(test, loom))]
+mod loom_tests {
+    use super::*;
+    use loom::sync::Arc;
+    use loom::sync::atomic::AtomicUsize;
+    use loom::thread;
+
+    #[test]
+    fn loom_zone_exclusive_with_two_robots() {
+        loom::model(|| {
+            let access = Arc::new(ZoneAccess::new());
+            let inside = Arc::new(AtomicUsize::new(0));
+            let handles: Vec<_> = (0..2)
+                .map(|robot| {
+                    let access = Arc::clone(&access);
+                    let inside = Arc::clone(&inside);
+                    thread::spawn(move || {
+                        access.acquire(1, robot);
+                        assert_eq!(inside.fetch_add(1, Ordering::SeqCst), 0);
+                        inside.fetch_sub(1, Ordering::SeqCst);
+                        assert!(access.release(1, robot));
+                    })
+                })
+                .collect();
+            for handle in handles {
+                handle.join().expect("loom robot panicked");
+            }
+            assert!(access.occupied_zones().is_empty());
+        });
+    }
+}
@@ -0,0 +1,538 @@
+//! CLI integration tests for the benchmark and stress modes.
+
+use std::process::Command;
+
+#[test]
+fn bench_trace_logs_each_task_lifecycle() {
+    let bin = env!("CARGO_BIN_EXE_project_blaze");
+    // One robot, two tasks, one zone, no work delay.
+    let output = Command::new(bin)
+        .args(["bench", "1", "2", "1", "0", "--trace"])
+        .output()
+        .expect("failed to run bench binary");
+    assert!(
+        output.status.success(),
+        "bench exited with non-zero status: {:?}",
+        output.status
+    );
+
+    // Trace lines go to stderr so the CSV on stdout stays clean.
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!stdout.contains("# trace"), "trace leaked into stdout");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    for task in 0..2 {
+        for event in ["popped", "acquired", "released"] {
+            let needle = format!("task={task},event={event}");
+            assert!(
+                stderr
+                    .lines()
+                    .any(|line| line.starts_with("# trace") && line.contains(&needle)),
+                "missing trace line for {needle}"
+            );
+        }
+    }
+}
+
+#[test]
+fn stress_max_duration_skips_remaining_configs() {
+    let bin = env!("CARGO_BIN_EXE_project_blaze");
+    // Four configs; each takes well over 1ms, so the budget trips after the first.
+    let output = Command::new(bin)
+        .args(["stress", "1,2,3,4", "2", "1", "0", "--max-duration", "1"])
+        .output()
+        .expect("failed to run stress binary");
+    assert!(
+        output.status.success(),
+        "stress exited with non-zero status: {:?}",
+        output.status
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // Header plus the configurations that actually ran.
+    let rows = stdout.lines().count() - 1;
+    assert!((1..=2).contains(&rows), "unexpected row count {rows}");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let skipped_line = stderr
+        .lines()
+        .find(|line| line.starts_with("# skipped,"))
+        .expect("skipped line missing");
+    let skipped: usize = skipped_line["# skipped,".len()..]
+        .trim()
+        .parse()
+        .expect("skipped count");
+    assert_eq!(rows + skipped, 4);
+}
+
+#[test]
+fn stress_configs_file_runs_only_listed_tuples() {
+    let bin = env!("CARGO_BIN_EXE_project_blaze");
+    let path = std::env::temp_dir().join(format!("blaze-configs-{}.csv", std::process::id()));
+    std::fs::write(&path, "# robots,tasks,zones,work_ms\n2,3,1,0\n1,4,2\n")
+        .expect("failed to write configs file");
+    let output = Command::new(bin)
+        .args(["stress", "-", "-", "-", "0", "--configs"])
+        .arg(&path)
+        .output()
+        .expect("failed to run stress binary");
+    let _ = std::fs::remove_file(&path);
+    assert!(
+        output.status.success(),
+        "stress exited with non-zero status: {:?}",
+        output.status
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let rows: Vec<Vec<&str>> = stdout
+        .lines()
+        .skip(1)
+        .map(|line| line.split(',').take(3).collect())
+        .collect();
+    assert_eq!(rows, vec![vec!["2", "3", "1"], vec!["1", "4", "2"]]);
+}
+
+#[test]
+fn stress_configs_file_respects_the_robot_cap() {
+    let path = std::env::temp_dir().join(format!("blaze-capped-{}.csv", std::process::id()));
+    std::fs::write(&path, "1,1,1\n1000000,1,1\n").expect("failed to write configs file");
+    let output = Command::new(env!("CARGO_BIN_EXE_project_blaze"))
+        .args([
+            "stress",
+            "-",
+            "-",
+            "-",
+            "0",
+            "--max-robots",
+            "2",
+            "--configs",
+        ])
+        .arg(&path)
+        .output()
+        .expect("failed to run stress binary");
+    let _ = std::fs::remove_file(&path);
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("stress: robots=1000000 exceeds --max-robots 2"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn stress_baseline_reports_speedup_over_first_config() {
+    let bin = env!("CARGO_BIN_EXE_project_blaze");
+    // Four robots on four zones do four times the work in the same time.
+    let output = Command::new(bin)
+        .args(["stress", "1,4", "8", "4", "10", "--baseline"])
+        .output()
+        .expect("failed to run stress binary");
+    assert!(
+        output.status.success(),
+        "stress exited with non-zero status: {:?}",
+        output.status
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut lines = stdout.lines();
+    assert!(lines.next().expect("header").ends_with(",speedup"));
+    let speedups: Vec<f64> = lines
+        .map(|line| line.rsplit(',').next().unwrap().parse().expect("speedup"))
+        .collect();
+    assert_eq!(speedups.len(), 2);
+    assert_eq!(speedups[0], 1.0);
+    assert!(speedups[1] > 1.0, "speedups {speedups:?}");
+}
+
+#[test]
+fn qbench_reports_queue_throughput() {
+    let output = Command::new(env!("CARGO_BIN_EXE_project_blaze"))
+        .args(["qbench", "2", "2", "500"])
+        .output()
+        .expect("failed to run qbench binary");
+    assert!(
+        output.status.success(),
+        "qbench exited with non-zero status: {:?}",
+        output.status
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(
+        lines[0],
+        "producers,consumers,pushed,popped,elapsed_ms,ops_per_s"
+    );
+    let cells: Vec<&str> = lines[1].split(',').collect();
+    assert_eq!(&cells[..4], &["2", "2", "1000", "1000"]);
+    let ops_per_s: f64 = cells[5].parse().expect("ops_per_s");
+    assert!(ops_per_s > 0.0);
+}
+
+#[test]
+fn bench_events_jsonl_streams_typed_objects() {
+    let bin = env!("CARGO_BIN_EXE_project_blaze");
+    let output = Command::new(bin)
+        .args(["bench", "2", "3", "1", "0", "--events-jsonl", "-"])
+        .output()
+        .expect("failed to run bench binary");
+    assert!(
+        output.status.success(),
+        "bench exited with non-zero status: {:?}",
+        output.status
+    );
+
+    // Events stream first; the CSV header and row follow once the run ends.
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    let (events, csv) = lines.split_at(lines.len() - 2);
+    assert!(csv[0].starts_with("robots,"), "CSV header missing");
+    // Six tasks, each popped, acquired, and released.
+    assert_eq!(events.len(), 18);
+    for line in events {
+        let value: serde_json::Value =
+            serde_json::from_str(line).unwrap_or_else(|err| panic!("bad JSON {line}: {err}"));
+        let kind = value["type"].as_str().expect("type field");
+        assert!(
+            ["popped", "acquired", "requeued", "released"].contains(&kind),
+            "unexpected type {kind}"
+        );
+    }
+}
+
+#[test]
+fn stress_table_format_aligns_columns() {
+    let bin = env!("CARGO_BIN_EXE_project_blaze");
+    let output = Command::new(bin)
+        .args(["stress", "1,2", "2", "1", "0", "--format", "table"])
+        .output()
+        .expect("failed to run stress binary");
+    assert!(
+        output.status.success(),
+        "stress exited with non-zero status: {:?}",
+        output.status
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    // Header, rule, then one row per configuration.
+    assert_eq!(lines.len(), 4, "unexpected table: {stdout}");
+    assert!(lines[0].trim_start().starts_with("robots"));
+    assert!(lines[1].chars().all(|c| c == '-' || c == ' '));
+    let width = lines[0].len();
+    for line in &lines[1..] {
+        assert_eq!(line.len(), width, "misaligned line: {line}");
+    }
+    // Right-justified cells end at the same offsets as their header names.
+    let header_ends: Vec<usize> = column_ends(lines[0]);
+    assert_eq!(column_ends(lines[2]), header_ends);
+}
+
+// Byte offsets where each whitespace-separated cell ends.
+fn column_ends(line: &str) -> Vec<usize> {
+    let bytes = line.as_bytes();
+    (0..bytes.len())
+        .filter(|&i| bytes[i] != b' ' && (i + 1 == bytes.len() || bytes[i + 1] == b' '))
+        .collect()
+}
+
+#[test]
+fn bench_min_throughput_gates_exit_status() {
+    let bin = env!("CARGO_BIN_EXE_project_blaze");
+    let run = |floor: &str| {
+        Command::new(bin)
+            .args(["bench", "2", "5", "2", "0", "--min-throughput", floor])
+            .output()
+            .expect("failed to run bench binary")
+    };
+
+    let failed = run("1000000000000");
+    assert_eq!(failed.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&failed.stderr);
+    assert!(
+        stderr
+            .lines()
+            .any(|line| line.starts_with("# gate,throughput,")
+                && line.ends_with(",1000000000000.00")),
+        "missing gate line: {stderr}"
+    );
+
+    let passed = run("1");
+    assert!(
+        passed.status.success(),
+        "bench exited with non-zero status: {:?}",
+        passed.status
+    );
+}
+
+#[test]
+fn bench_min_throughput_fails_when_no_run_happens() {
+    // One weight for two zones is rejected before any robot starts.
+    let output = Command::new(env!("CARGO_BIN_EXE_project_blaze"))
+        .args([
+            "bench",
+            "2",
+            "5",
+            "2",
+            "0",
+            "--zone-weights",
+            "1",
+            "--min-throughput",
+            "1",
+        ])
+        .output()
+        .expect("failed to run bench binary");
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("# gate,throughput,NA,1.00"), "{stderr}");
+}
+
+#[test]
+fn bench_refuses_robot_counts_over_the_cap() {
+    let bin = env!("CARGO_BIN_EXE_project_blaze");
+    let output = Command::new(bin)
+        .args(["bench", "1000000", "1", "1", "0"])
+        .output()
+        .expect("failed to run bench binary");
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("bench: robots=1000000 exceeds --max-robots 4096"),
+        "{stderr}"
+    );
+    assert!(!stderr.contains("# manifest"), "{stderr}");
+
+    let lowered = Command::new(bin)
+        .args(["bench", "3", "1", "1", "0", "--max-robots", "2"])
+        .output()
+        .expect("failed to run bench binary");
+    assert_eq!(lowered.status.code(), Some(2));
+}
+
+#[test]
+fn bench_strict_leftover_passes_a_drained_run() {
+    // Robots drain the whole queue, so there is nothing to fail on.
+    let output = Command::new(env!("CARGO_BIN_EXE_project_blaze"))
+        .args(["bench", "3", "4", "2", "0", "--strict-leftover"])
+        .output()
+        .expect("failed to run bench binary");
+    assert!(
+        output.status.success(),
+        "bench exited with non-zero status: {:?}",
+        output.status
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!stderr.contains("# error,leftover"), "{stderr}");
+}
+
+#[test]
+fn bench_manifest_names_the_build_commit() {
+    let bin = env!("CARGO_BIN_EXE_project_blaze");
+    let output = Command::new(bin)
+        .args(["bench", "1", "1", "1", "0"])
+        .output()
+        .expect("failed to run bench binary");
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let manifest = stderr
+        .lines()
+        .find(|line| line.starts_with("# manifest,"))
+        .expect("missing manifest line");
+    // The hash itself depends on the checkout; CI tarballs report `unknown`.
+    let commit = manifest
+        .split(',')
+        .find_map(|field| field.strip_prefix("commit="))
+        .expect("manifest has no commit field");
+    assert!(!commit.is_empty());
+}
+
+#[test]
+fn bench_binary_format_writes_one_framed_record() {
+    let bin = env!("CARGO_BIN_EXE_project_blaze");
+    let output = Command::new(bin)
+        .args(["bench", "1", "2", "1", "0", "--format", "binary"])
+        .output()
+        .expect("failed to run bench binary");
+    assert!(output.status.success());
+    let stdout = output.stdout;
+    assert_eq!(&stdout[..5], b"BLZR\x01");
+    let len = u32::from_le_bytes(stdout[5..9].try_into().unwrap()) as usize;
+    assert_eq!(stdout.len(), 9 + len, "expected exactly one record");
+    // The first field is the robot count.
+    assert_eq!(u64::from_le_bytes(stdout[9..17].try_into().unwrap()), 1);
+}
+
+#[test]
+fn bench_reads_robots_from_env_when_cli_omits_it() {
+    let bin = env!("CARGO_BIN_EXE_project_blaze");
+    let manifest = |args: &[&str]| {
+        let output = Command::new(bin)
+            .args(args)
+            .env("BLAZE_ROBOTS", "3")
+            .env("BLAZE_TASKS", "2")
+            .env("BLAZE_WORK_MS", "0")
+            .output()
+            .expect("failed to run bench binary");
+        assert!(output.status.success());
+        String::from_utf8_lossy(&output.stderr)
+            .lines()
+            .find(|line| line.starts_with("# manifest,"))
+            .expect("missing manifest line")
+            .to_string()
+    };
+
+    let from_env = manifest(&["bench"]);
+    assert!(from_env.contains(",robots=3,"), "{from_env}");
+    assert!(from_env.contains(",tasks_per_robot=2,"), "{from_env}");
+    // A CLI positional beats the environment.
+    let from_cli = manifest(&["bench", "2"]);
+    assert!(from_cli.contains(",robots=2,"), "{from_cli}");
+}
+
+#[test]
+fn stress_digest_totals_the_whole_sweep() {
+    let output = Command::new(env!("CARGO_BIN_EXE_project_blaze"))
+        .args(["stress", "1,2", "3", "1,2", "0"])
+        .output()
+        .expect("failed to run stress binary");
+    assert!(
+        output.status.success(),
+        "stress exited with non-zero status: {:?}",
+        output.status
+    );
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let digest = stderr
+        .lines()
+        .find(|line| line.starts_with("# digest,"))
+        .expect("missing digest line");
+    // Two robot counts by two zone counts; 3 + 3 + 6 + 6 tasks.
+    assert!(digest.contains(",configs=4,"), "{digest}");
+    assert!(digest.contains(",tasks=18,"), "{digest}");
+    assert!(digest.ends_with(",violations=0"), "{digest}");
+}
+
+#[test]
+fn stress_sort_by_orders_rows_by_throughput() {
+    let bin = env!("CARGO_BIN_EXE_project_blaze");
+    let output = Command::new(bin)
+        .args([
+            "stress",
+            "1,4,2",
+            "4",
+            "1,4",
+            "2",
+            "--sort-by",
+            "throughput_tasks_per_s:desc",
+        ])
+        .output()
+        .expect("failed to run stress binary");
+    assert!(
+        output.status.success(),
+        "stress exited with non-zero status: {:?}",
+        output.status
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut lines = stdout.lines();
+    let header: Vec<&str> = lines.next().expect("header").split(',').collect();
+    let column = header
+        .iter()
+        .position(|&name| name == "throughput_tasks_per_s")
+        .expect("throughput column");
+    let throughputs: Vec<f64> = lines
+        .map(|line| {
+            line.split(',')
+                .nth(column)
+                .unwrap()
+                .parse()
+                .expect("throughput")
+        })
+        .collect();
+    assert_eq!(throughputs.len(), 6);
+    assert!(
+        throughputs.windows(2).all(|pair| pair[0] >= pair[1]),
+        "not descending: {throughputs:?}"
+    );
+
+    let rejected = Command::new(bin)
+        .args(["stress", "1", "1", "1", "0", "--sort-by", "bogus"])
+        .output()
+        .expect("failed to run stress binary");
+    assert_eq!(rejected.status.code(), Some(2));
+}
+
+#[test]
+fn bench_fail_rate_reports_about_half_failures() {
+    let output = Command::new(env!("CARGO_BIN_EXE_project_blaze"))
+        .args([
+            "bench",
+            "4",
+            "50",
+            "2",
+            "0",
+            "--fail-rate",
+            "0.5",
+            "--seed",
+            "7",
+        ])
+        .output()
+        .expect("failed to run bench binary");
+    assert!(
+        output.status.success(),
+        "bench exited with non-zero status: {:?}",
+        output.status
+    );
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let line = stderr
+        .lines()
+        .find(|line| line.starts_with("# outcomes,"))
+        .expect("missing outcomes line");
+    let count = |key: &str| -> usize {
+        line.split(',')
+            .find_map(|field| field.strip_prefix(key))
+            .and_then(|value| value.parse().ok())
+            .unwrap_or_else(|| panic!("no {key} in {line}"))
+    };
+    let (succeeded, failed) = (count("succeeded="), count("failed="));
+    assert_eq!(succeeded + failed, 200);
+    assert!((70..=130).contains(&failed), "{line}");
+}
+
+#[test]
+fn bench_total_tasks_overrides_the_per_robot_share() {
+    // 4 robots with 1 task each would do 4; the budget raises it to 20.
+    let output = Command::new(env!("CARGO_BIN_EXE_project_blaze"))
+        .args([
+            "bench",
+            "4",
+            "1",
+            "2",
+            "0",
+            "--total-tasks",
+            "20",
+            "--strict-leftover",
+        ])
+        .output()
+        .expect("failed to run bench binary");
+    assert!(
+        output.status.success(),
+        "bench exited with non-zero status: {:?}",
+        output.status
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut lines = stdout.lines();
+    let header: Vec<&str> = lines.next().expect("header").split(',').collect();
+    let row: Vec<&str> = lines.next().expect("row").split(',').collect();
+    let column = header
+        .iter()
+        .position(|&name| name == "total_tasks")
+        .expect("total_tasks column");
+    assert_eq!(row[column], "20");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("# outcomes,succeeded=20,failed=0"),
+        "{stderr}"
+    );
+}
@@ -37,3 +37,57 @@ fn demo_cli_reports_offline_and_no_zone_violation() {
         .expect("offline_robots line missing");
     assert_ne!(offline_line.trim(), "offline_robots={}");
 }
+
+#[test]
+fn demo_timeline_writes_dot_schedule() {
+    let bin = env!("CARGO_BIN_EXE_project_blaze");
+    let path = std::env::temp_dir().join(format!("blaze-timeline-{}.dot", std::process::id()));
+    let output = Command::new(bin)
+        .arg("--timeline")
+        .arg(&path)
+        .output()
+        .expect("failed to run demo binary");
+    assert!(
+        output.status.success(),
+        "demo exited with non-zero status: {:?}",
+        output.status
+    );
+
+    let dot = std::fs::read_to_string(&path).expect("timeline file missing");
+    let _ = std::fs::remove_file(&path);
+    assert!(dot.starts_with("digraph timeline {"), "not a DOT digraph");
+    assert!(dot.trim_end().ends_with('}'), "unterminated DOT graph");
+    // The demo runs three robots over two zones.
+    for node in [
+        "\"robot-0\"",
+        "\"robot-1\"",
+        "\"robot-2\"",
+        "\"zone-1\"",
+        "\"zone-2\"",
+    ] {
+        assert!(dot.contains(node), "missing node {node}");
+    }
+    // One edge per task: 3 robots x 3 tasks.
+    assert_eq!(dot.matches(" -> ").count(), 9);
+}
+
+#[test]
+fn demo_without_offline_simulation_reports_no_offline_robots() {
+    let bin = env!("CARGO_BIN_EXE_project_blaze");
+    let output = Command::new(bin)
+        .arg("--no-offline-demo")
+        .output()
+        .expect("failed to run demo binary");
+    assert!(
+        output.status.success(),
+        "demo exited with non-zero status: {:?}",
+        output.status
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let offline_line = stdout
+        .lines()
+        .find(|line| line.starts_with("offline_robots="))
+        .expect("offline_robots line missing");
+    assert_eq!(offline_line.trim(), "offline_robots={}");
+}
@@ -0,0 +1,73 @@
+//! CLI integration tests for tasks file validation.
+
+use std::process::Command;
+
+#[test]
+fn validate_tasks_reports_line_errors_and_fails() {
+    let bin = env!("CARGO_BIN_EXE_project_blaze");
+    let path = std::env::temp_dir().join(format!("blaze-tasks-{}.csv", std::process::id()));
+    std::fs::write(
+        &path,
+        "# id,zone,description\n1,1,dock\n2,x,shelf\n1,2,repeat\n3,5,far\n",
+    )
+    .expect("failed to write tasks file");
+    let output = Command::new(bin)
+        .arg("validate-tasks")
+        .arg(&path)
+        .arg("4")
+        .output()
+        .expect("failed to run validate-tasks");
+    let _ = std::fs::remove_file(&path);
+
+    assert_eq!(output.status.code(), Some(1), "expected failure exit");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    for needle in [
+        "line 3: invalid zone 'x'",
+        "line 4: duplicate task id 1 (first on line 2)",
+        "line 5: zone 5 out of range",
+    ] {
+        assert!(stderr.contains(needle), "missing '{needle}' in:\n{stderr}");
+    }
+}
+
+#[test]
+fn validate_tasks_accepts_clean_file() {
+    let bin = env!("CARGO_BIN_EXE_project_blaze");
+    let path = std::env::temp_dir().join(format!("blaze-tasks-ok-{}.csv", std::process::id()));
+    std::fs::write(&path, "1,1,dock\n2,,shelf\n").expect("failed to write tasks file");
+    let output = Command::new(bin)
+        .arg("validate-tasks")
+        .arg(&path)
+        .output()
+        .expect("failed to run validate-tasks");
+    let _ = std::fs::remove_file(&path);
+
+    assert!(output.status.success(), "status: {:?}", output.status);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("2 tasks ok"), "stdout: {stdout}");
+}
+
+#[test]
+fn bench_draws_tasks_from_a_tasks_file() {
+    let bin = env!("CARGO_BIN_EXE_project_blaze");
+    let path = std::env::temp_dir().join(format!("blaze-tasks-bench-{}.csv", std::process::id()));
+    std::fs::write(&path, "1,1,dock\n2,2,shelf\n3,,charge\n").expect("failed to write tasks file");
+    // A share of 5 outlasts the file, so the run ends once it is drained.
+    let output = Command::new(bin)
+        .args(["bench", "1", "5", "2", "0", "--tasks-file"])
+        .arg(&path)
+        .output()
+        .expect("failed to run bench");
+    let _ = std::fs::remove_file(&path);
+
+    assert!(output.status.success(), "status: {:?}", output.status);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut lines = stdout.lines();
+    let header: Vec<&str> = lines.next().expect("header").split(',').collect();
+    let row: Vec<&str> = lines.next().expect("row").split(',').collect();
+    let column = header
+        .iter()
+        .position(|&name| name == "total_tasks")
+        .expect("total_tasks column");
+    assert_eq!(row[column], "3");
+}